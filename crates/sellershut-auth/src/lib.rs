@@ -530,9 +530,15 @@ async fn find_user_by_email(
             u.username,
             u.name,
             u.inbox,
+            u.shared_inbox as "shared_inbox: sellershut_core::Url",
+            u.hide_network,
+            u.deleted_at,
+            u.also_known_as as "also_known_as: Vec<sellershut_core::Url>",
+            u.moved_to as "moved_to: sellershut_core::Url",
             u.public_key,
             u.avatar,
-            u.private_key as "private_key: RedactedSecret",
+            -- private keys are encrypted, only sellershut-users reads them
+            null::text as "private_key: RedactedSecret",
             u.kind as "kind: ActorType",
             u.last_refreshed_at,
             u.created_at,
@@ -564,9 +570,15 @@ async fn find_user_by_identity(
             u.username,
             u.name,
             u.inbox,
+            u.shared_inbox as "shared_inbox: sellershut_core::Url",
+            u.hide_network,
+            u.deleted_at,
+            u.also_known_as as "also_known_as: Vec<sellershut_core::Url>",
+            u.moved_to as "moved_to: sellershut_core::Url",
             u.public_key,
             u.avatar,
-            u.private_key as "private_key: RedactedSecret",
+            -- private keys are encrypted, only sellershut-users reads them
+            null::text as "private_key: RedactedSecret",
             u.kind as "kind: ActorType",
             u.last_refreshed_at,
             u.created_at,
@@ -0,0 +1,13 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum CategoryError {
+    #[error("database error")]
+    Database(#[from] sqlx::Error),
+    #[error("invalid category: {0}")]
+    Invalid(&'static str),
+    #[error("slug is taken")]
+    SlugTaken,
+    #[error("category has children")]
+    HasChildren,
+}
@@ -0,0 +1,482 @@
+pub mod error;
+
+use std::{collections::BTreeMap, time::Duration};
+
+use sellershut_core::category::{Category, DEFAULT_LOCALE, descendant_ids};
+use sellershut_svc::cache::Cache;
+use sellershut_utilities::cache_key::CacheKey;
+use sqlx::PgConnection;
+use tracing::{debug, trace};
+use uuid::Uuid;
+
+use crate::error::CategoryError;
+
+const MAX_SLUG_LEN: usize = 64;
+const MAX_NAME_LEN: usize = 100;
+const MAX_LOCALES: usize = 50;
+
+pub const CATEGORY_TREE_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+pub struct CreateCategory {
+    pub id: Uuid,
+    pub parent_id: Option<Uuid>,
+    pub slug: String,
+    pub position: i32,
+    /// Names by locale, one in [`DEFAULT_LOCALE`] is required.
+    pub names: BTreeMap<String, String>,
+}
+
+/// Changes to a category, fields left `None` are kept as they are.
+#[derive(Default)]
+pub struct UpdateCategory {
+    /// `Some(None)` makes it a top level category.
+    pub parent_id: Option<Option<Uuid>>,
+    pub slug: Option<String>,
+    pub position: Option<i32>,
+    /// Replaces all of its names.
+    pub names: Option<BTreeMap<String, String>>,
+}
+
+impl CreateCategory {
+    pub fn validate(&self) -> Result<(), CategoryError> {
+        validate_slug(&self.slug)?;
+        validate_names(&self.names)
+    }
+}
+
+impl UpdateCategory {
+    pub fn validate(&self) -> Result<(), CategoryError> {
+        if let Some(slug) = &self.slug {
+            validate_slug(slug)?;
+        }
+        if let Some(names) = &self.names {
+            validate_names(names)?;
+        }
+        Ok(())
+    }
+}
+
+/// Lowercase words of ASCII letters and digits, joined by single hyphens.
+fn validate_slug(slug: &str) -> Result<(), CategoryError> {
+    let valid = slug.len() <= MAX_SLUG_LEN
+        && slug.split('-').all(|word| {
+            !word.is_empty()
+                && word
+                    .bytes()
+                    .all(|byte| byte.is_ascii_lowercase() || byte.is_ascii_digit())
+        });
+    if !valid {
+        return Err(CategoryError::Invalid(
+            "slug is not lowercase words joined by hyphens",
+        ));
+    }
+    Ok(())
+}
+
+fn validate_names(names: &BTreeMap<String, String>) -> Result<(), CategoryError> {
+    if !names.contains_key(DEFAULT_LOCALE) {
+        return Err(CategoryError::Invalid(
+            "name in the default locale is missing",
+        ));
+    }
+    if names.len() > MAX_LOCALES {
+        return Err(CategoryError::Invalid("too many names"));
+    }
+    for (locale, name) in names {
+        if !is_locale(locale) {
+            return Err(CategoryError::Invalid(
+                "locale is not a language tag like en or pt-BR",
+            ));
+        }
+        if name.trim().is_empty() {
+            return Err(CategoryError::Invalid("name is empty"));
+        }
+        if name.chars().count() > MAX_NAME_LEN {
+            return Err(CategoryError::Invalid("name is too long"));
+        }
+    }
+    Ok(())
+}
+
+/// A language, optionally with a region, like `en` or `pt-BR`.
+fn is_locale(locale: &str) -> bool {
+    let (language, region) = match locale.split_once('-') {
+        Some((language, region)) => (language, Some(region)),
+        None => (locale, None),
+    };
+
+    (2..=3).contains(&language.len())
+        && language.bytes().all(|byte| byte.is_ascii_lowercase())
+        && region.is_none_or(|region| {
+            region.len() == 2 && region.bytes().all(|byte| byte.is_ascii_uppercase())
+        })
+}
+
+#[async_trait::async_trait]
+pub trait CategoryDriver: Send + Sync {
+    /// Every category, ordered by position. Cached for [`CATEGORY_TREE_CACHE_TTL`].
+    async fn category_tree(&self) -> Result<Vec<Category>, CategoryError>;
+    async fn get_category(&self, id: Uuid) -> Result<Option<Category>, CategoryError>;
+    async fn create_category(&self, data: &CreateCategory) -> Result<Category, CategoryError>;
+    /// `None` if there is no such category.
+    async fn update_category(
+        &self,
+        id: Uuid,
+        data: &UpdateCategory,
+    ) -> Result<Option<Category>, CategoryError>;
+    /// `None` if there is no such category. Its listings are left without one.
+    async fn delete_category(&self, id: Uuid) -> Result<Option<Category>, CategoryError>;
+}
+
+pub struct CategoryService {
+    database: sqlx::PgPool,
+    cache: Cache,
+}
+
+#[async_trait::async_trait]
+impl CategoryDriver for CategoryService {
+    async fn category_tree(&self) -> Result<Vec<Category>, CategoryError> {
+        let cache_key = CacheKey::CategoryTree;
+
+        match self.cache.get::<Vec<u8>>(cache_key).await {
+            Ok(Some(cached)) => match serde_json::from_slice(&cached) {
+                Ok(tree) => return Ok(tree),
+                Err(error) => {
+                    debug!(cache_key = %cache_key, error = %error, "cached category tree is invalid");
+                }
+            },
+            Ok(None) => debug!(cache_key = %cache_key, "category tree cache miss"),
+            Err(error) => {
+                debug!(
+                    cache_key = %cache_key,
+                    error = %error,
+                    "cache read failed; falling back to database"
+                );
+            }
+        }
+
+        debug!("loading category tree from database");
+
+        let mut connection = self.database.acquire().await?;
+        let tree = load_tree(&mut connection).await?;
+
+        match serde_json::to_vec(&tree) {
+            Ok(value) => {
+                if let Err(error) = self
+                    .cache
+                    .set_ex(cache_key, value.as_slice(), CATEGORY_TREE_CACHE_TTL)
+                    .await
+                {
+                    debug!(cache_key = %cache_key, error = %error, "failed to cache category tree");
+                }
+            }
+            Err(error) => {
+                debug!(cache_key = %cache_key, error = %error, "failed to serialize category tree");
+            }
+        }
+
+        Ok(tree)
+    }
+
+    async fn get_category(&self, id: Uuid) -> Result<Option<Category>, CategoryError> {
+        let tree = self.category_tree().await?;
+        Ok(tree.into_iter().find(|category| category.id == id))
+    }
+
+    async fn create_category(&self, data: &CreateCategory) -> Result<Category, CategoryError> {
+        trace!(category_id = %data.id, slug = data.slug, "creating category");
+
+        data.validate()?;
+
+        let mut transaction = self.database.begin().await?;
+
+        sqlx::query!(
+            r#"
+            insert into category
+            (
+                id,
+                parent_id,
+                slug,
+                position
+            )
+            values ($1, $2, $3, $4)
+            "#,
+            data.id,
+            data.parent_id,
+            data.slug,
+            data.position,
+        )
+        .execute(&mut *transaction)
+        .await
+        .map_err(write_error)?;
+
+        insert_names(&mut transaction, data.id, &data.names).await?;
+
+        let category = load_tree(&mut transaction)
+            .await?
+            .into_iter()
+            .find(|category| category.id == data.id)
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        transaction.commit().await?;
+        self.invalidate_tree().await;
+
+        Ok(category)
+    }
+
+    async fn update_category(
+        &self,
+        id: Uuid,
+        data: &UpdateCategory,
+    ) -> Result<Option<Category>, CategoryError> {
+        trace!(category_id = %id, "updating category");
+
+        data.validate()?;
+
+        let mut transaction = self.database.begin().await?;
+
+        // Locked so concurrent moves can't make a cycle between them.
+        sqlx::query!("lock table category in share row exclusive mode")
+            .execute(&mut *transaction)
+            .await?;
+
+        if let Some(Some(parent_id)) = data.parent_id {
+            let tree = load_tree(&mut transaction).await?;
+            if descendant_ids(&tree, id).contains(&parent_id) {
+                return Err(CategoryError::Invalid(
+                    "a category can't be moved below itself",
+                ));
+            }
+        }
+
+        let updated = sqlx::query!(
+            r#"
+            update category
+            set
+                parent_id = case when $2 then $3 else parent_id end,
+                slug = coalesce($4, slug),
+                position = coalesce($5, position),
+                updated_at = now()
+            where id = $1
+            "#,
+            id,
+            data.parent_id.is_some(),
+            data.parent_id.flatten(),
+            data.slug,
+            data.position,
+        )
+        .execute(&mut *transaction)
+        .await
+        .map_err(write_error)?;
+
+        if updated.rows_affected() == 0 {
+            return Ok(None);
+        }
+
+        if let Some(names) = &data.names {
+            sqlx::query!(
+                r#"
+                delete from category_name
+                where category_id = $1
+                "#,
+                id
+            )
+            .execute(&mut *transaction)
+            .await?;
+
+            insert_names(&mut transaction, id, names).await?;
+        }
+
+        let category = load_tree(&mut transaction)
+            .await?
+            .into_iter()
+            .find(|category| category.id == id);
+
+        transaction.commit().await?;
+        self.invalidate_tree().await;
+
+        Ok(category)
+    }
+
+    async fn delete_category(&self, id: Uuid) -> Result<Option<Category>, CategoryError> {
+        trace!(category_id = %id, "deleting category");
+
+        let Some(category) = self.get_category(id).await? else {
+            return Ok(None);
+        };
+
+        let deleted = sqlx::query!(
+            r#"
+            delete from category
+            where id = $1
+            "#,
+            id
+        )
+        .execute(&self.database)
+        .await
+        .map_err(|e| match e.as_database_error() {
+            Some(error) if error.is_foreign_key_violation() => CategoryError::HasChildren,
+            _ => e.into(),
+        })?;
+
+        self.invalidate_tree().await;
+
+        Ok((deleted.rows_affected() > 0).then_some(category))
+    }
+}
+
+impl CategoryService {
+    pub fn new(pool: sqlx::PgPool, cache: Cache) -> Self {
+        Self {
+            database: pool,
+            cache,
+        }
+    }
+
+    async fn invalidate_tree(&self) {
+        let key = CacheKey::CategoryTree;
+        trace!(cache_key = %key, "invalidating category tree cache");
+
+        if let Err(error) = self.cache.del(key).await {
+            debug!(
+                cache_key = %key,
+                error = %error,
+                "failed to invalidate category tree cache"
+            );
+        }
+    }
+}
+
+async fn load_tree(connection: &mut PgConnection) -> Result<Vec<Category>, CategoryError> {
+    let rows = sqlx::query!(
+        r#"
+        select
+            c.id,
+            c.parent_id,
+            c.slug,
+            c.position,
+            c.created_at,
+            c.updated_at,
+            coalesce(
+                array_agg(n.locale order by n.locale) filter (where n.locale is not null),
+                '{}'
+            ) as "locales!",
+            coalesce(
+                array_agg(n.name order by n.locale) filter (where n.locale is not null),
+                '{}'
+            ) as "names!"
+        from category as c
+        left join category_name as n on n.category_id = c.id
+        group by c.id
+        order by c.position, c.slug
+        "#
+    )
+    .fetch_all(connection)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| Category {
+            id: row.id,
+            parent_id: row.parent_id,
+            slug: row.slug,
+            position: row.position,
+            names: row.locales.into_iter().zip(row.names).collect(),
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        })
+        .collect())
+}
+
+async fn insert_names(
+    connection: &mut PgConnection,
+    category_id: Uuid,
+    names: &BTreeMap<String, String>,
+) -> Result<(), CategoryError> {
+    let (locales, names): (Vec<_>, Vec<_>) = names
+        .iter()
+        .map(|(locale, name)| (locale.clone(), name.trim().to_owned()))
+        .unzip();
+
+    sqlx::query!(
+        r#"
+        insert into category_name (category_id, locale, name)
+        select $1, locale, name
+        from unnest($2::text[], $3::text[]) as names(locale, name)
+        "#,
+        category_id,
+        &locales,
+        &names,
+    )
+    .execute(connection)
+    .await?;
+
+    Ok(())
+}
+
+/// Errors of inserting or updating a category that are the caller's fault.
+fn write_error(e: sqlx::Error) -> CategoryError {
+    match e.as_database_error() {
+        Some(error) if error.is_unique_violation() => CategoryError::SlugTaken,
+        Some(error) if error.is_foreign_key_violation() => CategoryError::Invalid("unknown parent"),
+        Some(error) if error.is_check_violation() => {
+            CategoryError::Invalid("a category can't be moved below itself")
+        }
+        _ => e.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn check_slug(input: &str, expected_result: bool) {
+        assert_eq!(expected_result, validate_slug(input).is_ok());
+    }
+
+    #[test]
+    fn slugs() {
+        check_slug("bikes", true);
+        check_slug("road-bikes", true);
+        check_slug("3d-printers", true);
+        check_slug("", false);
+        check_slug("Bikes", false);
+        check_slug("road--bikes", false);
+        check_slug("-bikes", false);
+        check_slug("bikes-", false);
+        check_slug("road_bikes", false);
+        check_slug("fahrräder", false);
+        check_slug(&"a".repeat(MAX_SLUG_LEN), true);
+        check_slug(&"a".repeat(MAX_SLUG_LEN + 1), false);
+    }
+
+    #[track_caller]
+    fn check_names(input: &[(&str, &str)], expected_result: bool) {
+        let names = input
+            .iter()
+            .map(|(locale, name)| (locale.to_string(), name.to_string()))
+            .collect();
+        assert_eq!(expected_result, validate_names(&names).is_ok());
+    }
+
+    #[test]
+    fn names() {
+        check_names(&[("en", "Bikes")], true);
+        check_names(
+            &[
+                ("en", "Bikes"),
+                ("de", "Fahrräder"),
+                ("pt-BR", "Bicicletas"),
+            ],
+            true,
+        );
+        check_names(&[("de", "Fahrräder")], false);
+        check_names(&[], false);
+        check_names(&[("en", "  ")], false);
+        check_names(&[("en", &"a".repeat(MAX_NAME_LEN + 1))], false);
+        check_names(&[("en", "Bikes"), ("EN", "Bikes")], false);
+        check_names(&[("en", "Bikes"), ("pt-br", "Bicicletas")], false);
+        check_names(&[("en", "Bikes"), ("english", "Bikes")], false);
+    }
+}
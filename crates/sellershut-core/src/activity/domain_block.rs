@@ -0,0 +1,28 @@
+use sqlx::prelude::Type;
+use time::OffsetDateTime;
+
+/// A moderation decision about a remote domain and its subdomains.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DomainBlock {
+    pub domain: String,
+    pub severity: BlockSeverity,
+    pub public_reason: Option<String>,
+    pub created_at: OffsetDateTime,
+    pub updated_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Type)]
+#[sqlx(type_name = "block_severity")]
+#[sqlx(rename_all = "PascalCase")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "PascalCase"))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub enum BlockSeverity {
+    /// Federate, but keep the domain's content out of public listings.
+    Silence,
+    /// Refuse all federation with the domain.
+    Suspend,
+    /// Federate, but do not fetch or store the domain's media.
+    RejectMedia,
+}
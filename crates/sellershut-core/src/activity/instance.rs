@@ -0,0 +1,20 @@
+use time::OffsetDateTime;
+
+/// Another server we federate with.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Instance {
+    /// Host, with the port if it is not the default one.
+    pub domain: String,
+    pub software_name: Option<String>,
+    pub software_version: Option<String>,
+    pub last_success_at: Option<OffsetDateTime>,
+    pub last_failure_at: Option<OffsetDateTime>,
+    /// When the current run of failures started, `None` while the instance is reachable.
+    pub first_failure_at: Option<OffsetDateTime>,
+    pub consecutive_failures: i32,
+    pub suspended_until: Option<OffsetDateTime>,
+    pub last_probed_at: Option<OffsetDateTime>,
+    pub created_at: OffsetDateTime,
+    pub updated_at: OffsetDateTime,
+}
@@ -0,0 +1,39 @@
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::custom_url::Url;
+
+mod domain_block;
+mod instance;
+pub use domain_block::*;
+pub use instance::*;
+
+/// An activity sent by a local actor.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Activity {
+    pub id: Uuid,
+    pub ap_id: Url,
+    pub actor_id: Uuid,
+    /// The activity's `type`, like `Create` or `Follow`.
+    pub kind: String,
+    pub data: serde_json::Value,
+    pub is_public: bool,
+    pub created_at: OffsetDateTime,
+}
+
+/// An activity waiting to be delivered to a single inbox.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Delivery {
+    pub id: Uuid,
+    pub inbox: Url,
+    /// Instance the inbox belongs to.
+    pub domain: String,
+    /// ActivityPub id of the actor the activity is signed as.
+    pub actor: Url,
+    pub activity_id: Url,
+    pub payload: serde_json::Value,
+    pub attempts: i32,
+    pub created_at: OffsetDateTime,
+}
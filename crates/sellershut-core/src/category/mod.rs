@@ -0,0 +1,140 @@
+use std::collections::BTreeMap;
+
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// Locale every category has a name in, used when there is none in the one asked for.
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// A node of the category tree listings are sorted into.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Category {
+    pub id: Uuid,
+    /// `None` for top level categories.
+    pub parent_id: Option<Uuid>,
+    /// Unique, used in urls.
+    pub slug: String,
+    /// Order among its siblings.
+    pub position: i32,
+    /// Names by locale, like `en` or `pt-BR`.
+    pub names: BTreeMap<String, String>,
+    pub created_at: OffsetDateTime,
+    pub updated_at: OffsetDateTime,
+}
+
+impl Category {
+    /// The name for `locale`, falling back to its language and then to [`DEFAULT_LOCALE`].
+    pub fn name(&self, locale: Option<&str>) -> &str {
+        let language = locale.and_then(|locale| locale.split('-').next());
+
+        [locale, language, Some(DEFAULT_LOCALE)]
+            .into_iter()
+            .flatten()
+            .find_map(|locale| self.names.get(locale))
+            .or_else(|| self.names.values().next())
+            .map(String::as_str)
+            .unwrap_or(&self.slug)
+    }
+}
+
+/// Ids of `id` and every category below it.
+pub fn descendant_ids(categories: &[Category], id: Uuid) -> Vec<Uuid> {
+    let mut ids = vec![id];
+    let mut next = 0;
+
+    while let Some(parent) = ids.get(next).copied() {
+        let children: Vec<_> = categories
+            .iter()
+            .filter(|category| category.parent_id == Some(parent) && !ids.contains(&category.id))
+            .map(|category| category.id)
+            .collect();
+        ids.extend(children);
+        next += 1;
+    }
+
+    ids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn category(id: u128, parent_id: Option<u128>, names: &[(&str, &str)]) -> Category {
+        Category {
+            id: Uuid::from_u128(id),
+            parent_id: parent_id.map(Uuid::from_u128),
+            slug: format!("category-{id}"),
+            position: 0,
+            names: names
+                .iter()
+                .map(|(locale, name)| (locale.to_string(), name.to_string()))
+                .collect(),
+            created_at: OffsetDateTime::UNIX_EPOCH,
+            updated_at: OffsetDateTime::UNIX_EPOCH,
+        }
+    }
+
+    #[track_caller]
+    fn check_name(locale: Option<&str>, expected_result: &str) {
+        let category = category(
+            1,
+            None,
+            &[
+                ("de", "Fahrräder"),
+                ("en", "Bikes"),
+                ("pt-BR", "Bicicletas"),
+            ],
+        );
+        assert_eq!(expected_result, category.name(locale));
+    }
+
+    #[test]
+    fn names() {
+        check_name(None, "Bikes");
+        check_name(Some("en"), "Bikes");
+        check_name(Some("de"), "Fahrräder");
+        check_name(Some("de-AT"), "Fahrräder");
+        check_name(Some("pt-BR"), "Bicicletas");
+        check_name(Some("pt"), "Bikes");
+        check_name(Some("fr"), "Bikes");
+    }
+
+    #[test]
+    fn name_without_default_locale() {
+        assert_eq!(
+            "Fahrräder",
+            category(1, None, &[("de", "Fahrräder")]).name(None)
+        );
+        assert_eq!("category-1", category(1, None, &[]).name(Some("de")));
+    }
+
+    #[track_caller]
+    fn check_descendants(id: u128, expected_result: &[u128]) {
+        let tree = [
+            category(1, None, &[]),
+            category(2, Some(1), &[]),
+            category(3, Some(2), &[]),
+            category(4, Some(1), &[]),
+            category(5, None, &[]),
+        ];
+        let mut ids = descendant_ids(&tree, Uuid::from_u128(id));
+        ids.sort();
+
+        let expected: Vec<_> = expected_result
+            .iter()
+            .copied()
+            .map(Uuid::from_u128)
+            .collect();
+        assert_eq!(expected, ids);
+    }
+
+    #[test]
+    fn descendants() {
+        check_descendants(1, &[1, 2, 3, 4]);
+        check_descendants(2, &[2, 3]);
+        check_descendants(3, &[3]);
+        check_descendants(5, &[5]);
+        check_descendants(6, &[6]);
+    }
+}
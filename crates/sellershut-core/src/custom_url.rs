@@ -30,6 +30,16 @@ impl Type<Postgres> for Url {
     }
 }
 
+impl PgHasArrayType for Url {
+    fn array_type_info() -> PgTypeInfo {
+        <String as PgHasArrayType>::array_type_info()
+    }
+
+    fn array_compatible(ty: &PgTypeInfo) -> bool {
+        <String as PgHasArrayType>::array_compatible(ty)
+    }
+}
+
 impl<'q> Encode<'q, Postgres> for Url {
     fn encode_by_ref(
         &self,
@@ -59,7 +69,7 @@ use std::fmt;
 use sqlx::{
     Decode, Encode, Postgres, Type,
     encode::IsNull,
-    postgres::{PgArgumentBuffer, PgTypeInfo, PgValueRef},
+    postgres::{PgArgumentBuffer, PgHasArrayType, PgTypeInfo, PgValueRef},
 };
 
 #[derive(Debug)]
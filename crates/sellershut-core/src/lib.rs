@@ -1,10 +1,20 @@
+#[cfg(feature = "activity")]
+pub mod activity;
 #[cfg(feature = "auth")]
 pub mod auth;
+#[cfg(feature = "categories")]
+pub mod category;
+#[cfg(feature = "listings")]
+pub mod listing;
+#[cfg(feature = "media")]
+pub mod media;
 #[cfg(feature = "users")]
 pub mod user;
 
 mod custom_url;
+mod money;
 mod redacted_secret;
 
 pub use custom_url::*;
+pub use money::*;
 pub use redacted_secret::*;
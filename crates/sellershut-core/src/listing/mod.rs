@@ -0,0 +1,87 @@
+use sqlx::prelude::Type;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{custom_url::Url, money::Money};
+
+/// Something a user is selling, local or from another instance.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Listing {
+    pub id: Uuid,
+    pub ap_id: Url,
+    pub owner_id: Uuid,
+    pub title: String,
+    pub description: String,
+    pub price: Money,
+    pub condition: ListingCondition,
+    pub status: ListingStatus,
+    pub is_local: bool,
+    pub created_at: OffsetDateTime,
+    pub updated_at: OffsetDateTime,
+    /// When the listing was first made active.
+    pub published_at: Option<OffsetDateTime>,
+    /// Only local listings are sorted into categories.
+    pub category_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[sqlx(type_name = "listing_status")]
+#[sqlx(rename_all = "PascalCase")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "PascalCase"))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub enum ListingStatus {
+    /// Only visible to its owner.
+    Draft,
+    Active,
+    Sold,
+    /// Taken down by its owner without being sold.
+    Archived,
+}
+
+impl ListingStatus {
+    /// Whether anyone but the owner can see a listing.
+    pub fn is_public(self) -> bool {
+        !matches!(self, Self::Draft)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[sqlx(type_name = "listing_condition")]
+#[sqlx(rename_all = "PascalCase")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "PascalCase"))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub enum ListingCondition {
+    New,
+    LikeNew,
+    Good,
+    Fair,
+    Poor,
+}
+
+/// How listings found by a search are ordered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "PascalCase"))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub enum SearchSort {
+    /// Best matches first, newest first when there is nothing to match.
+    Relevance,
+    /// Most recently published first.
+    Newest,
+    PriceAsc,
+    PriceDesc,
+}
+
+impl SearchSort {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Relevance => "Relevance",
+            Self::Newest => "Newest",
+            Self::PriceAsc => "PriceAsc",
+            Self::PriceDesc => "PriceDesc",
+        }
+    }
+}
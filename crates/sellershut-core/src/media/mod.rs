@@ -0,0 +1,51 @@
+use sqlx::prelude::Type;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// An uploaded file, like a photo of a listing.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Media {
+    pub id: Uuid,
+    pub owner_id: Uuid,
+    /// Sniffed from the file itself, never taken from the upload.
+    pub content_type: String,
+    /// In bytes.
+    pub size: i64,
+    /// Where the file is kept in the media store, `None` for files of remote listings.
+    pub storage_key: Option<String>,
+    /// Where a file of a remote listing is, it is never copied here.
+    pub remote_url: Option<crate::Url>,
+    /// Alt text.
+    pub description: Option<String>,
+    /// The listing the file is attached to, `None` until it is used.
+    pub listing_id: Option<Uuid>,
+    /// Order among the listing's other files.
+    pub position: i32,
+    pub status: MediaStatus,
+    /// In pixels, known once the file is processed.
+    pub width: Option<i32>,
+    /// In pixels, known once the file is processed.
+    pub height: Option<i32>,
+    /// Placeholder shown while the file loads.
+    pub blurhash: Option<String>,
+    /// Where the thumbnail is kept in the media store.
+    pub thumbnail_key: Option<String>,
+    /// Times processing the file was tried.
+    pub attempts: i32,
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[sqlx(type_name = "media_status")]
+#[sqlx(rename_all = "PascalCase")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "PascalCase"))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub enum MediaStatus {
+    /// Uploaded, metadata not stripped yet. Not served.
+    Pending,
+    Ready,
+    /// Could not be processed, the file is gone.
+    Failed,
+}
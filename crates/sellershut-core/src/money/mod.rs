@@ -0,0 +1,487 @@
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+use sqlx::{
+    Decode, Encode, Postgres, Type,
+    encode::IsNull,
+    postgres::{
+        PgArgumentBuffer, PgHasArrayType, PgTypeInfo, PgValueRef,
+        types::{PgRecordDecoder, PgRecordEncoder},
+    },
+};
+
+/// An ISO 4217 currency code, like `EUR`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "String", into = "String"))]
+pub struct Currency([u8; 3]);
+
+/// The ISO 4217 currencies, with the number of decimal places of their minor unit, sorted by
+/// code.
+///
+/// Precious metals, testing codes and the other codes without a minor unit are left out, nothing
+/// is sold in them.
+const CURRENCIES: &[(&str, u8)] = &[
+    ("AED", 2),
+    ("AFN", 2),
+    ("ALL", 2),
+    ("AMD", 2),
+    ("ANG", 2),
+    ("AOA", 2),
+    ("ARS", 2),
+    ("AUD", 2),
+    ("AWG", 2),
+    ("AZN", 2),
+    ("BAM", 2),
+    ("BBD", 2),
+    ("BDT", 2),
+    ("BGN", 2),
+    ("BHD", 3),
+    ("BIF", 0),
+    ("BMD", 2),
+    ("BND", 2),
+    ("BOB", 2),
+    ("BOV", 2),
+    ("BRL", 2),
+    ("BSD", 2),
+    ("BTN", 2),
+    ("BWP", 2),
+    ("BYN", 2),
+    ("BZD", 2),
+    ("CAD", 2),
+    ("CDF", 2),
+    ("CHE", 2),
+    ("CHF", 2),
+    ("CHW", 2),
+    ("CLF", 4),
+    ("CLP", 0),
+    ("CNY", 2),
+    ("COP", 2),
+    ("COU", 2),
+    ("CRC", 2),
+    ("CUC", 2),
+    ("CUP", 2),
+    ("CVE", 2),
+    ("CZK", 2),
+    ("DJF", 0),
+    ("DKK", 2),
+    ("DOP", 2),
+    ("DZD", 2),
+    ("EGP", 2),
+    ("ERN", 2),
+    ("ETB", 2),
+    ("EUR", 2),
+    ("FJD", 2),
+    ("FKP", 2),
+    ("GBP", 2),
+    ("GEL", 2),
+    ("GHS", 2),
+    ("GIP", 2),
+    ("GMD", 2),
+    ("GNF", 0),
+    ("GTQ", 2),
+    ("GYD", 2),
+    ("HKD", 2),
+    ("HNL", 2),
+    ("HTG", 2),
+    ("HUF", 2),
+    ("IDR", 2),
+    ("ILS", 2),
+    ("INR", 2),
+    ("IQD", 3),
+    ("IRR", 2),
+    ("ISK", 0),
+    ("JMD", 2),
+    ("JOD", 3),
+    ("JPY", 0),
+    ("KES", 2),
+    ("KGS", 2),
+    ("KHR", 2),
+    ("KMF", 0),
+    ("KPW", 2),
+    ("KRW", 0),
+    ("KWD", 3),
+    ("KYD", 2),
+    ("KZT", 2),
+    ("LAK", 2),
+    ("LBP", 2),
+    ("LKR", 2),
+    ("LRD", 2),
+    ("LSL", 2),
+    ("LYD", 3),
+    ("MAD", 2),
+    ("MDL", 2),
+    ("MGA", 2),
+    ("MKD", 2),
+    ("MMK", 2),
+    ("MNT", 2),
+    ("MOP", 2),
+    ("MRU", 2),
+    ("MUR", 2),
+    ("MVR", 2),
+    ("MWK", 2),
+    ("MXN", 2),
+    ("MXV", 2),
+    ("MYR", 2),
+    ("MZN", 2),
+    ("NAD", 2),
+    ("NGN", 2),
+    ("NIO", 2),
+    ("NOK", 2),
+    ("NPR", 2),
+    ("NZD", 2),
+    ("OMR", 3),
+    ("PAB", 2),
+    ("PEN", 2),
+    ("PGK", 2),
+    ("PHP", 2),
+    ("PKR", 2),
+    ("PLN", 2),
+    ("PYG", 0),
+    ("QAR", 2),
+    ("RON", 2),
+    ("RSD", 2),
+    ("RUB", 2),
+    ("RWF", 0),
+    ("SAR", 2),
+    ("SBD", 2),
+    ("SCR", 2),
+    ("SDG", 2),
+    ("SEK", 2),
+    ("SGD", 2),
+    ("SHP", 2),
+    ("SLE", 2),
+    ("SLL", 2),
+    ("SOS", 2),
+    ("SRD", 2),
+    ("SSP", 2),
+    ("STN", 2),
+    ("SVC", 2),
+    ("SYP", 2),
+    ("SZL", 2),
+    ("THB", 2),
+    ("TJS", 2),
+    ("TMT", 2),
+    ("TND", 3),
+    ("TOP", 2),
+    ("TRY", 2),
+    ("TTD", 2),
+    ("TWD", 2),
+    ("TZS", 2),
+    ("UAH", 2),
+    ("UGX", 0),
+    ("USD", 2),
+    ("USN", 2),
+    ("UYI", 0),
+    ("UYU", 2),
+    ("UYW", 4),
+    ("UZS", 2),
+    ("VED", 2),
+    ("VES", 2),
+    ("VND", 0),
+    ("VUV", 0),
+    ("WST", 2),
+    ("XAF", 0),
+    ("XCD", 2),
+    ("XCG", 2),
+    ("XOF", 0),
+    ("XPF", 0),
+    ("YER", 2),
+    ("ZAR", 2),
+    ("ZMW", 2),
+    ("ZWG", 2),
+];
+
+impl Currency {
+    /// Fails unless `code` is an ISO 4217 currency code, in uppercase.
+    pub fn new(code: &str) -> Result<Self, MoneyError> {
+        match <[u8; 3]>::try_from(code.as_bytes()) {
+            Ok(bytes) if Self::lookup(code).is_some() => Ok(Self(bytes)),
+            _ => Err(MoneyError::InvalidCurrency),
+        }
+    }
+
+    fn lookup(code: &str) -> Option<u8> {
+        CURRENCIES
+            .binary_search_by(|(known, _)| (*known).cmp(code))
+            .ok()
+            .map(|index| CURRENCIES[index].1)
+    }
+
+    pub fn as_str(&self) -> &str {
+        // only ever built from a code in the table
+        std::str::from_utf8(&self.0).expect("currency to be ascii")
+    }
+
+    /// Number of decimal places of the currency's minor unit, 2 for cents.
+    pub fn minor_units(&self) -> u8 {
+        // only ever built from a code in the table
+        Self::lookup(self.as_str()).expect("currency to be in the table")
+    }
+}
+
+impl FromStr for Currency {
+    type Err = MoneyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl TryFrom<String> for Currency {
+    type Error = MoneyError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::new(&value)
+    }
+}
+
+impl From<Currency> for String {
+    fn from(value: Currency) -> Self {
+        value.as_str().to_owned()
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Type<Postgres> for Currency {
+    fn type_info() -> PgTypeInfo {
+        <String as Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <String as Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl PgHasArrayType for Currency {
+    fn array_type_info() -> PgTypeInfo {
+        <String as PgHasArrayType>::array_type_info()
+    }
+
+    fn array_compatible(ty: &PgTypeInfo) -> bool {
+        <String as PgHasArrayType>::array_compatible(ty)
+    }
+}
+
+impl<'q> Encode<'q, Postgres> for Currency {
+    fn encode_by_ref(
+        &self,
+        buf: &mut PgArgumentBuffer,
+    ) -> Result<IsNull, sqlx::error::BoxDynError> {
+        <&str as Encode<Postgres>>::encode(self.as_str(), buf)
+    }
+
+    fn size_hint(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for Currency {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
+        let value = <&str as Decode<Postgres>>::decode(value)?;
+        Ok(Self::new(value)?)
+    }
+}
+
+/// An amount of money in a currency's minor unit, like cents.
+///
+/// Amounts are never negative and never larger than [`Money::MAX_AMOUNT`]. Money in different
+/// currencies can't be added or ordered.
+///
+/// In Postgres it is a `money_amount`, though tables keep the amount and currency in their own
+/// columns and build one with `(price, currency)::money_amount`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "UncheckedMoney"))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub struct Money {
+    /// In the currency's minor unit, like cents
+    amount: i64,
+    /// ISO 4217 code
+    #[cfg_attr(feature = "utoipa", schema(value_type = String, example = "EUR"))]
+    currency: Currency,
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct UncheckedMoney {
+    amount: i64,
+    currency: Currency,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<UncheckedMoney> for Money {
+    type Error = MoneyError;
+
+    fn try_from(value: UncheckedMoney) -> Result<Self, Self::Error> {
+        Self::new(value.amount, value.currency)
+    }
+}
+
+impl Money {
+    /// The largest amount there can be, so amounts survive a round trip through a JavaScript
+    /// number.
+    pub const MAX_AMOUNT: i64 = (1 << 53) - 1;
+
+    /// Fails if `amount` is negative or larger than [`Money::MAX_AMOUNT`].
+    pub fn new(amount: i64, currency: Currency) -> Result<Self, MoneyError> {
+        if amount < 0 {
+            return Err(MoneyError::Negative);
+        }
+        if amount > Self::MAX_AMOUNT {
+            return Err(MoneyError::Overflow);
+        }
+        Ok(Self { amount, currency })
+    }
+
+    pub fn zero(currency: Currency) -> Self {
+        Self {
+            amount: 0,
+            currency,
+        }
+    }
+
+    /// Parse an amount in the currency's major unit, like `"12.50"` euros.
+    pub fn from_major(value: &str, currency: Currency) -> Result<Self, MoneyError> {
+        let (whole, fraction) = value.split_once('.').unwrap_or((value, ""));
+        let units = usize::from(currency.minor_units());
+        if whole.is_empty()
+            || fraction.len() > units
+            || !whole
+                .bytes()
+                .chain(fraction.bytes())
+                .all(|b| b.is_ascii_digit())
+        {
+            return Err(MoneyError::InvalidAmount);
+        }
+
+        let digits = format!("{whole}{fraction:0<units$}");
+        let amount = digits.parse().map_err(|_| MoneyError::Overflow)?;
+        Self::new(amount, currency)
+    }
+
+    pub fn amount(&self) -> i64 {
+        self.amount
+    }
+
+    pub fn currency(&self) -> Currency {
+        self.currency
+    }
+
+    /// The amount in the currency's major unit, like `"12.50"`.
+    pub fn to_major(&self) -> String {
+        let units = u32::from(self.currency.minor_units());
+        if units == 0 {
+            return self.amount.to_string();
+        }
+
+        let scale = 10_i64.pow(units);
+        format!(
+            "{}.{:0width$}",
+            self.amount / scale,
+            self.amount % scale,
+            width = units as usize
+        )
+    }
+
+    pub fn checked_add(self, other: Self) -> Result<Self, MoneyError> {
+        if self.currency != other.currency {
+            return Err(MoneyError::CurrencyMismatch);
+        }
+        let amount = self
+            .amount
+            .checked_add(other.amount)
+            .ok_or(MoneyError::Overflow)?;
+        Self::new(amount, self.currency)
+    }
+
+    pub fn checked_mul(self, quantity: u32) -> Result<Self, MoneyError> {
+        let amount = self
+            .amount
+            .checked_mul(i64::from(quantity))
+            .ok_or(MoneyError::Overflow)?;
+        Self::new(amount, self.currency)
+    }
+}
+
+impl PartialOrd for Money {
+    /// `None` for money in different currencies.
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        (self.currency == other.currency).then(|| self.amount.cmp(&other.amount))
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.to_major(), self.currency)
+    }
+}
+
+/// Parses what [`Money`] displays as, like `"12.50 EUR"`.
+impl FromStr for Money {
+    type Err = MoneyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (amount, currency) = s.split_once(' ').ok_or(MoneyError::InvalidCurrency)?;
+        Self::from_major(amount, currency.parse()?)
+    }
+}
+
+impl Type<Postgres> for Money {
+    fn type_info() -> PgTypeInfo {
+        PgTypeInfo::with_name("money_amount")
+    }
+}
+
+impl<'q> Encode<'q, Postgres> for Money {
+    fn encode_by_ref(
+        &self,
+        buf: &mut PgArgumentBuffer,
+    ) -> Result<IsNull, sqlx::error::BoxDynError> {
+        let mut encoder = PgRecordEncoder::new(buf);
+        encoder.encode(self.amount)?;
+        encoder.encode(self.currency)?;
+        encoder.finish();
+        Ok(IsNull::No)
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for Money {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
+        let mut decoder = PgRecordDecoder::new(value)?;
+        let amount = decoder.try_decode::<i64>()?;
+        let currency = decoder.try_decode::<Currency>()?;
+        Ok(Self::new(amount, currency)?)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoneyError {
+    Negative,
+    Overflow,
+    InvalidAmount,
+    InvalidCurrency,
+    CurrencyMismatch,
+}
+
+impl fmt::Display for MoneyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            Self::Negative => "amount is negative",
+            Self::Overflow => "amount is too large",
+            Self::InvalidAmount => "amount is not a number in the currency's major unit",
+            Self::InvalidCurrency => "currency is not an ISO 4217 code",
+            Self::CurrencyMismatch => "amounts are in different currencies",
+        };
+        f.write_str(reason)
+    }
+}
+
+impl std::error::Error for MoneyError {}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,176 @@
+use super::*;
+
+fn eur() -> Currency {
+    Currency::new("EUR").unwrap()
+}
+
+#[track_caller]
+fn check_currency(input: &str, expected_result: Result<&str, MoneyError>) {
+    let actual_result = Currency::new(input);
+
+    assert_eq!(
+        expected_result,
+        actual_result.as_ref().map(Currency::as_str).map_err(|e| *e)
+    );
+}
+
+#[track_caller]
+fn check_new(amount: i64, expected_result: Result<i64, MoneyError>) {
+    assert_eq!(
+        expected_result,
+        Money::new(amount, eur()).map(|money| money.amount())
+    );
+}
+
+#[track_caller]
+fn check_from_major(input: &str, currency: &str, expected_result: Result<i64, MoneyError>) {
+    let currency = Currency::new(currency).unwrap();
+
+    assert_eq!(
+        expected_result,
+        Money::from_major(input, currency).map(|money| money.amount())
+    );
+}
+
+#[track_caller]
+fn check_display(amount: i64, currency: &str, expected_result: &str) {
+    let money = Money::new(amount, Currency::new(currency).unwrap()).unwrap();
+
+    assert_eq!(expected_result, money.to_string());
+}
+
+#[test]
+fn currency() {
+    check_currency("ZAR", Ok("ZAR"));
+    check_currency("zar", Err(MoneyError::InvalidCurrency));
+    check_currency("RAND", Err(MoneyError::InvalidCurrency));
+    check_currency("R", Err(MoneyError::InvalidCurrency));
+    check_currency("", Err(MoneyError::InvalidCurrency));
+    check_currency("ÉU", Err(MoneyError::InvalidCurrency));
+    check_currency("XYZ", Err(MoneyError::InvalidCurrency));
+    check_currency("AAA", Err(MoneyError::InvalidCurrency));
+    check_currency("XAU", Err(MoneyError::InvalidCurrency));
+}
+
+#[test]
+fn currencies_are_sorted() {
+    assert!(CURRENCIES.windows(2).all(|pair| pair[0].0 < pair[1].0));
+}
+
+#[test]
+fn minor_units() {
+    assert_eq!(2, eur().minor_units());
+    assert_eq!(0, Currency::new("JPY").unwrap().minor_units());
+    assert_eq!(3, Currency::new("KWD").unwrap().minor_units());
+    assert_eq!(4, Currency::new("CLF").unwrap().minor_units());
+}
+
+#[test]
+fn new() {
+    check_new(0, Ok(0));
+    check_new(10_000, Ok(10_000));
+    check_new(-1, Err(MoneyError::Negative));
+    check_new(Money::MAX_AMOUNT, Ok(Money::MAX_AMOUNT));
+    check_new(Money::MAX_AMOUNT + 1, Err(MoneyError::Overflow));
+    check_new(i64::MAX, Err(MoneyError::Overflow));
+}
+
+#[test]
+fn from_major() {
+    check_from_major("12.50", "EUR", Ok(1250));
+    check_from_major("12.5", "EUR", Ok(1250));
+    check_from_major("12", "EUR", Ok(1200));
+    check_from_major("0.01", "EUR", Ok(1));
+    check_from_major("1500", "JPY", Ok(1500));
+    check_from_major("1.234", "KWD", Ok(1234));
+    check_from_major("12.505", "EUR", Err(MoneyError::InvalidAmount));
+    check_from_major("1.5", "JPY", Err(MoneyError::InvalidAmount));
+    check_from_major("-1", "EUR", Err(MoneyError::InvalidAmount));
+    check_from_major(".5", "EUR", Err(MoneyError::InvalidAmount));
+    check_from_major("1,50", "EUR", Err(MoneyError::InvalidAmount));
+    check_from_major("", "EUR", Err(MoneyError::InvalidAmount));
+    check_from_major("99999999999999999999", "EUR", Err(MoneyError::Overflow));
+    check_from_major("90071992547409.92", "EUR", Err(MoneyError::Overflow));
+}
+
+#[test]
+fn display() {
+    check_display(1250, "EUR", "12.50 EUR");
+    check_display(5, "EUR", "0.05 EUR");
+    check_display(0, "EUR", "0.00 EUR");
+    check_display(1500, "JPY", "1500 JPY");
+    check_display(1234, "KWD", "1.234 KWD");
+}
+
+#[track_caller]
+fn check_parse(input: &str, expected_result: Result<&str, MoneyError>) {
+    let actual_result = input.parse::<Money>().map(|money| money.to_string());
+
+    assert_eq!(expected_result.map(str::to_owned), actual_result);
+}
+
+#[test]
+fn parse() {
+    check_parse("12.50 EUR", Ok("12.50 EUR"));
+    check_parse("12 EUR", Ok("12.00 EUR"));
+    check_parse("1500 JPY", Ok("1500 JPY"));
+    check_parse("12.50", Err(MoneyError::InvalidCurrency));
+    check_parse("12.50 XYZ", Err(MoneyError::InvalidCurrency));
+    check_parse("12,50 EUR", Err(MoneyError::InvalidAmount));
+    check_parse("-1 EUR", Err(MoneyError::InvalidAmount));
+}
+
+#[test]
+fn arithmetic() {
+    let one = Money::new(100, eur()).unwrap();
+    let max = Money::new(Money::MAX_AMOUNT, eur()).unwrap();
+    let yen = Money::new(100, Currency::new("JPY").unwrap()).unwrap();
+
+    assert_eq!(Ok(200), one.checked_add(one).map(|money| money.amount()));
+    assert_eq!(Ok(300), one.checked_mul(3).map(|money| money.amount()));
+    assert_eq!(Err(MoneyError::Overflow), max.checked_add(one));
+    assert_eq!(Err(MoneyError::Overflow), max.checked_mul(u32::MAX));
+    assert_eq!(Err(MoneyError::CurrencyMismatch), one.checked_add(yen));
+}
+
+#[test]
+fn compare() {
+    let one = Money::new(100, eur()).unwrap();
+    let two = Money::new(200, eur()).unwrap();
+    let yen = Money::new(100, Currency::new("JPY").unwrap()).unwrap();
+
+    assert!(one < two);
+    assert_eq!(Some(Ordering::Equal), one.partial_cmp(&one));
+    assert_eq!(None, one.partial_cmp(&yen));
+    assert_ne!(one, yen);
+}
+
+#[test]
+fn currency_is_str() {
+    assert!(<Currency as Type<Postgres>>::compatible(
+        &<String as Type<Postgres>>::type_info()
+    ));
+}
+
+#[test]
+fn can_encode() {
+    let money = Money::new(1250, eur()).unwrap();
+    let mut buf = PgArgumentBuffer::default();
+    let result = money.encode_by_ref(&mut buf);
+
+    assert!(result.is_ok());
+    assert!(!buf.is_empty());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde() {
+    let money = Money::new(1250, eur()).unwrap();
+
+    let serialized = serde_json::to_string(&money).unwrap();
+    assert_eq!(r#"{"amount":1250,"currency":"EUR"}"#, serialized);
+    assert_eq!(money, serde_json::from_str(&serialized).unwrap());
+
+    assert!(serde_json::from_str::<Money>(r#"{"amount":-1,"currency":"EUR"}"#).is_err());
+    assert!(serde_json::from_str::<Money>(r#"{"amount":1,"currency":"eur"}"#).is_err());
+}
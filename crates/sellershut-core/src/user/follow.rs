@@ -0,0 +1,30 @@
+use sqlx::prelude::Type;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::custom_url::Url;
+
+/// A follow relationship between two actors, local or remote.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Follow {
+    pub follower_id: Uuid,
+    pub followee_id: Uuid,
+    pub state: FollowState,
+    /// Id of the `Follow` activity that created the relationship.
+    pub activity_id: Url,
+    pub created_at: OffsetDateTime,
+    pub updated_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[sqlx(type_name = "follow_state")]
+#[sqlx(rename_all = "PascalCase")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "PascalCase"))]
+#[cfg_attr(feature = "utoipa", derive(utoipa::ToSchema))]
+pub enum FollowState {
+    /// Waiting for the followee to answer with `Accept` or `Reject`.
+    Pending,
+    Accepted,
+}
@@ -4,6 +4,9 @@ use uuid::Uuid;
 
 use crate::{custom_url::Url, redacted_secret::RedactedSecret};
 
+mod follow;
+pub use follow::*;
+
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct User {
@@ -13,34 +16,49 @@ pub struct User {
     pub avatar: Option<String>,
     pub name: Option<String>,
     pub inbox: Url,
+    pub shared_inbox: Option<Url>,
     pub public_key: String,
     pub kind: ActorType,
-    #[cfg_attr(
-        feature = "serde",
-        serde(serialize_with = "s::serialize_redacted_secret")
-    )]
+    /// Left out when users are read, it is only loaded to sign. Never serialized, so private
+    /// keys stay out of caches and API responses.
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub private_key: Option<RedactedSecret>,
     pub created_at: OffsetDateTime,
     pub last_refreshed_at: OffsetDateTime,
     pub is_local: bool,
+    /// Keep the followers and following collections private.
+    pub hide_network: bool,
+    /// When the account was deleted. Deleted accounts are kept as tombstones so their ids are
+    /// never reused.
+    pub deleted_at: Option<OffsetDateTime>,
+    /// Other actors this account says it also is, usually the ones it moved from.
+    pub also_known_as: Vec<Url>,
+    /// The actor this account moved to.
+    pub moved_to: Option<Url>,
 }
 
-#[cfg(feature = "serde")]
-mod s {
+/// A public key a local actor signed with before its key was rotated.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RetiredKey {
+    /// The version the key was published with while it was current.
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub public_key: String,
+    pub retired_at: OffsetDateTime,
+    /// The key is no longer served after this.
+    pub expires_at: OffsetDateTime,
+}
 
-    use serde::Serializer;
-    pub(super) fn serialize_redacted_secret<S>(
-        s: &Option<crate::RedactedSecret>,
-        serializer: S,
-    ) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        match s {
-            Some(v) => serializer.serialize_str(&v.expose()),
-            None => serializer.serialize_none(),
-        }
-    }
+/// How many local people have accounts, and how many of them were recently active.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UserCounts {
+    pub total: i64,
+    /// Logged in or published an activity in the last 30 days.
+    pub active_month: i64,
+    /// Logged in or published an activity in the last 180 days.
+    pub active_halfyear: i64,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Type)]
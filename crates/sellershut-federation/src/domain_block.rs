@@ -0,0 +1,107 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+use sellershut_core::activity::BlockSeverity;
+
+/// How long the blocklist is kept in memory before it is read again.
+///
+/// Changes made through this process are seen right away, changes made elsewhere (another
+/// replica, the CLI) within this time.
+pub const BLOCK_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Lowercase a domain and drop a trailing dot, the form blocks are stored in.
+pub fn normalize_domain(domain: &str) -> String {
+    domain.trim().trim_end_matches('.').to_ascii_lowercase()
+}
+
+/// `domain` followed by each of its parent domains, like `a.b.c`, `b.c` and `c`.
+pub fn domain_and_parents(domain: &str) -> impl Iterator<Item = &str> {
+    std::iter::successors(Some(domain), |domain| {
+        domain.split_once('.').map(|(_, parent)| parent)
+    })
+}
+
+pub(crate) type Blocks = HashMap<String, BlockSeverity>;
+
+/// The block for `domain`, a block on the closest parent domain applies otherwise.
+pub(crate) fn severity(blocks: &Blocks, domain: &str) -> Option<BlockSeverity> {
+    domain_and_parents(domain).find_map(|domain| blocks.get(domain).copied())
+}
+
+#[derive(Default)]
+pub(crate) struct BlockCache {
+    snapshot: RwLock<Option<(Instant, Arc<Blocks>)>>,
+}
+
+impl BlockCache {
+    pub(crate) fn get(&self) -> Option<Arc<Blocks>> {
+        let snapshot = self.snapshot.read().unwrap_or_else(|e| e.into_inner());
+
+        snapshot
+            .as_ref()
+            .filter(|(loaded_at, _)| loaded_at.elapsed() < BLOCK_CACHE_TTL)
+            .map(|(_, blocks)| Arc::clone(blocks))
+    }
+
+    pub(crate) fn set(&self, blocks: Arc<Blocks>) {
+        let mut snapshot = self.snapshot.write().unwrap_or_else(|e| e.into_inner());
+        *snapshot = Some((Instant::now(), blocks));
+    }
+
+    pub(crate) fn invalidate(&self) {
+        let mut snapshot = self.snapshot.write().unwrap_or_else(|e| e.into_inner());
+        *snapshot = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parents() {
+        assert_eq!(
+            vec!["a.evil.example", "evil.example", "example"],
+            domain_and_parents("a.evil.example").collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn normalize() {
+        assert_eq!("evil.example", normalize_domain(" Evil.Example. "));
+    }
+
+    #[track_caller]
+    fn check_severity(domain: &str, expected_result: Option<BlockSeverity>) {
+        let blocks = Blocks::from([
+            ("evil.example".to_owned(), BlockSeverity::Suspend),
+            ("media.evil.example".to_owned(), BlockSeverity::RejectMedia),
+        ]);
+
+        assert_eq!(expected_result, severity(&blocks, domain));
+    }
+
+    #[test]
+    fn blocked_domain() {
+        check_severity("evil.example", Some(BlockSeverity::Suspend));
+    }
+
+    #[test]
+    fn blocked_subdomain() {
+        check_severity("shop.evil.example", Some(BlockSeverity::Suspend));
+    }
+
+    #[test]
+    fn closest_block_wins() {
+        check_severity("cdn.media.evil.example", Some(BlockSeverity::RejectMedia));
+    }
+
+    #[test]
+    fn unrelated_domain() {
+        check_severity("notevil.example", None);
+        check_severity("example", None);
+    }
+}
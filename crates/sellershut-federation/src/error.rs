@@ -0,0 +1,7 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum FederationError {
+    #[error("database error")]
+    Database(#[from] sqlx::Error),
+}
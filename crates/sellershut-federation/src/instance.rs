@@ -0,0 +1,188 @@
+use sellershut_core::activity::Instance;
+use sqlx::PgConnection;
+use time::{Duration, OffsetDateTime};
+use tracing::{debug, info, warn};
+use url::Url;
+
+use crate::error::FederationError;
+
+/// How long an instance has to be failing without a success in between before deliveries to it
+/// are suspended.
+pub const SUSPEND_AFTER: Duration = Duration::days(3);
+
+/// Failures in a row an instance needs before deliveries to it are suspended, so one failed
+/// attempt after a quiet stretch isn't enough.
+pub const SUSPEND_AFTER_FAILURES: i32 = 10;
+
+/// How long a suspension lasts, and how often instances are probed.
+pub const PROBE_INTERVAL: Duration = Duration::days(1);
+
+/// The domain an instance is tracked by: the host, and the port if one is set.
+pub fn instance_domain(url: &Url) -> Option<String> {
+    let host = url.host_str()?;
+
+    Some(match url.port() {
+        Some(port) => format!("{host}:{port}"),
+        None => host.to_owned(),
+    })
+}
+
+/// The end of a suspension lasting `until` if it is still in force at `now`. Lapsed
+/// suspensions stay in the row until the next success.
+fn suspension_in_force(
+    until: Option<OffsetDateTime>,
+    now: OffsetDateTime,
+) -> Option<OffsetDateTime> {
+    until.filter(|until| *until > now)
+}
+
+pub(crate) async fn record_success(
+    conn: &mut PgConnection,
+    domain: &str,
+) -> Result<(), FederationError> {
+    let instance = sqlx::query_as!(
+        Instance,
+        r#"
+        with previous as (
+            select suspended_until
+            from instance
+            where domain = $1
+        )
+        insert into instance (domain, last_success_at)
+        values ($1, now())
+        on conflict (domain) do update
+        set
+            last_success_at = now(),
+            consecutive_failures = 0,
+            first_failure_at = null,
+            suspended_until = null,
+            updated_at = now()
+        returning
+            domain,
+            software_name,
+            software_version,
+            last_success_at,
+            last_failure_at,
+            first_failure_at,
+            consecutive_failures,
+            (select suspended_until from previous) as suspended_until,
+            last_probed_at,
+            created_at,
+            updated_at
+        "#,
+        domain
+    )
+    .fetch_one(conn)
+    .await?;
+
+    if suspension_in_force(instance.suspended_until, OffsetDateTime::now_utc()).is_some() {
+        info!(domain, "instance is reachable again, suspension lifted");
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn record_failure(
+    conn: &mut PgConnection,
+    domain: &str,
+) -> Result<(), FederationError> {
+    let instance = sqlx::query_as!(
+        Instance,
+        r#"
+        insert into instance (domain, last_failure_at, first_failure_at, consecutive_failures)
+        values ($1, now(), now(), 1)
+        on conflict (domain) do update
+        set
+            last_failure_at = now(),
+            first_failure_at = coalesce(instance.first_failure_at, now()),
+            consecutive_failures = instance.consecutive_failures + 1,
+            suspended_until = case
+                when instance.first_failure_at < now() - make_interval(secs => $2)
+                    and instance.consecutive_failures + 1 >= $4
+                then now() + make_interval(secs => $3)
+                else instance.suspended_until
+            end,
+            updated_at = now()
+        returning
+            domain,
+            software_name,
+            software_version,
+            last_success_at,
+            last_failure_at,
+            first_failure_at,
+            consecutive_failures,
+            suspended_until,
+            last_probed_at,
+            created_at,
+            updated_at
+        "#,
+        domain,
+        SUSPEND_AFTER.as_seconds_f64(),
+        PROBE_INTERVAL.as_seconds_f64(),
+        SUSPEND_AFTER_FAILURES
+    )
+    .fetch_one(conn)
+    .await?;
+
+    match suspension_in_force(instance.suspended_until, OffsetDateTime::now_utc()) {
+        Some(until) => warn!(
+            domain,
+            consecutive_failures = instance.consecutive_failures,
+            %until,
+            "instance unreachable, deliveries suspended"
+        ),
+        None => debug!(
+            domain,
+            consecutive_failures = instance.consecutive_failures,
+            "instance unreachable"
+        ),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn check_domain(url: &str, expected_result: Option<&str>) {
+        let url = Url::parse(url).unwrap();
+
+        assert_eq!(expected_result, instance_domain(&url).as_deref());
+    }
+
+    #[test]
+    fn domain() {
+        check_domain("https://some.hut/inbox", Some("some.hut"));
+        check_domain("https://some.hut:443/inbox", Some("some.hut"));
+    }
+
+    #[test]
+    fn domain_with_port() {
+        check_domain("http://localhost:2210/inbox", Some("localhost:2210"));
+    }
+
+    #[test]
+    fn no_host() {
+        check_domain("urn:isbn:0451450523", None);
+    }
+
+    #[track_caller]
+    fn check_suspension(until: Option<Duration>, expected_result: bool) {
+        let now = OffsetDateTime::now_utc();
+
+        assert_eq!(
+            expected_result,
+            suspension_in_force(until.map(|until| now + until), now).is_some()
+        );
+    }
+
+    #[test]
+    fn suspension() {
+        check_suspension(None, false);
+        check_suspension(Some(PROBE_INTERVAL), true);
+        check_suspension(Some(-Duration::minutes(1)), false);
+        check_suspension(Some(Duration::ZERO), false);
+    }
+}
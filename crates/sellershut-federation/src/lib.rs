@@ -0,0 +1,759 @@
+mod domain_block;
+pub mod error;
+mod instance;
+
+pub use domain_block::{BLOCK_CACHE_TTL, domain_and_parents, normalize_domain};
+pub use instance::{PROBE_INTERVAL, SUSPEND_AFTER, instance_domain};
+
+use sellershut_core::activity::{Activity, BlockSeverity, Delivery, DomainBlock, Instance};
+use std::{collections::HashSet, sync::Arc};
+use time::{Duration, OffsetDateTime};
+use tracing::{debug, info, trace, warn};
+use url::Url;
+use uuid::Uuid;
+
+use crate::{
+    domain_block::{BlockCache, Blocks},
+    error::FederationError,
+};
+
+pub struct CreateActivity {
+    pub ap_id: Url,
+    pub actor_id: Uuid,
+    pub kind: String,
+    pub data: serde_json::Value,
+    pub is_public: bool,
+}
+
+#[async_trait::async_trait]
+pub trait FederationDriver: Send + Sync {
+    async fn record_activity(&self, data: &CreateActivity) -> Result<Activity, FederationError>;
+    async fn count_outbox(&self, actor_id: Uuid) -> Result<i64, FederationError>;
+    /// Public activities sent by an actor, newest first.
+    async fn outbox(
+        &self,
+        actor_id: Uuid,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<serde_json::Value>, FederationError>;
+    /// Queue an activity for delivery to each inbox, skipping dead ones.
+    ///
+    /// Returns the number of deliveries queued.
+    async fn enqueue_deliveries(
+        &self,
+        actor_id: Uuid,
+        activity_id: &Url,
+        payload: &serde_json::Value,
+        inboxes: &[Url],
+    ) -> Result<u64, FederationError>;
+    /// Lock up to `limit` due deliveries for `lease`, so other workers skip them.
+    async fn claim_deliveries(
+        &self,
+        limit: i64,
+        lease: Duration,
+    ) -> Result<Vec<Delivery>, FederationError>;
+    async fn complete_delivery(&self, delivery: &Delivery) -> Result<(), FederationError>;
+    async fn retry_delivery(
+        &self,
+        delivery: &Delivery,
+        error: &str,
+        next_attempt_at: OffsetDateTime,
+    ) -> Result<(), FederationError>;
+    /// Drop a delivery that kept failing and mark its inbox as dead.
+    async fn abandon_delivery(
+        &self,
+        delivery: &Delivery,
+        error: &str,
+    ) -> Result<(), FederationError>;
+    async fn get_instance(&self, domain: &str) -> Result<Option<Instance>, FederationError>;
+    async fn count_instances(&self) -> Result<i64, FederationError>;
+    async fn list_instances(
+        &self,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Instance>, FederationError>;
+    /// Record a successful contact with an instance, lifting any suspension.
+    async fn record_instance_success(&self, domain: &str) -> Result<(), FederationError>;
+    /// Record a failed contact with an instance, suspending it once it has been unreachable
+    /// for [`SUSPEND_AFTER`].
+    async fn record_instance_failure(&self, domain: &str) -> Result<(), FederationError>;
+    /// Claim up to `limit` instances that are due a probe.
+    ///
+    /// These are instances whose suspension ran out, and instances whose software is not known
+    /// yet. Each is probed at most once per [`PROBE_INTERVAL`].
+    async fn instances_to_probe(&self, limit: i64) -> Result<Vec<Instance>, FederationError>;
+    async fn set_instance_software(
+        &self,
+        domain: &str,
+        name: &str,
+        version: &str,
+    ) -> Result<(), FederationError>;
+    async fn list_domain_blocks(&self) -> Result<Vec<DomainBlock>, FederationError>;
+    /// Block a domain and its subdomains, or change an existing block.
+    async fn block_domain(
+        &self,
+        domain: &str,
+        severity: BlockSeverity,
+        public_reason: Option<&str>,
+    ) -> Result<DomainBlock, FederationError>;
+    async fn unblock_domain(&self, domain: &str) -> Result<Option<DomainBlock>, FederationError>;
+    /// The block that applies to `domain`, served from memory for [`BLOCK_CACHE_TTL`].
+    async fn domain_block_severity(
+        &self,
+        domain: &str,
+    ) -> Result<Option<BlockSeverity>, FederationError>;
+}
+
+/// How long a dead inbox is skipped before deliveries to it are attempted again.
+pub const DEAD_INBOX_RETRY: Duration = Duration::days(7);
+
+const FIRST_RETRY: Duration = Duration::seconds(30);
+const MAX_RETRY: Duration = Duration::hours(6);
+
+/// When a delivery that failed `attempts` times should be tried next.
+///
+/// The delay doubles with every attempt. Returns `None` once the next attempt would fall after
+/// `max_age`, meaning the delivery should be given up.
+pub fn next_attempt_at(
+    attempts: i32,
+    created_at: OffsetDateTime,
+    now: OffsetDateTime,
+    max_age: Duration,
+) -> Option<OffsetDateTime> {
+    let delay = 2_i32
+        .checked_pow(attempts.clamp(0, 30) as u32)
+        .map_or(MAX_RETRY, |factor| FIRST_RETRY * factor)
+        .min(MAX_RETRY);
+    let next = now + delay;
+
+    (next <= created_at + max_age).then_some(next)
+}
+
+pub struct FederationService {
+    database: sqlx::PgPool,
+    blocks: BlockCache,
+}
+
+impl FederationService {
+    pub fn new(pool: sqlx::PgPool) -> Self {
+        Self {
+            database: pool,
+            blocks: BlockCache::default(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl FederationDriver for FederationService {
+    async fn record_activity(&self, data: &CreateActivity) -> Result<Activity, FederationError> {
+        trace!(
+            ap_id = %data.ap_id,
+            kind = data.kind,
+            "recording activity"
+        );
+
+        let activity = sqlx::query_as!(
+            Activity,
+            r#"
+            insert into activity (id, ap_id, actor_id, kind, data, is_public)
+            values ($1, $2, $3, $4, $5, $6)
+            returning
+                id,
+                ap_id,
+                actor_id,
+                kind,
+                data,
+                is_public,
+                created_at
+            "#,
+            Uuid::now_v7(),
+            data.ap_id.as_str(),
+            data.actor_id,
+            data.kind,
+            data.data,
+            data.is_public
+        )
+        .fetch_one(&self.database)
+        .await?;
+
+        debug!(
+            ap_id = %data.ap_id,
+            actor_id = %data.actor_id,
+            "activity recorded"
+        );
+
+        Ok(activity)
+    }
+
+    async fn count_outbox(&self, actor_id: Uuid) -> Result<i64, FederationError> {
+        trace!(%actor_id, "counting outbox");
+
+        let count = sqlx::query_scalar!(
+            r#"
+            select count(*) as "count!"
+            from activity
+            where
+                actor_id = $1
+                and is_public = true
+            "#,
+            actor_id
+        )
+        .fetch_one(&self.database)
+        .await?;
+
+        Ok(count)
+    }
+
+    async fn outbox(
+        &self,
+        actor_id: Uuid,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<serde_json::Value>, FederationError> {
+        trace!(%actor_id, limit, offset, "listing outbox");
+
+        let activities = sqlx::query_scalar!(
+            r#"
+            select data
+            from activity
+            where
+                actor_id = $1
+                and is_public = true
+            order by created_at desc, id desc
+            limit $2
+            offset $3
+            "#,
+            actor_id,
+            limit,
+            offset
+        )
+        .fetch_all(&self.database)
+        .await?;
+
+        Ok(activities)
+    }
+
+    async fn enqueue_deliveries(
+        &self,
+        actor_id: Uuid,
+        activity_id: &Url,
+        payload: &serde_json::Value,
+        inboxes: &[Url],
+    ) -> Result<u64, FederationError> {
+        let mut seen = HashSet::new();
+        let (inboxes, domains): (Vec<_>, Vec<_>) = inboxes
+            .iter()
+            .filter(|inbox| seen.insert(inbox.as_str()))
+            .filter_map(|inbox| Some((inbox.as_str().to_owned(), instance_domain(inbox)?)))
+            .unzip();
+        let ids = inboxes.iter().map(|_| Uuid::now_v7()).collect::<Vec<_>>();
+
+        trace!(
+            %activity_id,
+            inboxes = inboxes.len(),
+            "queueing deliveries"
+        );
+
+        let result = sqlx::query!(
+            r#"
+            insert into delivery (id, inbox, domain, actor_id, activity_id, payload)
+            select t.id, t.inbox, t.domain, $4, $5, $6
+            from unnest($1::uuid[], $2::text[], $3::text[]) as t(id, inbox, domain)
+            where
+                not exists (
+                    select 1
+                    from dead_inbox as d
+                    where
+                        d.inbox = t.inbox
+                        and d.dead_at > now() - make_interval(secs => $7)
+                )
+                and not exists (
+                    select 1
+                    from instance as i
+                    where
+                        i.domain = t.domain
+                        and i.suspended_until > now()
+                )
+            "#,
+            &ids,
+            &inboxes,
+            &domains,
+            actor_id,
+            activity_id.as_str(),
+            payload,
+            DEAD_INBOX_RETRY.as_seconds_f64()
+        )
+        .execute(&self.database)
+        .await?;
+
+        let queued = result.rows_affected();
+        let skipped = inboxes.len() as u64 - queued;
+        if skipped > 0 {
+            debug!(%activity_id, skipped, "skipped dead inboxes and suspended instances");
+        }
+
+        Ok(queued)
+    }
+
+    async fn claim_deliveries(
+        &self,
+        limit: i64,
+        lease: Duration,
+    ) -> Result<Vec<Delivery>, FederationError> {
+        let deliveries = sqlx::query_as!(
+            Delivery,
+            r#"
+            update delivery as d
+            set locked_until = now() + make_interval(secs => $2)
+            from "user" as u
+            where
+                u.id = d.actor_id
+                and d.id in (
+                    select id
+                    from delivery
+                    where
+                        next_attempt_at <= now()
+                        and (locked_until is null or locked_until < now())
+                        and not exists (
+                            select 1
+                            from instance as i
+                            where
+                                i.domain = delivery.domain
+                                and i.suspended_until > now()
+                        )
+                    order by next_attempt_at
+                    limit $1
+                    for update skip locked
+                )
+            returning
+                d.id,
+                d.inbox,
+                d.domain,
+                u.ap_id as actor,
+                d.activity_id,
+                d.payload,
+                d.attempts,
+                d.created_at
+            "#,
+            limit,
+            lease.as_seconds_f64()
+        )
+        .fetch_all(&self.database)
+        .await?;
+
+        trace!(count = deliveries.len(), "claimed deliveries");
+
+        Ok(deliveries)
+    }
+
+    async fn complete_delivery(&self, delivery: &Delivery) -> Result<(), FederationError> {
+        let mut tx = self.database.begin().await?;
+
+        sqlx::query!("delete from delivery where id = $1", delivery.id)
+            .execute(&mut *tx)
+            .await?;
+
+        let inbox = delivery.inbox.inner();
+        let revived = sqlx::query!("delete from dead_inbox where inbox = $1", inbox.as_str())
+            .execute(&mut *tx)
+            .await?;
+
+        instance::record_success(&mut tx, &delivery.domain).await?;
+
+        tx.commit().await?;
+
+        if revived.rows_affected() > 0 {
+            info!(inbox = %delivery.inbox, "dead inbox is reachable again");
+        }
+
+        trace!(
+            id = %delivery.id,
+            inbox = %delivery.inbox,
+            "delivery completed"
+        );
+
+        Ok(())
+    }
+
+    async fn retry_delivery(
+        &self,
+        delivery: &Delivery,
+        error: &str,
+        next_attempt_at: OffsetDateTime,
+    ) -> Result<(), FederationError> {
+        let mut tx = self.database.begin().await?;
+
+        sqlx::query!(
+            r#"
+            update delivery
+            set
+                attempts = attempts + 1,
+                next_attempt_at = $2,
+                locked_until = null,
+                last_error = $3
+            where id = $1
+            "#,
+            delivery.id,
+            next_attempt_at,
+            error
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        instance::record_failure(&mut tx, &delivery.domain).await?;
+
+        tx.commit().await?;
+
+        debug!(
+            id = %delivery.id,
+            inbox = %delivery.inbox,
+            attempts = delivery.attempts + 1,
+            %next_attempt_at,
+            "delivery rescheduled"
+        );
+
+        Ok(())
+    }
+
+    async fn abandon_delivery(
+        &self,
+        delivery: &Delivery,
+        error: &str,
+    ) -> Result<(), FederationError> {
+        let inbox = delivery.inbox.inner();
+        let mut tx = self.database.begin().await?;
+
+        sqlx::query!("delete from delivery where id = $1", delivery.id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query!(
+            r#"
+            insert into dead_inbox (inbox, last_error)
+            values ($1, $2)
+            on conflict (inbox) do update
+            set
+                last_error = excluded.last_error,
+                dead_at = now()
+            "#,
+            inbox.as_str(),
+            error
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        instance::record_failure(&mut tx, &delivery.domain).await?;
+
+        tx.commit().await?;
+
+        warn!(
+            id = %delivery.id,
+            inbox = %delivery.inbox,
+            activity_id = %delivery.activity_id,
+            attempts = delivery.attempts + 1,
+            "delivery abandoned, inbox marked dead"
+        );
+
+        Ok(())
+    }
+
+    async fn get_instance(&self, domain: &str) -> Result<Option<Instance>, FederationError> {
+        trace!(domain, "getting instance");
+
+        let instance = sqlx::query_as!(
+            Instance,
+            r#"
+            select
+                domain,
+                software_name,
+                software_version,
+                last_success_at,
+                last_failure_at,
+                first_failure_at,
+                consecutive_failures,
+                suspended_until,
+                last_probed_at,
+                created_at,
+                updated_at
+            from instance
+            where domain = $1
+            "#,
+            domain
+        )
+        .fetch_optional(&self.database)
+        .await?;
+
+        Ok(instance)
+    }
+
+    async fn count_instances(&self) -> Result<i64, FederationError> {
+        let count = sqlx::query_scalar!(r#"select count(*) as "count!" from instance"#)
+            .fetch_one(&self.database)
+            .await?;
+
+        Ok(count)
+    }
+
+    async fn list_instances(
+        &self,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Instance>, FederationError> {
+        trace!(limit, offset, "listing instances");
+
+        let instances = sqlx::query_as!(
+            Instance,
+            r#"
+            select
+                domain,
+                software_name,
+                software_version,
+                last_success_at,
+                last_failure_at,
+                first_failure_at,
+                consecutive_failures,
+                suspended_until,
+                last_probed_at,
+                created_at,
+                updated_at
+            from instance
+            order by domain
+            limit $1
+            offset $2
+            "#,
+            limit,
+            offset
+        )
+        .fetch_all(&self.database)
+        .await?;
+
+        Ok(instances)
+    }
+
+    async fn record_instance_success(&self, domain: &str) -> Result<(), FederationError> {
+        let mut conn = self.database.acquire().await?;
+        instance::record_success(&mut conn, domain).await
+    }
+
+    async fn record_instance_failure(&self, domain: &str) -> Result<(), FederationError> {
+        let mut conn = self.database.acquire().await?;
+        instance::record_failure(&mut conn, domain).await
+    }
+
+    async fn instances_to_probe(&self, limit: i64) -> Result<Vec<Instance>, FederationError> {
+        let instances = sqlx::query_as!(
+            Instance,
+            r#"
+            update instance
+            set last_probed_at = now()
+            where domain in (
+                select domain
+                from instance
+                where
+                    (last_probed_at is null or last_probed_at < now() - make_interval(secs => $2))
+                    and (
+                        suspended_until <= now()
+                        or (suspended_until is null and software_name is null)
+                    )
+                order by last_probed_at nulls first
+                limit $1
+                for update skip locked
+            )
+            returning
+                domain,
+                software_name,
+                software_version,
+                last_success_at,
+                last_failure_at,
+                first_failure_at,
+                consecutive_failures,
+                suspended_until,
+                last_probed_at,
+                created_at,
+                updated_at
+            "#,
+            limit,
+            PROBE_INTERVAL.as_seconds_f64()
+        )
+        .fetch_all(&self.database)
+        .await?;
+
+        trace!(count = instances.len(), "claimed instances to probe");
+
+        Ok(instances)
+    }
+
+    async fn set_instance_software(
+        &self,
+        domain: &str,
+        name: &str,
+        version: &str,
+    ) -> Result<(), FederationError> {
+        sqlx::query!(
+            r#"
+            update instance
+            set
+                software_name = $2,
+                software_version = $3,
+                updated_at = now()
+            where domain = $1
+            "#,
+            domain,
+            name,
+            version
+        )
+        .execute(&self.database)
+        .await?;
+
+        debug!(domain, name, version, "instance software updated");
+
+        Ok(())
+    }
+
+    async fn list_domain_blocks(&self) -> Result<Vec<DomainBlock>, FederationError> {
+        let blocks = sqlx::query_as!(
+            DomainBlock,
+            r#"
+            select
+                domain,
+                severity as "severity: BlockSeverity",
+                public_reason,
+                created_at,
+                updated_at
+            from domain_block
+            order by domain
+            "#
+        )
+        .fetch_all(&self.database)
+        .await?;
+
+        Ok(blocks)
+    }
+
+    async fn block_domain(
+        &self,
+        domain: &str,
+        severity: BlockSeverity,
+        public_reason: Option<&str>,
+    ) -> Result<DomainBlock, FederationError> {
+        let domain = normalize_domain(domain);
+
+        let block = sqlx::query_as!(
+            DomainBlock,
+            r#"
+            insert into domain_block (domain, severity, public_reason)
+            values ($1, $2, $3)
+            on conflict (domain) do update
+            set
+                severity = excluded.severity,
+                public_reason = excluded.public_reason,
+                updated_at = now()
+            returning
+                domain,
+                severity as "severity: BlockSeverity",
+                public_reason,
+                created_at,
+                updated_at
+            "#,
+            domain,
+            severity as BlockSeverity,
+            public_reason
+        )
+        .fetch_one(&self.database)
+        .await?;
+
+        self.blocks.invalidate();
+
+        info!(domain, ?severity, "domain blocked");
+
+        Ok(block)
+    }
+
+    async fn unblock_domain(&self, domain: &str) -> Result<Option<DomainBlock>, FederationError> {
+        let domain = normalize_domain(domain);
+
+        let block = sqlx::query_as!(
+            DomainBlock,
+            r#"
+            delete from domain_block
+            where domain = $1
+            returning
+                domain,
+                severity as "severity: BlockSeverity",
+                public_reason,
+                created_at,
+                updated_at
+            "#,
+            domain
+        )
+        .fetch_optional(&self.database)
+        .await?;
+
+        self.blocks.invalidate();
+
+        if block.is_some() {
+            info!(domain, "domain unblocked");
+        }
+
+        Ok(block)
+    }
+
+    async fn domain_block_severity(
+        &self,
+        domain: &str,
+    ) -> Result<Option<BlockSeverity>, FederationError> {
+        let blocks = match self.blocks.get() {
+            Some(blocks) => blocks,
+            None => {
+                trace!("loading domain blocks");
+
+                let blocks = self
+                    .list_domain_blocks()
+                    .await?
+                    .into_iter()
+                    .map(|block| (block.domain, block.severity))
+                    .collect::<Blocks>();
+                let blocks = Arc::new(blocks);
+                self.blocks.set(Arc::clone(&blocks));
+                blocks
+            }
+        };
+
+        Ok(domain_block::severity(&blocks, &normalize_domain(domain)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn check_next_attempt(attempts: i32, age: Duration, expected_result: Option<Duration>) {
+        let now = OffsetDateTime::now_utc();
+        let max_age = Duration::days(2);
+
+        assert_eq!(
+            expected_result.map(|delay| now + delay),
+            next_attempt_at(attempts, now - age, now, max_age)
+        );
+    }
+
+    #[test]
+    fn backoff_doubles() {
+        check_next_attempt(0, Duration::ZERO, Some(Duration::seconds(30)));
+        check_next_attempt(1, Duration::ZERO, Some(Duration::minutes(1)));
+        check_next_attempt(4, Duration::ZERO, Some(Duration::minutes(8)));
+    }
+
+    #[test]
+    fn backoff_is_capped() {
+        check_next_attempt(10, Duration::ZERO, Some(Duration::hours(6)));
+        check_next_attempt(i32::MAX, Duration::ZERO, Some(Duration::hours(6)));
+    }
+
+    #[test]
+    fn gives_up_after_max_age() {
+        check_next_attempt(10, Duration::hours(43), None);
+        check_next_attempt(0, Duration::days(3), None);
+    }
+}
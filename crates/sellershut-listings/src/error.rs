@@ -0,0 +1,14 @@
+use sellershut_core::MoneyError;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ListingError {
+    #[error("database error")]
+    Database(#[from] sqlx::Error),
+    #[error("invalid listing: {0}")]
+    Invalid(&'static str),
+    #[error("invalid price: {0}")]
+    Price(#[from] MoneyError),
+    #[error("listing belongs to another user")]
+    OwnerMismatch,
+}
@@ -0,0 +1,1036 @@
+pub mod error;
+pub mod search;
+
+use sellershut_core::{
+    Money,
+    listing::{Listing, ListingCondition, ListingStatus},
+};
+use sellershut_svc::cache::Cache;
+use sellershut_utilities::cache_key::CacheKey;
+use std::time::Duration;
+use time::OffsetDateTime;
+use tracing::{debug, trace};
+use url::Url;
+use uuid::Uuid;
+
+use crate::{
+    error::ListingError,
+    search::{CategoryCount, SearchHit, SearchListings, SearchPosition},
+};
+
+const MAX_TITLE_LEN: usize = 200;
+const MAX_DESCRIPTION_LEN: usize = 10_000;
+
+pub struct CreateListing {
+    pub id: Uuid,
+    pub ap_id: Url,
+    pub owner_id: Uuid,
+    pub title: String,
+    pub description: String,
+    pub price: Money,
+    pub condition: ListingCondition,
+    pub status: ListingStatus,
+    pub is_local: bool,
+    pub published_at: Option<OffsetDateTime>,
+    pub category_id: Option<Uuid>,
+}
+
+/// Changes to a listing, fields left `None` are kept as they are.
+#[derive(Default)]
+pub struct UpdateListing {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub price: Option<Money>,
+    pub condition: Option<ListingCondition>,
+    pub status: Option<ListingStatus>,
+    /// `Some(None)` takes the listing out of its category.
+    pub category_id: Option<Option<Uuid>>,
+}
+
+impl CreateListing {
+    pub fn validate(&self) -> Result<(), ListingError> {
+        validate_title(&self.title)?;
+        validate_description(&self.description)
+    }
+}
+
+impl UpdateListing {
+    pub fn validate(&self) -> Result<(), ListingError> {
+        if let Some(title) = &self.title {
+            validate_title(title)?;
+        }
+        if let Some(description) = &self.description {
+            validate_description(description)?;
+        }
+        Ok(())
+    }
+}
+
+fn validate_title(title: &str) -> Result<(), ListingError> {
+    if title.trim().is_empty() {
+        return Err(ListingError::Invalid("title is empty"));
+    }
+    if title.chars().count() > MAX_TITLE_LEN {
+        return Err(ListingError::Invalid("title is too long"));
+    }
+    Ok(())
+}
+
+fn validate_description(description: &str) -> Result<(), ListingError> {
+    if description.chars().count() > MAX_DESCRIPTION_LEN {
+        return Err(ListingError::Invalid("description is too long"));
+    }
+    Ok(())
+}
+
+#[async_trait::async_trait]
+pub trait ListingDriver: Send + Sync {
+    async fn create_listing(&self, data: &CreateListing) -> Result<Listing, ListingError>;
+    async fn get_listing(&self, id: Uuid) -> Result<Option<Listing>, ListingError>;
+    async fn get_listing_by_ap_id(&self, ap_id: &Url) -> Result<Option<Listing>, ListingError>;
+    /// Store a listing from another instance, or replace the one stored with the same
+    /// `ap_id`.
+    async fn upsert_listing(&self, data: &CreateListing) -> Result<Listing, ListingError>;
+    /// `None` if there is no such listing.
+    async fn update_listing(
+        &self,
+        id: Uuid,
+        data: &UpdateListing,
+    ) -> Result<Option<Listing>, ListingError>;
+    /// `None` if there is no such listing.
+    async fn delete_listing(&self, id: Uuid) -> Result<Option<Listing>, ListingError>;
+    /// Archive the listings of a user that are not sold or archived yet, e.g. when the
+    /// account is deleted.
+    async fn archive_listings_by_owner(&self, owner_id: Uuid)
+    -> Result<Vec<Listing>, ListingError>;
+    /// Listings of a user, newest first, all of them unless `status` is set.
+    async fn listings_by_owner(
+        &self,
+        owner_id: Uuid,
+        status: Option<ListingStatus>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Listing>, ListingError>;
+    async fn count_listings_by_owner(
+        &self,
+        owner_id: Uuid,
+        status: Option<ListingStatus>,
+    ) -> Result<i64, ListingError>;
+    /// Active local listings, cached for [`LISTING_COUNT_CACHE_TTL`].
+    async fn count_local_listings(&self) -> Result<i64, ListingError>;
+    /// Active listings in any of `category_ids`, most recently published first.
+    ///
+    /// Listings from silenced domains are left out, as they are from searches.
+    async fn listings_in_categories(
+        &self,
+        category_ids: &[Uuid],
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Listing>, ListingError>;
+    async fn count_listings_in_categories(
+        &self,
+        category_ids: &[Uuid],
+    ) -> Result<i64, ListingError>;
+    /// Active listings found by `search`, in its order.
+    async fn search_listings(
+        &self,
+        search: &SearchListings,
+    ) -> Result<Vec<SearchHit>, ListingError>;
+    /// Active listings found by `search` in each category they are in, whatever categories
+    /// it is limited to.
+    async fn search_category_counts(
+        &self,
+        search: &SearchListings,
+    ) -> Result<Vec<CategoryCount>, ListingError>;
+}
+
+pub struct ListingService {
+    database: sqlx::PgPool,
+    cache: Cache,
+}
+
+#[async_trait::async_trait]
+impl ListingDriver for ListingService {
+    async fn create_listing(&self, data: &CreateListing) -> Result<Listing, ListingError> {
+        trace!(
+            listing_id = %data.id,
+            owner_id = %data.owner_id,
+            local = data.is_local,
+            "creating listing"
+        );
+
+        data.validate()?;
+
+        let listing = sqlx::query_as!(
+            Listing,
+            r#"
+            insert into listing
+            (
+                id,
+                ap_id,
+                owner_id,
+                title,
+                description,
+                price,
+                currency,
+                condition,
+                status,
+                is_local,
+                published_at,
+                category_id
+            )
+            values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            returning
+                id,
+                ap_id,
+                owner_id,
+                title,
+                description,
+                (price, currency)::money_amount as "price!: Money",
+                condition as "condition: ListingCondition",
+                status as "status: ListingStatus",
+                is_local,
+                created_at,
+                updated_at,
+                published_at,
+                category_id
+            "#,
+            data.id,
+            data.ap_id.as_str(),
+            data.owner_id,
+            data.title.trim(),
+            data.description,
+            data.price.amount(),
+            data.price.currency() as _,
+            data.condition as _,
+            data.status as _,
+            data.is_local,
+            data.published_at,
+            data.category_id,
+        )
+        .fetch_one(&self.database)
+        .await
+        .map_err(write_error)?;
+
+        self.cache_listing(&listing).await;
+        if listing.is_local {
+            self.invalidate_cache_key(CacheKey::LocalListingCount).await;
+        }
+
+        Ok(listing)
+    }
+
+    async fn get_listing(&self, id: Uuid) -> Result<Option<Listing>, ListingError> {
+        trace!(listing_id = %id, "getting listing");
+
+        let cache_key = CacheKey::ListingById(id);
+
+        if let Some(listing) = self.get_cached_listing(cache_key).await {
+            return Ok(Some(listing));
+        }
+
+        debug!(listing_id = %id, "loading listing from database");
+
+        let listing = sqlx::query_as!(
+            Listing,
+            r#"
+            select
+                id,
+                ap_id,
+                owner_id,
+                title,
+                description,
+                (price, currency)::money_amount as "price!: Money",
+                condition as "condition: ListingCondition",
+                status as "status: ListingStatus",
+                is_local,
+                created_at,
+                updated_at,
+                published_at,
+                category_id
+            from listing
+            where id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.database)
+        .await?;
+
+        if let Some(listing) = &listing {
+            self.cache_listing(listing).await;
+        }
+
+        Ok(listing)
+    }
+
+    async fn get_listing_by_ap_id(&self, ap_id: &Url) -> Result<Option<Listing>, ListingError> {
+        trace!(%ap_id, "getting listing by ActivityPub id");
+
+        let listing = sqlx::query_as!(
+            Listing,
+            r#"
+            select
+                id,
+                ap_id,
+                owner_id,
+                title,
+                description,
+                (price, currency)::money_amount as "price!: Money",
+                condition as "condition: ListingCondition",
+                status as "status: ListingStatus",
+                is_local,
+                created_at,
+                updated_at,
+                published_at,
+                category_id
+            from listing
+            where ap_id = $1
+            "#,
+            ap_id.as_str()
+        )
+        .fetch_optional(&self.database)
+        .await?;
+
+        Ok(listing)
+    }
+
+    async fn upsert_listing(&self, data: &CreateListing) -> Result<Listing, ListingError> {
+        trace!(
+            ap_id = %data.ap_id,
+            owner_id = %data.owner_id,
+            "upserting listing"
+        );
+
+        data.validate()?;
+
+        let listing = sqlx::query_as!(
+            Listing,
+            r#"
+            insert into listing
+            (
+                id,
+                ap_id,
+                owner_id,
+                title,
+                description,
+                price,
+                currency,
+                condition,
+                status,
+                is_local,
+                published_at,
+                category_id
+            )
+            values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            on conflict (ap_id) do update
+            set
+                title = excluded.title,
+                description = excluded.description,
+                price = excluded.price,
+                currency = excluded.currency,
+                condition = excluded.condition,
+                status = excluded.status,
+                published_at = coalesce(listing.published_at, excluded.published_at),
+                updated_at = now()
+            where listing.owner_id = excluded.owner_id
+            returning
+                id,
+                ap_id,
+                owner_id,
+                title,
+                description,
+                (price, currency)::money_amount as "price!: Money",
+                condition as "condition: ListingCondition",
+                status as "status: ListingStatus",
+                is_local,
+                created_at,
+                updated_at,
+                published_at,
+                category_id
+            "#,
+            data.id,
+            data.ap_id.as_str(),
+            data.owner_id,
+            data.title.trim(),
+            data.description,
+            data.price.amount(),
+            data.price.currency() as _,
+            data.condition as _,
+            data.status as _,
+            data.is_local,
+            data.published_at,
+            data.category_id,
+        )
+        .fetch_optional(&self.database)
+        .await?
+        .ok_or(ListingError::OwnerMismatch)?;
+
+        self.cache_listing(&listing).await;
+        if listing.is_local {
+            self.invalidate_cache_key(CacheKey::LocalListingCount).await;
+        }
+
+        Ok(listing)
+    }
+
+    async fn update_listing(
+        &self,
+        id: Uuid,
+        data: &UpdateListing,
+    ) -> Result<Option<Listing>, ListingError> {
+        trace!(listing_id = %id, "updating listing");
+
+        data.validate()?;
+
+        let listing = sqlx::query_as!(
+            Listing,
+            r#"
+            update listing
+            set
+                title = coalesce($2, title),
+                description = coalesce($3, description),
+                price = coalesce($4, price),
+                currency = coalesce($5, currency),
+                condition = coalesce($6, condition),
+                status = coalesce($7, status),
+                category_id = case when $8 then $9 else category_id end,
+                published_at = coalesce(
+                    published_at,
+                    case when $7 = 'Active'::listing_status then now() end
+                ),
+                updated_at = now()
+            where id = $1
+            returning
+                id,
+                ap_id,
+                owner_id,
+                title,
+                description,
+                (price, currency)::money_amount as "price!: Money",
+                condition as "condition: ListingCondition",
+                status as "status: ListingStatus",
+                is_local,
+                created_at,
+                updated_at,
+                published_at,
+                category_id
+            "#,
+            id,
+            data.title.as_deref().map(str::trim),
+            data.description,
+            data.price.map(|price| price.amount()),
+            data.price.map(|price| price.currency()) as _,
+            data.condition as _,
+            data.status as _,
+            data.category_id.is_some(),
+            data.category_id.flatten(),
+        )
+        .fetch_optional(&self.database)
+        .await
+        .map_err(write_error)?;
+
+        if let Some(listing) = &listing {
+            self.cache_listing(listing).await;
+            if listing.is_local && data.status.is_some() {
+                self.invalidate_cache_key(CacheKey::LocalListingCount).await;
+            }
+        }
+
+        Ok(listing)
+    }
+
+    async fn delete_listing(&self, id: Uuid) -> Result<Option<Listing>, ListingError> {
+        trace!(listing_id = %id, "deleting listing");
+
+        let listing = sqlx::query_as!(
+            Listing,
+            r#"
+            delete from listing
+            where id = $1
+            returning
+                id,
+                ap_id,
+                owner_id,
+                title,
+                description,
+                (price, currency)::money_amount as "price!: Money",
+                condition as "condition: ListingCondition",
+                status as "status: ListingStatus",
+                is_local,
+                created_at,
+                updated_at,
+                published_at,
+                category_id
+            "#,
+            id
+        )
+        .fetch_optional(&self.database)
+        .await?;
+
+        if let Some(listing) = &listing {
+            self.invalidate_cache_key(CacheKey::ListingById(listing.id))
+                .await;
+            if listing.is_local {
+                self.invalidate_cache_key(CacheKey::LocalListingCount).await;
+            }
+        }
+
+        Ok(listing)
+    }
+
+    async fn archive_listings_by_owner(
+        &self,
+        owner_id: Uuid,
+    ) -> Result<Vec<Listing>, ListingError> {
+        trace!(%owner_id, "archiving listings of owner");
+
+        let listings = sqlx::query_as!(
+            Listing,
+            r#"
+            update listing
+            set
+                status = 'Archived',
+                updated_at = now()
+            where
+                owner_id = $1
+                and status in ('Draft', 'Active')
+            returning
+                id,
+                ap_id,
+                owner_id,
+                title,
+                description,
+                (price, currency)::money_amount as "price!: Money",
+                condition as "condition: ListingCondition",
+                status as "status: ListingStatus",
+                is_local,
+                created_at,
+                updated_at,
+                published_at,
+                category_id
+            "#,
+            owner_id
+        )
+        .fetch_all(&self.database)
+        .await?;
+
+        for listing in &listings {
+            self.cache_listing(listing).await;
+        }
+        if listings.iter().any(|listing| listing.is_local) {
+            self.invalidate_cache_key(CacheKey::LocalListingCount).await;
+        }
+
+        Ok(listings)
+    }
+
+    async fn listings_by_owner(
+        &self,
+        owner_id: Uuid,
+        status: Option<ListingStatus>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Listing>, ListingError> {
+        trace!(%owner_id, ?status, "listing listings of owner");
+
+        let listings = sqlx::query_as!(
+            Listing,
+            r#"
+            select
+                id,
+                ap_id,
+                owner_id,
+                title,
+                description,
+                (price, currency)::money_amount as "price!: Money",
+                condition as "condition: ListingCondition",
+                status as "status: ListingStatus",
+                is_local,
+                created_at,
+                updated_at,
+                published_at,
+                category_id
+            from listing
+            where
+                owner_id = $1
+                and ($2::listing_status is null or status = $2)
+            order by created_at desc, id desc
+            limit $3
+            offset $4
+            "#,
+            owner_id,
+            status as _,
+            limit,
+            offset
+        )
+        .fetch_all(&self.database)
+        .await?;
+
+        Ok(listings)
+    }
+
+    async fn count_listings_by_owner(
+        &self,
+        owner_id: Uuid,
+        status: Option<ListingStatus>,
+    ) -> Result<i64, ListingError> {
+        let count = sqlx::query_scalar!(
+            r#"
+            select count(*) as "count!"
+            from listing
+            where
+                owner_id = $1
+                and ($2::listing_status is null or status = $2)
+            "#,
+            owner_id,
+            status as _
+        )
+        .fetch_one(&self.database)
+        .await?;
+
+        Ok(count)
+    }
+
+    async fn count_local_listings(&self) -> Result<i64, ListingError> {
+        let cache_key = CacheKey::LocalListingCount;
+
+        match self.cache.get::<Vec<u8>>(cache_key).await {
+            Ok(Some(cached)) => match serde_json::from_slice(&cached) {
+                Ok(count) => return Ok(count),
+                Err(error) => {
+                    debug!(cache_key = %cache_key, error = %error, "cached listing count is invalid");
+                }
+            },
+            Ok(None) => debug!(cache_key = %cache_key, "listing count cache miss"),
+            Err(error) => {
+                debug!(
+                    cache_key = %cache_key,
+                    error = %error,
+                    "cache read failed; falling back to database"
+                );
+            }
+        }
+
+        debug!("counting local listings");
+
+        let count = sqlx::query_scalar!(
+            r#"
+            select count(*) as "count!"
+            from listing
+            where
+                is_local
+                and status = 'Active'
+            "#
+        )
+        .fetch_one(&self.database)
+        .await?;
+
+        match serde_json::to_vec(&count) {
+            Ok(value) => {
+                if let Err(error) = self
+                    .cache
+                    .set_ex(cache_key, value.as_slice(), LISTING_COUNT_CACHE_TTL)
+                    .await
+                {
+                    debug!(cache_key = %cache_key, error = %error, "failed to cache listing count");
+                }
+            }
+            Err(error) => {
+                debug!(cache_key = %cache_key, error = %error, "failed to serialize listing count");
+            }
+        }
+
+        Ok(count)
+    }
+
+    async fn listings_in_categories(
+        &self,
+        category_ids: &[Uuid],
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Listing>, ListingError> {
+        trace!(
+            categories = category_ids.len(),
+            "listing listings in categories"
+        );
+
+        let listings = sqlx::query_as!(
+            Listing,
+            r#"
+            select
+                id,
+                ap_id,
+                owner_id,
+                title,
+                description,
+                (price, currency)::money_amount as "price!: Money",
+                condition as "condition: ListingCondition",
+                status as "status: ListingStatus",
+                is_local,
+                created_at,
+                updated_at,
+                published_at,
+                category_id
+            from listing
+            where
+                category_id = any($1)
+                and status = 'Active'
+                and (is_local or not domain_silenced(ap_id))
+                and exists (
+                    select 1 from "user"
+                    where "user".id = owner_id and "user".deleted_at is null
+                )
+            order by published_at desc nulls last, id desc
+            limit $2
+            offset $3
+            "#,
+            category_ids,
+            limit,
+            offset
+        )
+        .fetch_all(&self.database)
+        .await?;
+
+        Ok(listings)
+    }
+
+    async fn count_listings_in_categories(
+        &self,
+        category_ids: &[Uuid],
+    ) -> Result<i64, ListingError> {
+        let count = sqlx::query_scalar!(
+            r#"
+            select count(*) as "count!"
+            from listing
+            where
+                category_id = any($1)
+                and status = 'Active'
+                and (is_local or not domain_silenced(ap_id))
+                and exists (
+                    select 1 from "user"
+                    where "user".id = owner_id and "user".deleted_at is null
+                )
+            "#,
+            category_ids
+        )
+        .fetch_one(&self.database)
+        .await?;
+
+        Ok(count)
+    }
+
+    async fn search_listings(
+        &self,
+        search: &SearchListings,
+    ) -> Result<Vec<SearchHit>, ListingError> {
+        trace!(sort = search.sort.as_str(), "searching listings");
+
+        search.validate()?;
+
+        // Keyset pagination needs one key to compare, so every order is turned into a number
+        // that goes down. Prices are at most 2^53 - 1 and fit in a double exactly.
+        let hits = sqlx::query!(
+            r#"
+            with found as (
+                select
+                    listing.*,
+                    case
+                        when $1::text is null then 0
+                        else ts_rank(search, websearch_to_tsquery('simple', $1))
+                            + word_similarity($1, title)
+                    end as rank
+                from listing
+                where
+                    status = 'Active'
+                    and (is_local or not domain_silenced(ap_id))
+                    and exists (
+                        select 1 from "user"
+                        where "user".id = owner_id and "user".deleted_at is null
+                    )
+                    and (
+                        $1::text is null
+                        or search @@ websearch_to_tsquery('simple', $1)
+                        or $1 <% title
+                    )
+                    and ($2::uuid[] is null or category_id = any($2))
+                    and ($3::text is null or currency = $3)
+                    and ($4::bigint is null or price >= $4)
+                    and ($5::bigint is null or price <= $5)
+                    and ($6::listing_condition is null or condition = $6)
+                    and ($7::boolean is null or is_local = $7)
+                    and ($8::uuid is null or owner_id = $8)
+            ),
+            keyed as (
+                select
+                    found.*,
+                    case
+                        when $9 = 'Relevance' and $1::text is not null then rank::float8
+                        when $9 = 'PriceAsc' then -price::float8
+                        when $9 = 'PriceDesc' then price::float8
+                        else extract(epoch from coalesce(published_at, created_at))::float8
+                    end as sort_key
+                from found
+            )
+            select
+                id,
+                ap_id as "ap_id: sellershut_core::Url",
+                owner_id,
+                title,
+                description,
+                (price, currency)::money_amount as "price!: Money",
+                condition as "condition: ListingCondition",
+                status as "status: ListingStatus",
+                is_local,
+                created_at,
+                updated_at,
+                published_at,
+                category_id,
+                sort_key as "sort_key!"
+            from keyed
+            where $10::float8 is null or (sort_key, id) < ($10, $11::uuid)
+            order by sort_key desc, id desc
+            limit $12
+            "#,
+            search.text.as_deref().map(str::trim),
+            search.category_ids.as_deref(),
+            search.currency(),
+            search.min_price.map(|price| price.amount()),
+            search.max_price.map(|price| price.amount()),
+            search.condition as _,
+            search.is_local,
+            search.owner_id,
+            search.sort.as_str(),
+            search.after.map(|after| after.sort_key),
+            search.after.map(|after| after.id),
+            search.limit,
+        )
+        .fetch_all(&self.database)
+        .await?
+        .into_iter()
+        .map(|row| SearchHit {
+            position: SearchPosition {
+                sort_key: row.sort_key,
+                id: row.id,
+            },
+            listing: Listing {
+                id: row.id,
+                ap_id: row.ap_id,
+                owner_id: row.owner_id,
+                title: row.title,
+                description: row.description,
+                price: row.price,
+                condition: row.condition,
+                status: row.status,
+                is_local: row.is_local,
+                created_at: row.created_at,
+                updated_at: row.updated_at,
+                published_at: row.published_at,
+                category_id: row.category_id,
+            },
+        })
+        .collect();
+
+        Ok(hits)
+    }
+
+    async fn search_category_counts(
+        &self,
+        search: &SearchListings,
+    ) -> Result<Vec<CategoryCount>, ListingError> {
+        search.validate()?;
+
+        let counts = sqlx::query_as!(
+            CategoryCount,
+            r#"
+            select
+                category_id as "category_id!",
+                count(*) as "count!"
+            from listing
+            where
+                status = 'Active'
+                and (is_local or not domain_silenced(ap_id))
+                and exists (
+                    select 1 from "user"
+                    where "user".id = owner_id and "user".deleted_at is null
+                )
+                and category_id is not null
+                and (
+                    $1::text is null
+                    or search @@ websearch_to_tsquery('simple', $1)
+                    or $1 <% title
+                )
+                and ($2::text is null or currency = $2)
+                and ($3::bigint is null or price >= $3)
+                and ($4::bigint is null or price <= $4)
+                and ($5::listing_condition is null or condition = $5)
+                and ($6::boolean is null or is_local = $6)
+                and ($7::uuid is null or owner_id = $7)
+            group by category_id
+            "#,
+            search.text.as_deref().map(str::trim),
+            search.currency(),
+            search.min_price.map(|price| price.amount()),
+            search.max_price.map(|price| price.amount()),
+            search.condition as _,
+            search.is_local,
+            search.owner_id,
+        )
+        .fetch_all(&self.database)
+        .await?;
+
+        Ok(counts)
+    }
+}
+
+/// Errors of writing a listing that are the caller's fault.
+fn write_error(e: sqlx::Error) -> ListingError {
+    match e.as_database_error() {
+        Some(error) if error.is_foreign_key_violation() => {
+            ListingError::Invalid("unknown category")
+        }
+        _ => e.into(),
+    }
+}
+
+const LISTING_CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+
+pub const LISTING_COUNT_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+impl ListingService {
+    pub fn new(pool: sqlx::PgPool, cache: Cache) -> Self {
+        Self {
+            database: pool,
+            cache,
+        }
+    }
+
+    async fn get_cached_listing(&self, key: CacheKey<'_>) -> Option<Listing> {
+        trace!(cache_key = %key, "checking listing cache");
+
+        let cached = match self.cache.get::<Vec<u8>>(key).await {
+            Ok(Some(value)) => value,
+            Ok(None) => {
+                debug!(cache_key = %key, "listing cache miss");
+                return None;
+            }
+            Err(error) => {
+                debug!(
+                    cache_key = %key,
+                    error = %error,
+                    "cache read failed; falling back to database"
+                );
+                return None;
+            }
+        };
+
+        match serde_json::from_slice::<Listing>(&cached) {
+            Ok(listing) => {
+                trace!(cache_key = %key, "listing cache hit");
+                Some(listing)
+            }
+            Err(error) => {
+                debug!(
+                    cache_key = %key,
+                    error = %error,
+                    "cached listing could not be deserialized; evicting entry"
+                );
+                self.invalidate_cache_key(key).await;
+                None
+            }
+        }
+    }
+
+    async fn cache_listing(&self, listing: &Listing) {
+        let key = CacheKey::ListingById(listing.id);
+
+        let value = match serde_json::to_vec(listing) {
+            Ok(value) => value,
+            Err(error) => {
+                debug!(
+                    listing_id = %listing.id,
+                    error = %error,
+                    "failed to serialize listing for cache"
+                );
+                return;
+            }
+        };
+
+        trace!(cache_key = %key, "populating listing cache");
+
+        if let Err(error) = self.cache.set_ex(key, &value, LISTING_CACHE_TTL).await {
+            debug!(
+                cache_key = %key,
+                error = %error,
+                "failed to populate listing cache"
+            );
+        }
+    }
+
+    async fn invalidate_cache_key(&self, key: CacheKey<'_>) {
+        trace!(cache_key = %key, "invalidating listing cache");
+
+        if let Err(error) = self.cache.del(key).await {
+            debug!(
+                cache_key = %key,
+                error = %error,
+                "failed to invalidate listing cache"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create(title: &str) -> CreateListing {
+        CreateListing {
+            id: Uuid::now_v7(),
+            ap_id: Url::parse("http://localhost:8080/listings/1").unwrap(),
+            owner_id: Uuid::now_v7(),
+            title: title.to_owned(),
+            description: String::new(),
+            price: Money::new(100, "EUR".parse().unwrap()).unwrap(),
+            condition: ListingCondition::Good,
+            status: ListingStatus::Draft,
+            is_local: true,
+            published_at: None,
+            category_id: None,
+        }
+    }
+
+    #[track_caller]
+    fn check_create(title: &str, expected_result: bool) {
+        assert_eq!(expected_result, create(title).validate().is_ok());
+    }
+
+    #[test]
+    fn title() {
+        check_create("Bike", true);
+        check_create("", false);
+        check_create("   ", false);
+        check_create(&"a".repeat(MAX_TITLE_LEN), true);
+        check_create(&"a".repeat(MAX_TITLE_LEN + 1), false);
+    }
+
+    #[test]
+    fn update_only_checks_set_fields() {
+        assert!(UpdateListing::default().validate().is_ok());
+        assert!(
+            UpdateListing {
+                title: Some(String::new()),
+                ..Default::default()
+            }
+            .validate()
+            .is_err()
+        );
+        assert!(
+            UpdateListing {
+                description: Some("a".repeat(MAX_DESCRIPTION_LEN + 1)),
+                ..Default::default()
+            }
+            .validate()
+            .is_err()
+        );
+    }
+}
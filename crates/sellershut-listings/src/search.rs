@@ -0,0 +1,221 @@
+use sellershut_core::{
+    Currency, Money,
+    listing::{Listing, ListingCondition, SearchSort},
+};
+use uuid::Uuid;
+
+use crate::error::ListingError;
+
+const MAX_TEXT_LEN: usize = 200;
+
+/// A search of active listings, filters left `None` match everything.
+pub struct SearchListings {
+    /// Words to look for in titles and descriptions.
+    pub text: Option<String>,
+    pub category_ids: Option<Vec<Uuid>>,
+    /// Listings priced in this currency, which sorting by price needs.
+    pub currency: Option<Currency>,
+    /// Listings in the currency of the price, costing at least this much.
+    pub min_price: Option<Money>,
+    /// Listings in the currency of the price, costing at most this much.
+    pub max_price: Option<Money>,
+    pub condition: Option<ListingCondition>,
+    pub is_local: Option<bool>,
+    pub owner_id: Option<Uuid>,
+    pub sort: SearchSort,
+    /// Only listings after this one in `sort` order.
+    pub after: Option<SearchPosition>,
+    pub limit: i64,
+}
+
+/// Where a listing is in the order of a search.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SearchPosition {
+    pub sort_key: f64,
+    pub id: Uuid,
+}
+
+pub struct SearchHit {
+    pub listing: Listing,
+    pub position: SearchPosition,
+}
+
+/// Number of listings found in a category, not counting the ones below it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CategoryCount {
+    pub category_id: Uuid,
+    pub count: i64,
+}
+
+impl SearchListings {
+    pub fn validate(&self) -> Result<(), ListingError> {
+        if let Some(text) = &self.text {
+            if text.trim().is_empty() {
+                return Err(ListingError::Invalid("search text is empty"));
+            }
+            if text.chars().count() > MAX_TEXT_LEN {
+                return Err(ListingError::Invalid("search text is too long"));
+            }
+        }
+
+        if let (Some(min), Some(max)) = (self.min_price, self.max_price) {
+            if min.currency() != max.currency() {
+                return Err(ListingError::Invalid("price range is in two currencies"));
+            }
+            if min > max {
+                return Err(ListingError::Invalid("minimum price is above the maximum"));
+            }
+        }
+
+        if let Some(currency) = self.currency
+            && [self.min_price, self.max_price]
+                .into_iter()
+                .flatten()
+                .any(|price| price.currency() != currency)
+        {
+            return Err(ListingError::Invalid("price range is in another currency"));
+        }
+
+        // Amounts in different currencies can't be compared.
+        if matches!(self.sort, SearchSort::PriceAsc | SearchSort::PriceDesc)
+            && self.currency().is_none()
+        {
+            return Err(ListingError::Invalid("sorting by price needs a currency"));
+        }
+
+        Ok(())
+    }
+
+    /// The currency searched in, or the one of the price range.
+    pub(crate) fn currency(&self) -> Option<String> {
+        self.currency
+            .or(self
+                .min_price
+                .or(self.max_price)
+                .map(|price| price.currency()))
+            .map(|currency| currency.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search() -> SearchListings {
+        SearchListings {
+            text: None,
+            category_ids: None,
+            currency: None,
+            min_price: None,
+            max_price: None,
+            condition: None,
+            is_local: None,
+            owner_id: None,
+            sort: SearchSort::Newest,
+            after: None,
+            limit: 20,
+        }
+    }
+
+    fn price(amount: i64, currency: &str) -> Option<Money> {
+        Some(Money::new(amount, currency.parse().unwrap()).unwrap())
+    }
+
+    #[track_caller]
+    fn check_text(text: &str, expected_result: bool) {
+        let search = SearchListings {
+            text: Some(text.to_owned()),
+            ..search()
+        };
+
+        assert_eq!(expected_result, search.validate().is_ok());
+    }
+
+    #[track_caller]
+    fn check_price_range(min: Option<Money>, max: Option<Money>, expected_result: bool) {
+        let search = SearchListings {
+            min_price: min,
+            max_price: max,
+            ..search()
+        };
+
+        assert_eq!(expected_result, search.validate().is_ok());
+    }
+
+    #[test]
+    fn text() {
+        assert!(search().validate().is_ok());
+        check_text("bike", true);
+        check_text(" ", false);
+        check_text(&"a".repeat(MAX_TEXT_LEN), true);
+        check_text(&"a".repeat(MAX_TEXT_LEN + 1), false);
+    }
+
+    #[track_caller]
+    fn check_sort(sort: SearchSort, currency: Option<&str>, expected_result: bool) {
+        let search = SearchListings {
+            sort,
+            currency: currency.map(|currency| currency.parse().unwrap()),
+            ..search()
+        };
+
+        assert_eq!(expected_result, search.validate().is_ok());
+    }
+
+    #[test]
+    fn price_range() {
+        check_price_range(price(100, "EUR"), None, true);
+        check_price_range(None, price(100, "EUR"), true);
+        check_price_range(price(100, "EUR"), price(100, "EUR"), true);
+        check_price_range(price(100, "EUR"), price(200, "EUR"), true);
+        check_price_range(price(200, "EUR"), price(100, "EUR"), false);
+        check_price_range(price(100, "EUR"), price(200, "USD"), false);
+    }
+
+    #[test]
+    fn price_range_in_searched_currency() {
+        let search = SearchListings {
+            currency: Some("EUR".parse().unwrap()),
+            min_price: price(100, "EUR"),
+            ..search()
+        };
+        assert!(search.validate().is_ok());
+
+        let search = SearchListings {
+            currency: Some("USD".parse().unwrap()),
+            ..search
+        };
+        assert!(search.validate().is_err());
+    }
+
+    #[test]
+    fn price_sorts_need_currency() {
+        check_sort(SearchSort::Newest, None, true);
+        check_sort(SearchSort::Relevance, None, true);
+        check_sort(SearchSort::PriceAsc, None, false);
+        check_sort(SearchSort::PriceDesc, None, false);
+        check_sort(SearchSort::PriceAsc, Some("EUR"), true);
+        check_sort(SearchSort::PriceDesc, Some("EUR"), true);
+    }
+
+    #[test]
+    fn currency() {
+        assert_eq!(None, search().currency());
+        assert_eq!(
+            Some("EUR".to_owned()),
+            SearchListings {
+                currency: Some("EUR".parse().unwrap()),
+                ..search()
+            }
+            .currency()
+        );
+        assert_eq!(
+            Some("USD".to_owned()),
+            SearchListings {
+                max_price: price(100, "USD"),
+                ..search()
+            }
+            .currency()
+        );
+    }
+}
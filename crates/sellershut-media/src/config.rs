@@ -0,0 +1,77 @@
+use std::path::PathBuf;
+
+use sellershut_core::RedactedSecret;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct Config {
+    /// Largest file that can be uploaded, in bytes
+    pub max_size: usize,
+    pub backend: Backend,
+    pub filesystem: Option<FilesystemConfig>,
+    pub s3: Option<S3Config>,
+    pub processing: Processing,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            max_size: 10 * 1024 * 1024,
+            backend: Backend::Filesystem,
+            filesystem: Some(FilesystemConfig {
+                path: PathBuf::from("media"),
+            }),
+            s3: None,
+            processing: Default::default(),
+        }
+    }
+}
+
+/// Stripping metadata from uploads and making their thumbnails
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct Processing {
+    /// Seconds between polls for new uploads
+    pub poll_interval: u64,
+    /// Files processed per poll
+    pub batch_size: i64,
+}
+
+impl Default for Processing {
+    fn default() -> Self {
+        Self {
+            poll_interval: 5,
+            batch_size: 10,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Backend {
+    Filesystem,
+    S3,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct FilesystemConfig {
+    /// Directory files are kept in, created if it does not exist
+    pub path: PathBuf,
+}
+
+/// Any S3 compatible object storage
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct S3Config {
+    pub endpoint: Url,
+    pub bucket: String,
+    pub region: String,
+    pub access_key: String,
+    pub secret_key: RedactedSecret,
+    /// Put the bucket in the path rather than the host name, most self hosted stores need it
+    #[serde(default)]
+    pub path_style: bool,
+}
@@ -0,0 +1,21 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum MediaError {
+    #[error("database error")]
+    Database(#[from] sqlx::Error),
+    #[error("media store error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("media store error: {0}")]
+    Storage(String),
+    #[error("invalid media: {0}")]
+    Invalid(&'static str),
+    /// The file is not an image we can read, trying again won't help.
+    #[error("unprocessable media: {0}")]
+    Unprocessable(String),
+    #[error("invalid configuration: {0}")]
+    InvalidConfig(String),
+    /// Some of the files to attach do not exist, belong to someone else or are used elsewhere.
+    #[error("media is unavailable")]
+    Unavailable,
+}
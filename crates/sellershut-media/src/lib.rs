@@ -0,0 +1,607 @@
+pub mod config;
+pub mod error;
+pub mod process;
+mod sniff;
+pub mod store;
+
+pub use sniff::sniff_content_type;
+
+use sellershut_core::media::{Media, MediaStatus};
+use time::{Duration, OffsetDateTime};
+use tracing::trace;
+use url::Url;
+use uuid::Uuid;
+
+use crate::error::MediaError;
+
+const MAX_DESCRIPTION_LEN: usize = 1_500;
+
+/// Most files a listing can have.
+pub const MAX_LISTING_MEDIA: usize = 20;
+
+/// Times processing a file is tried before it is given up.
+pub const MAX_PROCESSING_ATTEMPTS: i32 = 5;
+
+pub struct CreateMedia {
+    pub id: Uuid,
+    pub owner_id: Uuid,
+    pub content_type: String,
+    pub size: i64,
+    pub storage_key: String,
+    pub description: Option<String>,
+}
+
+/// A file of a listing from another instance, as its instance describes it.
+pub struct CreateRemoteMedia {
+    pub url: Url,
+    pub content_type: Option<String>,
+    pub description: Option<String>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub blurhash: Option<String>,
+}
+
+/// What processing found out about a file.
+pub struct ProcessedMedia {
+    pub content_type: String,
+    pub size: i64,
+    pub width: i32,
+    pub height: i32,
+    pub blurhash: String,
+    pub thumbnail_key: String,
+}
+
+impl CreateMedia {
+    pub fn validate(&self) -> Result<(), MediaError> {
+        if self
+            .description
+            .as_ref()
+            .is_some_and(|description| description.chars().count() > MAX_DESCRIPTION_LEN)
+        {
+            return Err(MediaError::Invalid("description is too long"));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+pub trait MediaDriver: Send + Sync {
+    async fn create_media(&self, data: &CreateMedia) -> Result<Media, MediaError>;
+    async fn get_media(&self, id: Uuid) -> Result<Option<Media>, MediaError>;
+    /// `None` if there is no such file.
+    async fn delete_media(&self, id: Uuid) -> Result<Option<Media>, MediaError>;
+    /// Make `media`, in that order, the files of a listing, replacing the ones it had.
+    ///
+    /// Every file must be an upload of `owner_id` that is not used by another listing, or
+    /// nothing changes and [`MediaError::Unavailable`] is returned.
+    async fn attach_media(
+        &self,
+        listing_id: Uuid,
+        owner_id: Uuid,
+        media: &[Uuid],
+    ) -> Result<Vec<Media>, MediaError>;
+    /// Whether [`MediaDriver::attach_media`] would accept `media` for a listing, without
+    /// changing anything.
+    async fn check_media(
+        &self,
+        listing_id: Uuid,
+        owner_id: Uuid,
+        media: &[Uuid],
+    ) -> Result<(), MediaError>;
+    /// Make `media`, in that order, the files of a remote listing of `owner_id`, replacing the
+    /// ones it had.
+    ///
+    /// Only where the files are is kept, they are never fetched. Files past
+    /// [`MAX_LISTING_MEDIA`] are dropped, like descriptions that are too long.
+    async fn replace_remote_media(
+        &self,
+        listing_id: Uuid,
+        owner_id: Uuid,
+        media: &[CreateRemoteMedia],
+    ) -> Result<Vec<Media>, MediaError>;
+    /// Files of a listing, in order.
+    async fn listing_media(&self, listing_id: Uuid) -> Result<Vec<Media>, MediaError>;
+    /// Files of all of `listing_ids`, in order within each listing.
+    async fn media_for_listings(&self, listing_ids: &[Uuid]) -> Result<Vec<Media>, MediaError>;
+    /// Lock up to `limit` files waiting to be processed for `lease`, oldest first, so other
+    /// workers skip them.
+    async fn claim_unprocessed_media(
+        &self,
+        limit: i64,
+        lease: Duration,
+    ) -> Result<Vec<Media>, MediaError>;
+    async fn complete_processing(&self, id: Uuid, data: &ProcessedMedia) -> Result<(), MediaError>;
+    /// Unlock a file to be processed again from `retry_at`.
+    async fn retry_processing(
+        &self,
+        id: Uuid,
+        error: &str,
+        retry_at: OffsetDateTime,
+    ) -> Result<(), MediaError>;
+    /// Give up on processing a file, it is never served.
+    async fn fail_processing(&self, id: Uuid, error: &str) -> Result<(), MediaError>;
+}
+
+pub struct MediaService {
+    database: sqlx::PgPool,
+}
+
+impl MediaService {
+    pub fn new(pool: sqlx::PgPool) -> Self {
+        Self { database: pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl MediaDriver for MediaService {
+    async fn create_media(&self, data: &CreateMedia) -> Result<Media, MediaError> {
+        trace!(media_id = %data.id, owner_id = %data.owner_id, "creating media");
+
+        data.validate()?;
+
+        let media = sqlx::query_as!(
+            Media,
+            r#"
+            insert into media
+            (
+                id,
+                owner_id,
+                content_type,
+                size,
+                storage_key,
+                description
+            )
+            values ($1, $2, $3, $4, $5, $6)
+            returning
+                id,
+                owner_id,
+                content_type,
+                size,
+                storage_key,
+                remote_url as "remote_url: sellershut_core::Url",
+                description,
+                listing_id,
+                position,
+                status as "status: MediaStatus",
+                width,
+                height,
+                blurhash,
+                thumbnail_key,
+                attempts,
+                created_at
+            "#,
+            data.id,
+            data.owner_id,
+            data.content_type,
+            data.size,
+            data.storage_key,
+            data.description,
+        )
+        .fetch_one(&self.database)
+        .await?;
+
+        Ok(media)
+    }
+
+    async fn get_media(&self, id: Uuid) -> Result<Option<Media>, MediaError> {
+        trace!(media_id = %id, "getting media");
+
+        let media = sqlx::query_as!(
+            Media,
+            r#"
+            select
+                id,
+                owner_id,
+                content_type,
+                size,
+                storage_key,
+                remote_url as "remote_url: sellershut_core::Url",
+                description,
+                listing_id,
+                position,
+                status as "status: MediaStatus",
+                width,
+                height,
+                blurhash,
+                thumbnail_key,
+                attempts,
+                created_at
+            from media
+            where id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.database)
+        .await?;
+
+        Ok(media)
+    }
+
+    async fn delete_media(&self, id: Uuid) -> Result<Option<Media>, MediaError> {
+        trace!(media_id = %id, "deleting media");
+
+        let media = sqlx::query_as!(
+            Media,
+            r#"
+            delete from media
+            where id = $1
+            returning
+                id,
+                owner_id,
+                content_type,
+                size,
+                storage_key,
+                remote_url as "remote_url: sellershut_core::Url",
+                description,
+                listing_id,
+                position,
+                status as "status: MediaStatus",
+                width,
+                height,
+                blurhash,
+                thumbnail_key,
+                attempts,
+                created_at
+            "#,
+            id
+        )
+        .fetch_optional(&self.database)
+        .await?;
+
+        Ok(media)
+    }
+
+    async fn attach_media(
+        &self,
+        listing_id: Uuid,
+        owner_id: Uuid,
+        media: &[Uuid],
+    ) -> Result<Vec<Media>, MediaError> {
+        trace!(%listing_id, count = media.len(), "attaching media");
+
+        if media.len() > MAX_LISTING_MEDIA {
+            return Err(MediaError::Invalid("too many files"));
+        }
+
+        let mut transaction = self.database.begin().await?;
+
+        sqlx::query!(
+            r#"
+            update media
+            set
+                listing_id = null,
+                position = 0
+            where listing_id = $1
+            "#,
+            listing_id
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        let attached = sqlx::query!(
+            r#"
+            update media
+            set
+                listing_id = $1,
+                position = attach.position::integer
+            from unnest($3::uuid[]) with ordinality as attach(id, position)
+            where
+                media.id = attach.id
+                and media.owner_id = $2
+                and media.listing_id is null
+                and media.status <> 'Failed'
+            "#,
+            listing_id,
+            owner_id,
+            media
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        // Unknown, foreign, already used and repeated files are all missing from the update.
+        if attached.rows_affected() != media.len() as u64 {
+            return Err(MediaError::Unavailable);
+        }
+
+        transaction.commit().await?;
+
+        self.listing_media(listing_id).await
+    }
+
+    async fn check_media(
+        &self,
+        listing_id: Uuid,
+        owner_id: Uuid,
+        media: &[Uuid],
+    ) -> Result<(), MediaError> {
+        trace!(%listing_id, count = media.len(), "checking media");
+
+        if media.len() > MAX_LISTING_MEDIA {
+            return Err(MediaError::Invalid("too many files"));
+        }
+
+        let usable = sqlx::query_scalar!(
+            r#"
+            select count(*) as "count!"
+            from media
+            where
+                id = any($3)
+                and owner_id = $2
+                and (listing_id is null or listing_id = $1)
+                and status <> 'Failed'
+            "#,
+            listing_id,
+            owner_id,
+            media
+        )
+        .fetch_one(&self.database)
+        .await?;
+
+        // Repeated files are counted once, like in `attach_media`.
+        if usable != media.len() as i64 {
+            return Err(MediaError::Unavailable);
+        }
+
+        Ok(())
+    }
+
+    async fn replace_remote_media(
+        &self,
+        listing_id: Uuid,
+        owner_id: Uuid,
+        media: &[CreateRemoteMedia],
+    ) -> Result<Vec<Media>, MediaError> {
+        trace!(%listing_id, count = media.len(), "replacing remote media");
+
+        let mut transaction = self.database.begin().await?;
+
+        sqlx::query!(
+            r#"
+            delete from media
+            where
+                listing_id = $1
+                and remote_url is not null
+            "#,
+            listing_id
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        for (position, file) in media.iter().take(MAX_LISTING_MEDIA).enumerate() {
+            let description = file
+                .description
+                .as_deref()
+                .filter(|description| description.chars().count() <= MAX_DESCRIPTION_LEN);
+
+            sqlx::query!(
+                r#"
+                insert into media
+                (
+                    id,
+                    owner_id,
+                    content_type,
+                    size,
+                    remote_url,
+                    description,
+                    listing_id,
+                    position,
+                    status,
+                    width,
+                    height,
+                    blurhash
+                )
+                values ($1, $2, $3, 0, $4, $5, $6, $7, 'Ready', $8, $9, $10)
+                "#,
+                Uuid::now_v7(),
+                owner_id,
+                file.content_type
+                    .as_deref()
+                    .unwrap_or("application/octet-stream"),
+                file.url.as_str(),
+                description,
+                listing_id,
+                position as i32 + 1,
+                file.width,
+                file.height,
+                file.blurhash,
+            )
+            .execute(&mut *transaction)
+            .await?;
+        }
+
+        transaction.commit().await?;
+
+        self.listing_media(listing_id).await
+    }
+
+    async fn listing_media(&self, listing_id: Uuid) -> Result<Vec<Media>, MediaError> {
+        self.media_for_listings(&[listing_id]).await
+    }
+
+    async fn media_for_listings(&self, listing_ids: &[Uuid]) -> Result<Vec<Media>, MediaError> {
+        let media = sqlx::query_as!(
+            Media,
+            r#"
+            select
+                id,
+                owner_id,
+                content_type,
+                size,
+                storage_key,
+                remote_url as "remote_url: sellershut_core::Url",
+                description,
+                listing_id,
+                position,
+                status as "status: MediaStatus",
+                width,
+                height,
+                blurhash,
+                thumbnail_key,
+                attempts,
+                created_at
+            from media
+            where listing_id = any($1)
+            order by listing_id, position
+            "#,
+            listing_ids
+        )
+        .fetch_all(&self.database)
+        .await?;
+
+        Ok(media)
+    }
+
+    async fn claim_unprocessed_media(
+        &self,
+        limit: i64,
+        lease: Duration,
+    ) -> Result<Vec<Media>, MediaError> {
+        let media = sqlx::query_as!(
+            Media,
+            r#"
+            update media
+            set
+                locked_until = now() + make_interval(secs => $2),
+                attempts = attempts + 1
+            where id in (
+                select id
+                from media
+                where
+                    status = 'Pending'
+                    and (locked_until is null or locked_until < now())
+                order by created_at
+                limit $1
+                for update skip locked
+            )
+            returning
+                id,
+                owner_id,
+                content_type,
+                size,
+                storage_key,
+                remote_url as "remote_url: sellershut_core::Url",
+                description,
+                listing_id,
+                position,
+                status as "status: MediaStatus",
+                width,
+                height,
+                blurhash,
+                thumbnail_key,
+                attempts,
+                created_at
+            "#,
+            limit,
+            lease.as_seconds_f64()
+        )
+        .fetch_all(&self.database)
+        .await?;
+
+        Ok(media)
+    }
+
+    async fn complete_processing(&self, id: Uuid, data: &ProcessedMedia) -> Result<(), MediaError> {
+        trace!(media_id = %id, "media processed");
+
+        sqlx::query!(
+            r#"
+            update media
+            set
+                status = 'Ready',
+                content_type = $2,
+                size = $3,
+                width = $4,
+                height = $5,
+                blurhash = $6,
+                thumbnail_key = $7,
+                locked_until = null,
+                last_error = null
+            where id = $1
+            "#,
+            id,
+            data.content_type,
+            data.size,
+            data.width,
+            data.height,
+            data.blurhash,
+            data.thumbnail_key,
+        )
+        .execute(&self.database)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn retry_processing(
+        &self,
+        id: Uuid,
+        error: &str,
+        retry_at: OffsetDateTime,
+    ) -> Result<(), MediaError> {
+        trace!(media_id = %id, %retry_at, "retrying media processing");
+
+        sqlx::query!(
+            r#"
+            update media
+            set
+                locked_until = $2,
+                last_error = $3
+            where id = $1
+            "#,
+            id,
+            retry_at,
+            error
+        )
+        .execute(&self.database)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn fail_processing(&self, id: Uuid, error: &str) -> Result<(), MediaError> {
+        trace!(media_id = %id, "giving up media processing");
+
+        sqlx::query!(
+            r#"
+            update media
+            set
+                status = 'Failed',
+                locked_until = null,
+                last_error = $2
+            where id = $1
+            "#,
+            id,
+            error
+        )
+        .execute(&self.database)
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn check_description(input: Option<&str>, expected_result: bool) {
+        let data = CreateMedia {
+            id: Uuid::now_v7(),
+            owner_id: Uuid::now_v7(),
+            content_type: "image/png".into(),
+            size: 0,
+            storage_key: "key".into(),
+            description: input.map(str::to_owned),
+        };
+        assert_eq!(expected_result, data.validate().is_ok());
+    }
+
+    #[test]
+    fn description() {
+        check_description(None, true);
+        check_description(Some("A red bike"), true);
+        check_description(Some(&"a".repeat(MAX_DESCRIPTION_LEN)), true);
+        check_description(Some(&"a".repeat(MAX_DESCRIPTION_LEN + 1)), false);
+    }
+}
@@ -0,0 +1,290 @@
+use std::{io::Cursor, num::NonZeroU64};
+
+use image::{
+    DynamicImage, ImageDecoder, ImageFormat, ImageReader, Limits, codecs::jpeg::JpegEncoder,
+    imageops::FilterType,
+};
+
+use crate::error::MediaError;
+
+/// Width and height of thumbnails, images are cropped to fill the square.
+pub const THUMBNAIL_SIZE: u32 = 400;
+
+/// Content type of thumbnails.
+pub const THUMBNAIL_CONTENT_TYPE: &str = "image/jpeg";
+
+/// Larger images are refused rather than decoded.
+const MAX_DIMENSION: u32 = 12_000;
+
+/// Bytes a single GIF frame may take, one per pixel of the largest image.
+const MAX_GIF_FRAME_BYTES: NonZeroU64 =
+    NonZeroU64::new(MAX_DIMENSION as u64 * MAX_DIMENSION as u64).unwrap();
+
+const JPEG_QUALITY: u8 = 85;
+const THUMBNAIL_QUALITY: u8 = 80;
+
+/// Blurhash components, more is more detail and a longer hash.
+const BLURHASH_COMPONENTS: (u32, u32) = (4, 3);
+
+/// Whether [`process`] can read images of `content_type`.
+pub fn can_process(content_type: &str) -> bool {
+    matches!(
+        content_type,
+        "image/jpeg" | "image/png" | "image/gif" | "image/webp"
+    )
+}
+
+/// An image without its metadata, with what is needed to show it.
+#[derive(Debug)]
+pub struct Processed {
+    pub bytes: Vec<u8>,
+    pub content_type: &'static str,
+    pub width: u32,
+    pub height: u32,
+    pub thumbnail: Vec<u8>,
+    pub blurhash: String,
+}
+
+/// Strip the metadata of an uploaded image, like the location a photo was taken at, and make
+/// its thumbnail and blurhash.
+///
+/// The image is decoded and encoded again, so nothing but the pixels survives. Its EXIF
+/// orientation is applied first, so it is still shown the right way up. GIFs keep all their
+/// frames and their looping, see [`strip_gif`].
+///
+/// CPU bound, keep it off the async runtime.
+pub fn process(bytes: &[u8], content_type: &str) -> Result<Processed, MediaError> {
+    let format = ImageFormat::from_mime_type(content_type)
+        .filter(|_| can_process(content_type))
+        .ok_or_else(|| MediaError::Unprocessable(format!("unsupported type {content_type}")))?;
+
+    let mut reader = ImageReader::with_format(Cursor::new(bytes), format);
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_DIMENSION);
+    limits.max_image_height = Some(MAX_DIMENSION);
+    reader.limits(limits);
+
+    let mut decoder = reader.into_decoder().map_err(unprocessable)?;
+    let orientation = decoder.orientation().map_err(unprocessable)?;
+    let mut image = DynamicImage::from_decoder(decoder).map_err(unprocessable)?;
+    image.apply_orientation(orientation);
+
+    let (bytes, content_type) = match format {
+        ImageFormat::Gif => (strip_gif(bytes)?, "image/gif"),
+        ImageFormat::Png => (encode_png(&image)?, "image/png"),
+        ImageFormat::WebP if image.color().has_alpha() => (encode_png(&image)?, "image/png"),
+        _ => (encode_jpeg(&image, JPEG_QUALITY)?, "image/jpeg"),
+    };
+
+    let thumbnail = image.resize_to_fill(THUMBNAIL_SIZE, THUMBNAIL_SIZE, FilterType::Lanczos3);
+    let small = thumbnail.thumbnail(32, 32).to_rgba8();
+    let blurhash = blurhash::encode(
+        BLURHASH_COMPONENTS.0,
+        BLURHASH_COMPONENTS.1,
+        small.width(),
+        small.height(),
+        small.as_raw(),
+    )
+    .map_err(|e| MediaError::Unprocessable(e.to_string()))?;
+
+    Ok(Processed {
+        bytes,
+        content_type,
+        width: image.width(),
+        height: image.height(),
+        thumbnail: encode_jpeg(&thumbnail, THUMBNAIL_QUALITY)?,
+        blurhash,
+    })
+}
+
+/// Copy the frames of a GIF, with their palettes and timing, and how often it loops.
+///
+/// Comments, XMP and any other extension blocks are left behind. The pixels are copied as
+/// palette indices, so nothing is lost.
+fn strip_gif(bytes: &[u8]) -> Result<Vec<u8>, MediaError> {
+    let mut options = gif::DecodeOptions::new();
+    options.set_color_output(gif::ColorOutput::Indexed);
+    options.set_memory_limit(gif::MemoryLimit::Bytes(MAX_GIF_FRAME_BYTES));
+    let mut decoder = options.read_info(bytes).map_err(unprocessable)?;
+
+    // The loop count is read with the blocks before the first frame.
+    let first = decoder
+        .read_next_frame()
+        .map_err(unprocessable)?
+        .cloned()
+        .ok_or_else(|| MediaError::Unprocessable("gif without frames".to_owned()))?;
+
+    let mut encoder = gif::Encoder::new(
+        Vec::new(),
+        decoder.width(),
+        decoder.height(),
+        decoder.global_palette().unwrap_or_default(),
+    )
+    .map_err(unprocessable)?;
+    encoder
+        .set_repeat(decoder.repeat())
+        .map_err(unprocessable)?;
+    encoder.write_frame(&first).map_err(unprocessable)?;
+    while let Some(frame) = decoder.read_next_frame().map_err(unprocessable)? {
+        encoder.write_frame(frame).map_err(unprocessable)?;
+    }
+
+    encoder.into_inner().map_err(unprocessable)
+}
+
+fn encode_jpeg(image: &DynamicImage, quality: u8) -> Result<Vec<u8>, MediaError> {
+    let mut bytes = Vec::new();
+    DynamicImage::ImageRgb8(image.to_rgb8())
+        .write_with_encoder(JpegEncoder::new_with_quality(&mut bytes, quality))
+        .map_err(unprocessable)?;
+    Ok(bytes)
+}
+
+fn encode_png(image: &DynamicImage) -> Result<Vec<u8>, MediaError> {
+    let mut bytes = Cursor::new(Vec::new());
+    image
+        .write_to(&mut bytes, ImageFormat::Png)
+        .map_err(unprocessable)?;
+    Ok(bytes.into_inner())
+}
+
+fn unprocessable(e: impl std::error::Error) -> MediaError {
+    MediaError::Unprocessable(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgb, RgbImage, Rgba, RgbaImage};
+
+    use super::*;
+
+    fn encoded(image: DynamicImage, format: ImageFormat) -> Vec<u8> {
+        let mut bytes = Cursor::new(Vec::new());
+        image.write_to(&mut bytes, format).unwrap();
+        bytes.into_inner()
+    }
+
+    /// A JPEG with an APP1 segment holding EXIF, like phones write.
+    fn jpeg_with_exif(width: u32, height: u32) -> Vec<u8> {
+        let plain = encoded(
+            DynamicImage::ImageRgb8(RgbImage::from_pixel(width, height, Rgb([200, 30, 30]))),
+            ImageFormat::Jpeg,
+        );
+
+        let exif = b"Exif\0\0MM\0\x2a\0\0\0\x08\0\0GPSLatitude52.37";
+        let mut bytes = plain[..2].to_vec();
+        bytes.extend_from_slice(&[0xff, 0xe1]);
+        bytes.extend_from_slice(&(exif.len() as u16 + 2).to_be_bytes());
+        bytes.extend_from_slice(exif);
+        bytes.extend_from_slice(&plain[2..]);
+        bytes
+    }
+
+    #[track_caller]
+    fn check_can_process(input: &str, expected_result: bool) {
+        assert_eq!(expected_result, can_process(input));
+    }
+
+    #[test]
+    fn processable_types() {
+        check_can_process("image/jpeg", true);
+        check_can_process("image/png", true);
+        check_can_process("image/gif", true);
+        check_can_process("image/webp", true);
+        check_can_process("image/avif", false);
+        check_can_process("text/html", false);
+    }
+
+    #[test]
+    fn strips_exif() {
+        let upload = jpeg_with_exif(640, 480);
+        assert!(upload.windows(4).any(|window| window == b"Exif"));
+
+        let processed = process(&upload, "image/jpeg").unwrap();
+
+        assert_eq!("image/jpeg", processed.content_type);
+        assert!(!processed.bytes.windows(4).any(|window| window == b"Exif"));
+        assert!(!processed.bytes.windows(3).any(|window| window == b"GPS"));
+        assert_eq!((640, 480), (processed.width, processed.height));
+    }
+
+    #[test]
+    fn thumbnail_and_blurhash() {
+        let processed = process(&jpeg_with_exif(900, 300), "image/jpeg").unwrap();
+
+        let thumbnail = image::load_from_memory(&processed.thumbnail).unwrap();
+        assert_eq!(
+            (THUMBNAIL_SIZE, THUMBNAIL_SIZE),
+            (thumbnail.width(), thumbnail.height())
+        );
+        assert_eq!(28, processed.blurhash.len());
+    }
+
+    #[test]
+    fn keeps_transparency() {
+        let upload = encoded(
+            DynamicImage::ImageRgba8(RgbaImage::from_pixel(10, 20, Rgba([0, 0, 0, 0]))),
+            ImageFormat::Png,
+        );
+
+        let processed = process(&upload, "image/png").unwrap();
+
+        assert_eq!("image/png", processed.content_type);
+        assert_eq!((10, 20), (processed.width, processed.height));
+    }
+
+    /// An animated GIF with an XMP packet and a comment, like editors write.
+    fn gif_with_xmp() -> Vec<u8> {
+        let mut encoder = gif::Encoder::new(Vec::new(), 2, 2, &[0, 0, 0, 255, 255, 255]).unwrap();
+        encoder.set_repeat(gif::Repeat::Infinite).unwrap();
+        encoder
+            .write_raw_extension(
+                gif::Extension::Application.into(),
+                &[b"XMP DataXMP", b"<x:xmpmeta exif:GPSLatitude=\"52.37\"/>"],
+            )
+            .unwrap();
+        encoder
+            .write_raw_extension(gif::Extension::Comment.into(), &[b"GPS 52.37"])
+            .unwrap();
+        for index in [0, 1] {
+            let mut frame = gif::Frame::from_indexed_pixels(2, 2, vec![index; 4], None);
+            frame.delay = 50;
+            encoder.write_frame(&frame).unwrap();
+        }
+        encoder.into_inner().unwrap()
+    }
+
+    #[test]
+    fn strips_gif_extensions() {
+        let upload = gif_with_xmp();
+        assert!(upload.windows(3).any(|window| window == b"XMP"));
+
+        let processed = process(&upload, "image/gif").unwrap();
+
+        assert_eq!("image/gif", processed.content_type);
+        assert!(!processed.bytes.windows(3).any(|window| window == b"XMP"));
+        assert!(!processed.bytes.windows(3).any(|window| window == b"GPS"));
+
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::Indexed);
+        let mut decoder = options.read_info(processed.bytes.as_slice()).unwrap();
+        let mut frames = Vec::new();
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            frames.push((frame.delay, frame.buffer.to_vec()));
+        }
+        assert_eq!(gif::Repeat::Infinite, decoder.repeat());
+        assert_eq!(vec![(50, vec![0; 4]), (50, vec![1; 4])], frames);
+    }
+
+    #[test]
+    fn refuses_garbage() {
+        assert!(matches!(
+            process(b"\xff\xd8\xffnot really a jpeg", "image/jpeg"),
+            Err(MediaError::Unprocessable(_))
+        ));
+        assert!(matches!(
+            process(b"\0\0\0\x20ftypavif", "image/avif"),
+            Err(MediaError::Unprocessable(_))
+        ));
+    }
+}
@@ -0,0 +1,71 @@
+/// The content type of an image, told from its first bytes.
+///
+/// Only formats browsers can show are recognised, anything else is `None`.
+pub fn sniff_content_type(bytes: &[u8]) -> Option<&'static str> {
+    match bytes {
+        [0xff, 0xd8, 0xff, ..] => Some("image/jpeg"),
+        [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n', ..] => Some("image/png"),
+        [b'G', b'I', b'F', b'8', b'7' | b'9', b'a', ..] => Some("image/gif"),
+        [
+            b'R',
+            b'I',
+            b'F',
+            b'F',
+            _,
+            _,
+            _,
+            _,
+            b'W',
+            b'E',
+            b'B',
+            b'P',
+            ..,
+        ] => Some("image/webp"),
+        [
+            _,
+            _,
+            _,
+            _,
+            b'f',
+            b't',
+            b'y',
+            b'p',
+            b'a',
+            b'v',
+            b'i',
+            b'f' | b's',
+            ..,
+        ] => Some("image/avif"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn check(input: &[u8], expected_result: Option<&str>) {
+        assert_eq!(expected_result, sniff_content_type(input));
+    }
+
+    #[test]
+    fn images() {
+        check(&[0xff, 0xd8, 0xff, 0xe0, 0, 0x10], Some("image/jpeg"));
+        check(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR", Some("image/png"));
+        check(b"GIF89a\x01\0", Some("image/gif"));
+        check(b"GIF87a\x01\0", Some("image/gif"));
+        check(b"RIFF\x24\0\0\0WEBPVP8 ", Some("image/webp"));
+        check(b"\0\0\0\x20ftypavif\0\0\0\0", Some("image/avif"));
+    }
+
+    #[test]
+    fn not_images() {
+        check(b"", None);
+        check(b"<svg xmlns=\"http://www.w3.org/2000/svg\"/>", None);
+        check(b"<!DOCTYPE html>", None);
+        check(b"%PDF-1.7", None);
+        check(b"RIFF\x24\0\0\0WAVEfmt ", None);
+        check(&[0xff, 0xd8], None);
+    }
+}
@@ -0,0 +1,98 @@
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use tracing::trace;
+use uuid::Uuid;
+
+use crate::{error::MediaError, store::MediaStore};
+
+/// Files in a directory on the local disk.
+pub struct FilesystemStore {
+    root: PathBuf,
+}
+
+impl FilesystemStore {
+    /// Creates `root` if it does not exist yet.
+    pub async fn new(root: &Path) -> Result<Self, MediaError> {
+        tokio::fs::create_dir_all(root).await?;
+        Ok(Self {
+            root: root.to_path_buf(),
+        })
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, MediaError> {
+        super::check_key(key)?;
+        Ok(self.root.join(key))
+    }
+}
+
+#[async_trait::async_trait]
+impl MediaStore for FilesystemStore {
+    async fn put(&self, key: &str, _content_type: &str, bytes: &[u8]) -> Result<(), MediaError> {
+        trace!(key, size = bytes.len(), "writing media file");
+        let path = self.path(key)?;
+
+        // Readers never see a half written file.
+        let partial = self.root.join(format!(".{}.partial", Uuid::now_v7()));
+        tokio::fs::write(&partial, bytes).await?;
+        if let Err(e) = tokio::fs::rename(&partial, &path).await {
+            let _ = tokio::fs::remove_file(&partial).await;
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, MediaError> {
+        trace!(key, "reading media file");
+        match tokio::fs::read(self.path(key)?).await {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), MediaError> {
+        trace!(key, "deleting media file");
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn store() -> FilesystemStore {
+        let root = std::env::temp_dir().join(format!("sellershut-media-{}", Uuid::now_v7()));
+        FilesystemStore::new(&root).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn round_trip() {
+        let store = store().await;
+
+        store.put("a", "image/png", b"first").await.unwrap();
+        store.put("a", "image/png", b"second").await.unwrap();
+        assert_eq!(Some(b"second".to_vec()), store.get("a").await.unwrap());
+
+        store.delete("a").await.unwrap();
+        assert_eq!(None, store.get("a").await.unwrap());
+        store.delete("a").await.unwrap();
+
+        tokio::fs::remove_dir_all(&store.root).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn stays_in_root() {
+        let store = store().await;
+
+        assert!(store.put("../a", "image/png", b"").await.is_err());
+        assert!(store.get("/etc/passwd").await.is_err());
+
+        tokio::fs::remove_dir_all(&store.root).await.unwrap();
+    }
+}
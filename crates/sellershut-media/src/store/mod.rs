@@ -0,0 +1,85 @@
+mod fs;
+#[cfg(feature = "s3")]
+mod s3;
+
+use std::sync::Arc;
+
+use crate::{
+    config::{Backend, Config},
+    error::MediaError,
+};
+
+pub use fs::FilesystemStore;
+#[cfg(feature = "s3")]
+pub use s3::S3Store;
+
+/// Where the bytes of uploaded files are kept.
+#[async_trait::async_trait]
+pub trait MediaStore: Send + Sync {
+    /// Store a file, replacing whatever was stored under `key`.
+    async fn put(&self, key: &str, content_type: &str, bytes: &[u8]) -> Result<(), MediaError>;
+    /// `None` if nothing is stored under `key`.
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, MediaError>;
+    /// Deleting a missing file is not an error.
+    async fn delete(&self, key: &str) -> Result<(), MediaError>;
+}
+
+impl Config {
+    /// The store of the configured backend.
+    pub async fn connect(&self) -> Result<Arc<dyn MediaStore>, MediaError> {
+        match self.backend {
+            Backend::Filesystem => {
+                let config = self.filesystem.as_ref().ok_or_else(|| {
+                    MediaError::InvalidConfig("the filesystem backend needs a path".into())
+                })?;
+                Ok(Arc::new(FilesystemStore::new(&config.path).await?))
+            }
+            #[cfg(feature = "s3")]
+            Backend::S3 => {
+                let config = self.s3.as_ref().ok_or_else(|| {
+                    MediaError::InvalidConfig("the s3 backend needs an s3 section".into())
+                })?;
+                Ok(Arc::new(S3Store::new(config)?))
+            }
+            #[cfg(not(feature = "s3"))]
+            Backend::S3 => Err(MediaError::InvalidConfig(
+                "built without support for the s3 backend".into(),
+            )),
+        }
+    }
+}
+
+/// Keys are generated by us, anything that could leave the store's root is refused.
+fn check_key(key: &str) -> Result<(), MediaError> {
+    let valid = !key.is_empty()
+        && key
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.'))
+        && !key.starts_with('.');
+    if !valid {
+        return Err(MediaError::Storage(format!("invalid key {key:?}")));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn check_key_valid(input: &str, expected_result: bool) {
+        assert_eq!(expected_result, check_key(input).is_ok());
+    }
+
+    #[test]
+    fn keys() {
+        check_key_valid("0199a3f2-6c1e-7c52-9d0f-2b1f6a1e0b3d", true);
+        check_key_valid("thumb_1.webp", true);
+        check_key_valid("", false);
+        check_key_valid("..", false);
+        check_key_valid(".hidden", false);
+        check_key_valid("../etc/passwd", false);
+        check_key_valid("a/b", false);
+        check_key_valid("a\\b", false);
+    }
+}
@@ -0,0 +1,142 @@
+use std::time::Duration;
+
+use reqwest::{StatusCode, header::CONTENT_TYPE};
+use rusty_s3::{Bucket, Credentials, S3Action, UrlStyle};
+use tracing::trace;
+
+use crate::{config::S3Config, error::MediaError, store::MediaStore};
+
+/// How long the signature of a request is valid.
+const SIGNATURE_TTL: Duration = Duration::from_secs(60);
+
+/// Objects in a bucket of an S3 compatible object storage.
+pub struct S3Store {
+    bucket: Bucket,
+    credentials: Credentials,
+    client: reqwest::Client,
+}
+
+impl S3Store {
+    pub fn new(config: &S3Config) -> Result<Self, MediaError> {
+        let style = if config.path_style {
+            UrlStyle::Path
+        } else {
+            UrlStyle::VirtualHost
+        };
+        let bucket = Bucket::new(
+            config.endpoint.clone(),
+            style,
+            config.bucket.clone(),
+            config.region.clone(),
+        )
+        .map_err(|e| MediaError::InvalidConfig(e.to_string()))?;
+
+        Ok(Self {
+            bucket,
+            credentials: Credentials::new(&config.access_key, config.secret_key.expose()),
+            client: reqwest::Client::new(),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl MediaStore for S3Store {
+    async fn put(&self, key: &str, content_type: &str, bytes: &[u8]) -> Result<(), MediaError> {
+        trace!(key, size = bytes.len(), "uploading media object");
+        super::check_key(key)?;
+
+        let url = self
+            .bucket
+            .put_object(Some(&self.credentials), key)
+            .sign(SIGNATURE_TTL);
+        let response = self
+            .client
+            .put(url)
+            .header(CONTENT_TYPE, content_type)
+            .body(bytes.to_vec())
+            .send()
+            .await
+            .map_err(storage)?;
+
+        response.error_for_status().map_err(storage)?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, MediaError> {
+        trace!(key, "downloading media object");
+        super::check_key(key)?;
+
+        let url = self
+            .bucket
+            .get_object(Some(&self.credentials), key)
+            .sign(SIGNATURE_TTL);
+        let response = self.client.get(url).send().await.map_err(storage)?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        let bytes = response
+            .error_for_status()
+            .map_err(storage)?
+            .bytes()
+            .await
+            .map_err(storage)?;
+        Ok(Some(bytes.to_vec()))
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), MediaError> {
+        trace!(key, "deleting media object");
+        super::check_key(key)?;
+
+        let url = self
+            .bucket
+            .delete_object(Some(&self.credentials), key)
+            .sign(SIGNATURE_TTL);
+        let response = self.client.delete(url).send().await.map_err(storage)?;
+
+        // S3 answers 204 for missing objects too, some stand-ins answer 404.
+        if response.status() != StatusCode::NOT_FOUND {
+            response.error_for_status().map_err(storage)?;
+        }
+        Ok(())
+    }
+}
+
+fn storage(e: reqwest::Error) -> MediaError {
+    MediaError::Storage(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use sellershut_core::RedactedSecret;
+
+    use super::*;
+
+    /// Runs against the stand-in of `docker compose up storage`.
+    #[tokio::test]
+    #[ignore = "requires a local S3 compatible store"]
+    async fn round_trip() {
+        let endpoint = std::env::var("S3_ENDPOINT").unwrap_or("http://localhost:9000".into());
+        let store = S3Store::new(&S3Config {
+            endpoint: endpoint.parse().unwrap(),
+            bucket: "sellershut".into(),
+            region: "us-east-1".into(),
+            access_key: "minioadmin".into(),
+            secret_key: RedactedSecret::from(String::from("minioadmin")),
+            path_style: true,
+        })
+        .unwrap();
+
+        store
+            .put("round-trip", "image/png", b"bytes")
+            .await
+            .unwrap();
+        assert_eq!(
+            Some(b"bytes".to_vec()),
+            store.get("round-trip").await.unwrap()
+        );
+
+        store.delete("round-trip").await.unwrap();
+        assert_eq!(None, store.get("round-trip").await.unwrap());
+    }
+}
@@ -1,3 +1,4 @@
+use sellershut_utilities::crypto::KeyError;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -6,4 +7,8 @@ pub enum UserError {
     Database(#[from] sqlx::Error),
     #[error("username is unavailable")]
     UsernameTaken,
+    #[error("users cannot follow themselves")]
+    SelfFollow,
+    #[error("private key could not be encrypted or decrypted")]
+    Key(#[from] KeyError),
 }
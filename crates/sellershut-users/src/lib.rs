@@ -2,12 +2,17 @@ pub mod error;
 
 use sellershut_core::{
     RedactedSecret,
-    user::{ActorType, User},
+    user::{ActorType, Follow, FollowState, RetiredKey, User, UserCounts},
 };
 use sellershut_svc::cache::Cache;
-use sellershut_utilities::{auth::hash_token, cache_key::CacheKey};
+use sellershut_utilities::{
+    auth::hash_token,
+    cache_key::CacheKey,
+    crypto::{Keyring, key_version},
+};
 use sqlx::PgConnection;
 use std::time::Duration;
+use time::OffsetDateTime;
 use tracing::{debug, info, trace};
 use url::Url;
 use uuid::Uuid;
@@ -20,16 +25,21 @@ pub struct CreateUser {
     pub ap_id: Url,
     pub name: Option<String>,
     pub inbox: Url,
+    pub shared_inbox: Option<Url>,
     pub public_key: String,
     pub private_key: Option<RedactedSecret>,
     pub is_local: bool,
     pub avatar: Option<Url>,
+    pub also_known_as: Vec<Url>,
+    pub moved_to: Option<Url>,
 }
 
 #[async_trait::async_trait]
 pub trait UserDriver: Send + Sync {
     async fn get_user(&self, username: &str) -> Result<Option<User>, UserError>;
     async fn get_user_by_id(&self, ap_id: &Url) -> Result<Option<User>, UserError>;
+    /// Look a user up by their database id rather than their ActivityPub id.
+    async fn get_user_by_uuid(&self, id: Uuid) -> Result<Option<User>, UserError>;
     async fn get_system_user(&self, domain: &str) -> Result<Option<User>, UserError>;
     async fn create_user(
         &self,
@@ -42,11 +52,121 @@ pub trait UserDriver: Send + Sync {
         tx: Option<&mut PgConnection>,
     ) -> Result<User, UserError>;
     async fn user_from_session(&self, session_token: &str) -> Result<User, UserError>;
+    /// Record a follow request, or refresh the activity id of an existing one.
+    ///
+    /// An already accepted follow stays accepted.
+    async fn follow(
+        &self,
+        follower_id: Uuid,
+        followee_id: Uuid,
+        activity_id: &Url,
+        state: FollowState,
+    ) -> Result<Follow, UserError>;
+    async fn get_follow(
+        &self,
+        follower_id: Uuid,
+        followee_id: Uuid,
+    ) -> Result<Option<Follow>, UserError>;
+    async fn accept_follow(
+        &self,
+        follower_id: Uuid,
+        followee_id: Uuid,
+    ) -> Result<Option<Follow>, UserError>;
+    /// Turn down a follow request, or remove an accepted follower.
+    async fn reject_follow(
+        &self,
+        follower_id: Uuid,
+        followee_id: Uuid,
+    ) -> Result<Option<Follow>, UserError>;
+    async fn unfollow(
+        &self,
+        follower_id: Uuid,
+        followee_id: Uuid,
+    ) -> Result<Option<Follow>, UserError>;
+    async fn count_followers(&self, user_id: Uuid) -> Result<i64, UserError>;
+    async fn count_following(&self, user_id: Uuid) -> Result<i64, UserError>;
+    /// ActivityPub ids of accepted followers, newest first.
+    async fn followers(
+        &self,
+        user_id: Uuid,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Url>, UserError>;
+    /// ActivityPub ids of accepted followees, newest first.
+    async fn following(
+        &self,
+        user_id: Uuid,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Url>, UserError>;
+    async fn set_hide_network(&self, user_id: Uuid, hide_network: bool) -> Result<User, UserError>;
+    /// Local people and how many of them were recently active, cached for
+    /// [`USER_COUNTS_CACHE_TTL`].
+    async fn count_local_users(&self) -> Result<UserCounts, UserError>;
+    /// ActivityPub ids of remote actors last refreshed before `refreshed_before`, oldest first.
+    async fn stale_remote_users(
+        &self,
+        refreshed_before: OffsetDateTime,
+        limit: i64,
+    ) -> Result<Vec<Url>, UserError>;
+    /// Set when a remote actor was last fetched. An old enough time makes it fetched again on
+    /// its next use.
+    async fn set_last_refreshed_at(
+        &self,
+        ap_id: &Url,
+        refreshed_at: OffsetDateTime,
+    ) -> Result<(), UserError>;
+    /// Set the display name and avatar of a user.
+    async fn update_profile(
+        &self,
+        user_id: Uuid,
+        name: Option<&str>,
+        avatar: Option<&Url>,
+    ) -> Result<User, UserError>;
+    /// Inboxes of a user's remote followers, their servers' shared inbox where they have one.
+    async fn follower_inboxes(&self, user_id: Uuid) -> Result<Vec<Url>, UserError>;
+    /// Tombstone an account.
+    ///
+    /// Its profile, follows and logins are removed. The row and its keys stay, so the id is
+    /// never handed out again and a `Delete` can still be signed. `None` if there is no such
+    /// account or it was already deleted.
+    async fn delete_user(&self, ap_id: &Url) -> Result<Option<User>, UserError>;
+    /// Replace the actors a user says they also are.
+    async fn set_aliases(&self, user_id: Uuid, aliases: &[Url]) -> Result<User, UserError>;
+    /// Record that a user moved to another actor.
+    async fn set_moved_to(&self, user_id: Uuid, target: &Url) -> Result<User, UserError>;
+    /// Local users with an accepted follow of `user_id`.
+    async fn local_followers(&self, user_id: Uuid) -> Result<Vec<User>, UserError>;
+    /// Replace the keypair of a local user.
+    ///
+    /// The old public key is kept as a [`RetiredKey`] until `grace_period` has passed, retired
+    /// keys that expired already are removed. Retired keys keep the id the key had, its
+    /// [`key_version`].
+    async fn rotate_key(
+        &self,
+        user_id: Uuid,
+        public_key: &str,
+        private_key: &RedactedSecret,
+        grace_period: time::Duration,
+    ) -> Result<(User, RetiredKey), UserError>;
+    /// The private key of a local user, decrypted, `None` for remote users.
+    ///
+    /// Users are read without it, only signing needs it.
+    async fn private_key(&self, user_id: Uuid) -> Result<Option<RedactedSecret>, UserError>;
+    /// Retired keys of `user_id` that have not expired yet, most recently retired first.
+    async fn retired_keys(&self, user_id: Uuid) -> Result<Vec<RetiredKey>, UserError>;
+    /// Encrypt the private keys that are not encrypted with the current master key again,
+    /// including ones stored before encryption was turned on.
+    ///
+    /// Returns how many keys were encrypted again.
+    async fn reseal_private_keys(&self) -> Result<u64, UserError>;
 }
 
 pub struct UserService {
     database: sqlx::PgPool,
     cache: sellershut_svc::cache::Cache,
+    /// Private keys are encrypted with it before they are stored.
+    keyring: Keyring,
 }
 
 #[async_trait::async_trait]
@@ -71,8 +191,13 @@ impl UserDriver for UserService {
                 username,
                 name,
                 inbox,
+                shared_inbox as "shared_inbox: sellershut_core::Url",
+                hide_network,
+                deleted_at,
+                also_known_as as "also_known_as: Vec<sellershut_core::Url>",
+                moved_to as "moved_to: sellershut_core::Url",
                 public_key,
-                private_key as "private_key: RedactedSecret",
+                null::text as "private_key: RedactedSecret",
                 kind as "kind: ActorType",
                 last_refreshed_at,
                 created_at,
@@ -82,6 +207,7 @@ impl UserDriver for UserService {
             where
                 username = $1
                 and is_local
+                and deleted_at is null
         "#,
             username
         )
@@ -114,9 +240,14 @@ impl UserDriver for UserService {
                 username,
                 name,
                 inbox,
+                shared_inbox as "shared_inbox: sellershut_core::Url",
+                hide_network,
+                deleted_at,
+                also_known_as as "also_known_as: Vec<sellershut_core::Url>",
+                moved_to as "moved_to: sellershut_core::Url",
                 public_key,
                 avatar,
-                private_key as "private_key: RedactedSecret",
+                null::text as "private_key: RedactedSecret",
                 kind as "kind: ActorType",
                 last_refreshed_at,
                 created_at,
@@ -149,6 +280,7 @@ impl UserDriver for UserService {
         );
 
         let external_transaction = tx.is_some();
+        let private_key = self.seal(data.private_key.as_ref())?;
 
         let query = sqlx::query_as!(
             User,
@@ -160,13 +292,16 @@ impl UserDriver for UserService {
                 username,
                 name,
                 inbox,
+                shared_inbox,
                 public_key,
                 avatar,
                 private_key,
                 kind,
-                is_local
+                is_local,
+                also_known_as,
+                moved_to
             )
-            values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
             on conflict do nothing
             returning
                 id,
@@ -174,9 +309,14 @@ impl UserDriver for UserService {
                 username,
                 name,
                 inbox,
+                shared_inbox as "shared_inbox: sellershut_core::Url",
+                hide_network,
+                deleted_at,
+                also_known_as as "also_known_as: Vec<sellershut_core::Url>",
+                moved_to as "moved_to: sellershut_core::Url",
                 public_key,
                 avatar,
-                private_key as "private_key: RedactedSecret",
+                null::text as "private_key: RedactedSecret",
                 kind as "kind: ActorType",
                 last_refreshed_at,
                 created_at,
@@ -187,11 +327,18 @@ impl UserDriver for UserService {
             data.username,
             data.name,
             data.inbox.to_string(),
+            data.shared_inbox.as_ref().map(|v| v.as_str()),
             data.public_key,
             data.avatar.as_ref().map(|v| v.as_str()),
-            data.private_key as _,
+            private_key as _,
             data.kind as _,
             data.is_local,
+            &data
+                .also_known_as
+                .iter()
+                .map(Url::to_string)
+                .collect::<Vec<_>>(),
+            data.moved_to.as_ref().map(Url::as_str),
         );
 
         let result = match tx {
@@ -259,12 +406,14 @@ impl UserDriver for UserService {
         );
 
         let external_transaction = tx.is_some();
+        let private_key = self.seal(data.private_key.as_ref())?;
 
         let previous = {
             let query = sqlx::query!(
                 r#"
             select
                 username,
+                public_key,
                 is_local
             from "user"
             where ap_id = $1
@@ -289,31 +438,43 @@ impl UserDriver for UserService {
             username,
             name,
             inbox,
+            shared_inbox,
             public_key,
             avatar,
             private_key,
             kind,
-            is_local
+            is_local,
+            also_known_as,
+            moved_to
         )
-        values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
         on conflict (ap_id) do update set
             username = excluded.username,
             name = excluded.name,
             inbox = excluded.inbox,
+            shared_inbox = excluded.shared_inbox,
             public_key = excluded.public_key,
             avatar = excluded.avatar,
             private_key = excluded.private_key,
             kind = excluded.kind,
-            is_local = excluded.is_local
+            is_local = excluded.is_local,
+            also_known_as = excluded.also_known_as,
+            moved_to = excluded.moved_to,
+            last_refreshed_at = now()
         returning
             id,
             ap_id,
             username,
             name,
             inbox,
+            shared_inbox as "shared_inbox: sellershut_core::Url",
+            hide_network,
+            deleted_at,
+            also_known_as as "also_known_as: Vec<sellershut_core::Url>",
+            moved_to as "moved_to: sellershut_core::Url",
             public_key,
             avatar,
-            private_key as "private_key: RedactedSecret",
+            null::text as "private_key: RedactedSecret",
             kind as "kind: ActorType",
             last_refreshed_at,
             created_at,
@@ -324,11 +485,18 @@ impl UserDriver for UserService {
             data.username,
             data.name,
             data.inbox.to_string(),
+            data.shared_inbox.as_ref().map(|v| v.as_str()),
             data.public_key,
             data.avatar.as_ref().map(|v| v.as_str()),
-            data.private_key as _,
+            private_key as _,
             data.kind as _,
             data.is_local,
+            &data
+                .also_known_as
+                .iter()
+                .map(Url::to_string)
+                .collect::<Vec<_>>(),
+            data.moved_to.as_ref().map(Url::as_str),
         );
 
         let user = match tx {
@@ -346,6 +514,16 @@ impl UserDriver for UserService {
         self.invalidate_cache_key(CacheKey::UserByApId(&user.ap_id))
             .await;
 
+        if let Some(previous) = &previous
+            && previous.public_key != user.public_key
+        {
+            info!(
+                user_id = %user.id,
+                ap_id = %user.ap_id,
+                "actor key changed"
+            );
+        }
+
         // Remove the previous local username if it existed.
         if let Some(previous) = previous
             && previous.is_local
@@ -385,9 +563,14 @@ impl UserDriver for UserService {
                 u.username,
                 u.name,
                 u.inbox,
+                u.shared_inbox as "shared_inbox: sellershut_core::Url",
+                u.hide_network,
+                u.deleted_at,
+                u.also_known_as as "also_known_as: Vec<sellershut_core::Url>",
+                u.moved_to as "moved_to: sellershut_core::Url",
                 u.public_key,
                 u.avatar,
-                u.private_key as "private_key: RedactedSecret",
+                null::text as "private_key: RedactedSecret",
                 u.kind as "kind: ActorType",
                 u.last_refreshed_at,
                 u.created_at,
@@ -396,6 +579,7 @@ impl UserDriver for UserService {
             JOIN "user" AS u ON u.id = s.user_id
             WHERE s.token_hash = $1
               AND s.expires_at > now()
+              AND u.deleted_at IS NULL
             "#,
             hash_token(session_token)
         )
@@ -436,9 +620,14 @@ impl UserDriver for UserService {
                 username,
                 name,
                 inbox,
+                shared_inbox as "shared_inbox: sellershut_core::Url",
+                hide_network,
+                deleted_at,
+                also_known_as as "also_known_as: Vec<sellershut_core::Url>",
+                moved_to as "moved_to: sellershut_core::Url",
                 public_key,
                 avatar,
-                private_key as "private_key: RedactedSecret",
+                null::text as "private_key: RedactedSecret",
                 kind as "kind: ActorType",
                 last_refreshed_at,
                 created_at,
@@ -469,18 +658,929 @@ impl UserDriver for UserService {
 
         Ok(result)
     }
+
+    async fn get_user_by_uuid(&self, id: Uuid) -> Result<Option<User>, UserError> {
+        trace!(user_id = %id, "getting user by id");
+
+        let result = sqlx::query_as!(
+            User,
+            r#"
+            select
+                id,
+                ap_id,
+                username,
+                name,
+                inbox,
+                shared_inbox as "shared_inbox: sellershut_core::Url",
+                hide_network,
+                deleted_at,
+                also_known_as as "also_known_as: Vec<sellershut_core::Url>",
+                moved_to as "moved_to: sellershut_core::Url",
+                public_key,
+                avatar,
+                null::text as "private_key: RedactedSecret",
+                kind as "kind: ActorType",
+                last_refreshed_at,
+                created_at,
+                is_local
+            from "user"
+            where
+                id = $1
+        "#,
+            id
+        )
+        .fetch_optional(&self.database)
+        .await?;
+
+        if let Some(user) = &result {
+            self.cache_user(user).await;
+        }
+
+        Ok(result)
+    }
+
+    async fn follow(
+        &self,
+        follower_id: Uuid,
+        followee_id: Uuid,
+        activity_id: &Url,
+        state: FollowState,
+    ) -> Result<Follow, UserError> {
+        if follower_id == followee_id {
+            return Err(UserError::SelfFollow);
+        }
+
+        trace!(
+            %follower_id,
+            %followee_id,
+            ?state,
+            "recording follow"
+        );
+
+        let follow = sqlx::query_as!(
+            Follow,
+            r#"
+            insert into follow (follower_id, followee_id, state, activity_id)
+            values ($1, $2, $3, $4)
+            on conflict (follower_id, followee_id) do update
+            set
+                activity_id = excluded.activity_id,
+                state = case
+                    when follow.state = 'Accepted' then follow.state
+                    else excluded.state
+                end,
+                updated_at = now()
+            returning
+                follower_id,
+                followee_id,
+                state as "state: FollowState",
+                activity_id,
+                created_at,
+                updated_at
+            "#,
+            follower_id,
+            followee_id,
+            state as FollowState,
+            activity_id.as_str()
+        )
+        .fetch_one(&self.database)
+        .await?;
+
+        info!(
+            %follower_id,
+            %followee_id,
+            state = ?follow.state,
+            "follow recorded"
+        );
+
+        Ok(follow)
+    }
+
+    async fn get_follow(
+        &self,
+        follower_id: Uuid,
+        followee_id: Uuid,
+    ) -> Result<Option<Follow>, UserError> {
+        trace!(%follower_id, %followee_id, "getting follow");
+
+        let follow = sqlx::query_as!(
+            Follow,
+            r#"
+            select
+                follower_id,
+                followee_id,
+                state as "state: FollowState",
+                activity_id,
+                created_at,
+                updated_at
+            from follow
+            where
+                follower_id = $1
+                and followee_id = $2
+            "#,
+            follower_id,
+            followee_id
+        )
+        .fetch_optional(&self.database)
+        .await?;
+
+        Ok(follow)
+    }
+
+    async fn accept_follow(
+        &self,
+        follower_id: Uuid,
+        followee_id: Uuid,
+    ) -> Result<Option<Follow>, UserError> {
+        trace!(%follower_id, %followee_id, "accepting follow");
+
+        let follow = sqlx::query_as!(
+            Follow,
+            r#"
+            update follow
+            set
+                state = 'Accepted',
+                updated_at = now()
+            where
+                follower_id = $1
+                and followee_id = $2
+            returning
+                follower_id,
+                followee_id,
+                state as "state: FollowState",
+                activity_id,
+                created_at,
+                updated_at
+            "#,
+            follower_id,
+            followee_id
+        )
+        .fetch_optional(&self.database)
+        .await?;
+
+        if follow.is_some() {
+            info!(%follower_id, %followee_id, "follow accepted");
+        }
+
+        Ok(follow)
+    }
+
+    async fn reject_follow(
+        &self,
+        follower_id: Uuid,
+        followee_id: Uuid,
+    ) -> Result<Option<Follow>, UserError> {
+        let follow = self.delete_follow(follower_id, followee_id).await?;
+
+        if follow.is_some() {
+            info!(%follower_id, %followee_id, "follow rejected");
+        }
+
+        Ok(follow)
+    }
+
+    async fn unfollow(
+        &self,
+        follower_id: Uuid,
+        followee_id: Uuid,
+    ) -> Result<Option<Follow>, UserError> {
+        let follow = self.delete_follow(follower_id, followee_id).await?;
+
+        if follow.is_some() {
+            info!(%follower_id, %followee_id, "follow removed");
+        }
+
+        Ok(follow)
+    }
+
+    async fn count_followers(&self, user_id: Uuid) -> Result<i64, UserError> {
+        trace!(%user_id, "counting followers");
+
+        let count = sqlx::query_scalar!(
+            r#"
+            select count(*) as "count!"
+            from follow
+            where
+                followee_id = $1
+                and state = 'Accepted'
+            "#,
+            user_id
+        )
+        .fetch_one(&self.database)
+        .await?;
+
+        Ok(count)
+    }
+
+    async fn count_following(&self, user_id: Uuid) -> Result<i64, UserError> {
+        trace!(%user_id, "counting followees");
+
+        let count = sqlx::query_scalar!(
+            r#"
+            select count(*) as "count!"
+            from follow
+            where
+                follower_id = $1
+                and state = 'Accepted'
+            "#,
+            user_id
+        )
+        .fetch_one(&self.database)
+        .await?;
+
+        Ok(count)
+    }
+
+    async fn followers(
+        &self,
+        user_id: Uuid,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Url>, UserError> {
+        trace!(%user_id, limit, offset, "listing followers");
+
+        let followers = sqlx::query_scalar!(
+            r#"
+            select u.ap_id as "ap_id: sellershut_core::Url"
+            from follow as f
+            join "user" as u on u.id = f.follower_id
+            where
+                f.followee_id = $1
+                and f.state = 'Accepted'
+            order by f.created_at desc, u.ap_id
+            limit $2
+            offset $3
+            "#,
+            user_id,
+            limit,
+            offset
+        )
+        .fetch_all(&self.database)
+        .await?;
+
+        Ok(followers.into_iter().map(|id| id.inner()).collect())
+    }
+
+    async fn following(
+        &self,
+        user_id: Uuid,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Url>, UserError> {
+        trace!(%user_id, limit, offset, "listing followees");
+
+        let following = sqlx::query_scalar!(
+            r#"
+            select u.ap_id as "ap_id: sellershut_core::Url"
+            from follow as f
+            join "user" as u on u.id = f.followee_id
+            where
+                f.follower_id = $1
+                and f.state = 'Accepted'
+            order by f.created_at desc, u.ap_id
+            limit $2
+            offset $3
+            "#,
+            user_id,
+            limit,
+            offset
+        )
+        .fetch_all(&self.database)
+        .await?;
+
+        Ok(following.into_iter().map(|id| id.inner()).collect())
+    }
+
+    async fn set_hide_network(&self, user_id: Uuid, hide_network: bool) -> Result<User, UserError> {
+        trace!(%user_id, hide_network, "updating network visibility");
+
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            update "user"
+            set hide_network = $2
+            where id = $1
+            returning
+                id,
+                ap_id,
+                username,
+                name,
+                inbox,
+                shared_inbox as "shared_inbox: sellershut_core::Url",
+                hide_network,
+                deleted_at,
+                also_known_as as "also_known_as: Vec<sellershut_core::Url>",
+                moved_to as "moved_to: sellershut_core::Url",
+                public_key,
+                avatar,
+                null::text as "private_key: RedactedSecret",
+                kind as "kind: ActorType",
+                last_refreshed_at,
+                created_at,
+                is_local
+            "#,
+            user_id,
+            hide_network
+        )
+        .fetch_one(&self.database)
+        .await?;
+
+        self.cache_user(&user).await;
+
+        Ok(user)
+    }
+
+    async fn count_local_users(&self) -> Result<UserCounts, UserError> {
+        let cache_key = CacheKey::LocalUserCounts;
+
+        match self.cache.get::<Vec<u8>>(cache_key).await {
+            Ok(Some(cached)) => match serde_json::from_slice(&cached) {
+                Ok(counts) => return Ok(counts),
+                Err(error) => {
+                    debug!(cache_key = %cache_key, error = %error, "cached user counts are invalid");
+                }
+            },
+            Ok(None) => debug!(cache_key = %cache_key, "user counts cache miss"),
+            Err(error) => {
+                debug!(
+                    cache_key = %cache_key,
+                    error = %error,
+                    "cache read failed; falling back to database"
+                );
+            }
+        }
+
+        debug!("counting local users");
+
+        let counts = sqlx::query_as!(
+            UserCounts,
+            r#"
+            select
+                count(*) as "total!",
+                count(*) filter (where active_at > now() - interval '30 days') as "active_month!",
+                count(*) filter (where active_at > now() - interval '180 days') as "active_halfyear!"
+            from (
+                select
+                    greatest(
+                        (select max(i.last_login_at) from oauth_identity i where i.user_id = u.id),
+                        (select max(a.created_at) from activity a where a.actor_id = u.id)
+                    ) as active_at
+                from "user" u
+                where
+                    u.is_local
+                    and u.kind = 'Person'
+                    and u.deleted_at is null
+            ) local_user
+            "#
+        )
+        .fetch_one(&self.database)
+        .await?;
+
+        match serde_json::to_vec(&counts) {
+            Ok(value) => {
+                if let Err(error) = self
+                    .cache
+                    .set_ex(cache_key, value.as_slice(), USER_COUNTS_CACHE_TTL)
+                    .await
+                {
+                    debug!(cache_key = %cache_key, error = %error, "failed to cache user counts");
+                }
+            }
+            Err(error) => debug!(error = %error, "failed to serialize user counts for cache"),
+        }
+
+        Ok(counts)
+    }
+
+    async fn stale_remote_users(
+        &self,
+        refreshed_before: OffsetDateTime,
+        limit: i64,
+    ) -> Result<Vec<Url>, UserError> {
+        trace!(%refreshed_before, limit, "listing stale remote users");
+
+        let ap_ids = sqlx::query_scalar!(
+            r#"
+            select ap_id as "ap_id: sellershut_core::Url"
+            from "user"
+            where
+                not is_local
+                and deleted_at is null
+                and last_refreshed_at < $1
+            order by last_refreshed_at
+            limit $2
+            "#,
+            refreshed_before,
+            limit
+        )
+        .fetch_all(&self.database)
+        .await?;
+
+        Ok(ap_ids.into_iter().map(|ap_id| ap_id.inner()).collect())
+    }
+
+    async fn set_last_refreshed_at(
+        &self,
+        ap_id: &Url,
+        refreshed_at: OffsetDateTime,
+    ) -> Result<(), UserError> {
+        trace!(%ap_id, %refreshed_at, "setting last refresh");
+
+        sqlx::query!(
+            r#"
+            update "user"
+            set last_refreshed_at = $2
+            where
+                ap_id = $1
+                and not is_local
+            "#,
+            ap_id.as_str(),
+            refreshed_at
+        )
+        .execute(&self.database)
+        .await?;
+
+        self.invalidate_cache_key(CacheKey::UserByApId(&ap_id.into()))
+            .await;
+
+        Ok(())
+    }
+
+    async fn update_profile(
+        &self,
+        user_id: Uuid,
+        name: Option<&str>,
+        avatar: Option<&Url>,
+    ) -> Result<User, UserError> {
+        trace!(%user_id, "updating profile");
+
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            update "user"
+            set
+                name = $2,
+                avatar = $3
+            where id = $1
+            returning
+                id,
+                ap_id,
+                username,
+                name,
+                inbox,
+                shared_inbox as "shared_inbox: sellershut_core::Url",
+                hide_network,
+                deleted_at,
+                also_known_as as "also_known_as: Vec<sellershut_core::Url>",
+                moved_to as "moved_to: sellershut_core::Url",
+                public_key,
+                avatar,
+                null::text as "private_key: RedactedSecret",
+                kind as "kind: ActorType",
+                last_refreshed_at,
+                created_at,
+                is_local
+            "#,
+            user_id,
+            name,
+            avatar.map(Url::as_str)
+        )
+        .fetch_one(&self.database)
+        .await?;
+
+        self.cache_user(&user).await;
+
+        Ok(user)
+    }
+
+    async fn follower_inboxes(&self, user_id: Uuid) -> Result<Vec<Url>, UserError> {
+        trace!(%user_id, "listing follower inboxes");
+
+        let inboxes = sqlx::query_scalar!(
+            r#"
+            select distinct coalesce(u.shared_inbox, u.inbox) as "inbox!: sellershut_core::Url"
+            from follow as f
+            join "user" as u on u.id = f.follower_id
+            where
+                f.followee_id = $1
+                and f.state = 'Accepted'
+                and not u.is_local
+                and u.deleted_at is null
+            "#,
+            user_id
+        )
+        .fetch_all(&self.database)
+        .await?;
+
+        Ok(inboxes.into_iter().map(|inbox| inbox.inner()).collect())
+    }
+
+    async fn delete_user(&self, ap_id: &Url) -> Result<Option<User>, UserError> {
+        trace!(%ap_id, "deleting user");
+
+        let mut tx = self.database.begin().await?;
+
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            update "user"
+            set
+                deleted_at = now(),
+                name = null,
+                avatar = null
+            where
+                ap_id = $1
+                and deleted_at is null
+            returning
+                id,
+                ap_id,
+                username,
+                name,
+                inbox,
+                shared_inbox as "shared_inbox: sellershut_core::Url",
+                hide_network,
+                deleted_at,
+                also_known_as as "also_known_as: Vec<sellershut_core::Url>",
+                moved_to as "moved_to: sellershut_core::Url",
+                public_key,
+                avatar,
+                null::text as "private_key: RedactedSecret",
+                kind as "kind: ActorType",
+                last_refreshed_at,
+                created_at,
+                is_local
+            "#,
+            ap_id.as_str()
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(user) = user else {
+            return Ok(None);
+        };
+
+        sqlx::query!(
+            "delete from follow where follower_id = $1 or followee_id = $1",
+            user.id
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!("delete from auth_session where user_id = $1", user.id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!("delete from oauth_identity where user_id = $1", user.id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        info!(user_id = %user.id, ap_id = %user.ap_id, "user deleted");
+
+        self.invalidate_cache_key(CacheKey::UserByApId(&user.ap_id))
+            .await;
+        if user.is_local {
+            self.invalidate_cache_key(CacheKey::LocalUserByUsername(&user.username))
+                .await;
+        }
+
+        Ok(Some(user))
+    }
+
+    async fn set_aliases(&self, user_id: Uuid, aliases: &[Url]) -> Result<User, UserError> {
+        trace!(%user_id, count = aliases.len(), "setting aliases");
+
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            update "user"
+            set also_known_as = $2
+            where id = $1
+            returning
+                id,
+                ap_id,
+                username,
+                name,
+                inbox,
+                shared_inbox as "shared_inbox: sellershut_core::Url",
+                hide_network,
+                deleted_at,
+                also_known_as as "also_known_as: Vec<sellershut_core::Url>",
+                moved_to as "moved_to: sellershut_core::Url",
+                public_key,
+                avatar,
+                null::text as "private_key: RedactedSecret",
+                kind as "kind: ActorType",
+                last_refreshed_at,
+                created_at,
+                is_local
+            "#,
+            user_id,
+            &aliases.iter().map(Url::to_string).collect::<Vec<_>>()
+        )
+        .fetch_one(&self.database)
+        .await?;
+
+        self.cache_user(&user).await;
+
+        Ok(user)
+    }
+
+    async fn set_moved_to(&self, user_id: Uuid, target: &Url) -> Result<User, UserError> {
+        trace!(%user_id, %target, "setting move target");
+
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            update "user"
+            set moved_to = $2
+            where id = $1
+            returning
+                id,
+                ap_id,
+                username,
+                name,
+                inbox,
+                shared_inbox as "shared_inbox: sellershut_core::Url",
+                hide_network,
+                deleted_at,
+                also_known_as as "also_known_as: Vec<sellershut_core::Url>",
+                moved_to as "moved_to: sellershut_core::Url",
+                public_key,
+                avatar,
+                null::text as "private_key: RedactedSecret",
+                kind as "kind: ActorType",
+                last_refreshed_at,
+                created_at,
+                is_local
+            "#,
+            user_id,
+            target.as_str()
+        )
+        .fetch_one(&self.database)
+        .await?;
+
+        self.cache_user(&user).await;
+
+        Ok(user)
+    }
+
+    async fn local_followers(&self, user_id: Uuid) -> Result<Vec<User>, UserError> {
+        trace!(%user_id, "listing local followers");
+
+        let followers = sqlx::query_as!(
+            User,
+            r#"
+            select
+                u.id,
+                u.ap_id,
+                u.username,
+                u.name,
+                u.inbox,
+                u.shared_inbox as "shared_inbox: sellershut_core::Url",
+                u.hide_network,
+                u.deleted_at,
+                u.also_known_as as "also_known_as: Vec<sellershut_core::Url>",
+                u.moved_to as "moved_to: sellershut_core::Url",
+                u.public_key,
+                u.avatar,
+                null::text as "private_key: RedactedSecret",
+                u.kind as "kind: ActorType",
+                u.last_refreshed_at,
+                u.created_at,
+                u.is_local
+            from follow as f
+            join "user" as u on u.id = f.follower_id
+            where
+                f.followee_id = $1
+                and f.state = 'Accepted'
+                and u.is_local
+                and u.deleted_at is null
+            "#,
+            user_id
+        )
+        .fetch_all(&self.database)
+        .await?;
+
+        Ok(followers)
+    }
+
+    async fn rotate_key(
+        &self,
+        user_id: Uuid,
+        public_key: &str,
+        private_key: &RedactedSecret,
+        grace_period: time::Duration,
+    ) -> Result<(User, RetiredKey), UserError> {
+        trace!(%user_id, "rotating key");
+
+        let private_key = self.seal(Some(private_key))?;
+        let mut tx = self.database.begin().await?;
+
+        sqlx::query!(
+            "delete from retired_key where user_id = $1 and expires_at <= now()",
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let old_public_key = sqlx::query_scalar!(
+            r#"select public_key from "user" where id = $1 and is_local for update"#,
+            user_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let retired = sqlx::query_as!(
+            RetiredKey,
+            r#"
+            insert into retired_key (id, user_id, public_key, expires_at)
+            values ($1, $2, $3, now() + make_interval(secs => $4))
+            returning id, user_id, public_key, retired_at, expires_at
+            "#,
+            key_version(&old_public_key),
+            user_id,
+            old_public_key,
+            grace_period.as_seconds_f64()
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            update "user"
+            set
+                public_key = $2,
+                private_key = $3
+            where id = $1
+            returning
+                id,
+                ap_id,
+                username,
+                name,
+                inbox,
+                shared_inbox as "shared_inbox: sellershut_core::Url",
+                hide_network,
+                deleted_at,
+                also_known_as as "also_known_as: Vec<sellershut_core::Url>",
+                moved_to as "moved_to: sellershut_core::Url",
+                public_key,
+                avatar,
+                null::text as "private_key: RedactedSecret",
+                kind as "kind: ActorType",
+                last_refreshed_at,
+                created_at,
+                is_local
+            "#,
+            user_id,
+            public_key,
+            private_key as _,
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        info!(user_id = %user.id, retired_key = %retired.id, "key rotated");
+
+        self.cache_user(&user).await;
+
+        Ok((user, retired))
+    }
+
+    async fn private_key(&self, user_id: Uuid) -> Result<Option<RedactedSecret>, UserError> {
+        trace!(%user_id, "loading private key");
+
+        let private_key = sqlx::query_scalar!(
+            r#"select private_key as "private_key: RedactedSecret" from "user" where id = $1"#,
+            user_id
+        )
+        .fetch_optional(&self.database)
+        .await?
+        .flatten();
+
+        private_key
+            .map(|key| Ok(RedactedSecret::from(self.keyring.open(&key.expose())?)))
+            .transpose()
+    }
+
+    async fn retired_keys(&self, user_id: Uuid) -> Result<Vec<RetiredKey>, UserError> {
+        trace!(%user_id, "listing retired keys");
+
+        let keys = sqlx::query_as!(
+            RetiredKey,
+            r#"
+            select id, user_id, public_key, retired_at, expires_at
+            from retired_key
+            where
+                user_id = $1
+                and expires_at > now()
+            order by retired_at desc
+            "#,
+            user_id
+        )
+        .fetch_all(&self.database)
+        .await?;
+
+        Ok(keys)
+    }
+
+    async fn reseal_private_keys(&self) -> Result<u64, UserError> {
+        trace!("encrypting private keys again");
+
+        let mut tx = self.database.begin().await?;
+
+        let keys = sqlx::query!(
+            r#"
+            select id, private_key as "private_key!: RedactedSecret"
+            from "user"
+            where private_key is not null
+            for update
+            "#
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut resealed = 0;
+        for key in keys {
+            let private_key = key.private_key.expose();
+            if !self.keyring.needs_reseal(&private_key) {
+                continue;
+            }
+
+            let private_key = RedactedSecret::from(self.keyring.reseal(&private_key)?);
+            sqlx::query!(
+                r#"update "user" set private_key = $2 where id = $1"#,
+                key.id,
+                private_key as _
+            )
+            .execute(&mut *tx)
+            .await?;
+            resealed += 1;
+        }
+
+        tx.commit().await?;
+
+        info!(resealed, "private keys encrypted again");
+
+        Ok(resealed)
+    }
 }
 
 const USER_CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+/// Usage statistics are only reported in aggregate, so they may lag behind a little.
+pub const USER_COUNTS_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
 
 impl UserService {
-    pub fn new(pool: sqlx::PgPool, cache: Cache) -> Self {
+    pub fn new(pool: sqlx::PgPool, cache: Cache, keyring: Keyring) -> Self {
         Self {
             database: pool,
             cache,
+            keyring,
         }
     }
 
+    /// Encrypt a private key before it is stored.
+    fn seal(
+        &self,
+        private_key: Option<&RedactedSecret>,
+    ) -> Result<Option<RedactedSecret>, UserError> {
+        private_key
+            .map(|key| Ok(RedactedSecret::from(self.keyring.seal(&key.expose())?)))
+            .transpose()
+    }
+
+    async fn delete_follow(
+        &self,
+        follower_id: Uuid,
+        followee_id: Uuid,
+    ) -> Result<Option<Follow>, UserError> {
+        trace!(%follower_id, %followee_id, "deleting follow");
+
+        let follow = sqlx::query_as!(
+            Follow,
+            r#"
+            delete from follow
+            where
+                follower_id = $1
+                and followee_id = $2
+            returning
+                follower_id,
+                followee_id,
+                state as "state: FollowState",
+                activity_id,
+                created_at,
+                updated_at
+            "#,
+            follower_id,
+            followee_id
+        )
+        .fetch_optional(&self.database)
+        .await?;
+
+        Ok(follow)
+    }
+
     async fn get_cached_user(&self, key: CacheKey<'_>) -> Option<User> {
         trace!(
             cache_key = %key,
@@ -555,7 +1655,7 @@ impl UserService {
         self.cache_user_key(ap_id_key, &value).await;
 
         // cache them by username if ttey are local
-        if user.is_local {
+        if user.is_local && user.deleted_at.is_none() {
             let username_key = CacheKey::LocalUserByUsername(&user.username);
             self.cache_user_key(username_key, &value).await;
         }
@@ -1,6 +1,7 @@
 use redis::{RedisWrite, ToRedisArgs, ToSingleRedisArg};
 use sellershut_core::Url;
 use std::fmt;
+use uuid::Uuid;
 
 const CACHE_NAMESPACE: &str = "app:v1";
 
@@ -8,6 +9,10 @@ const CACHE_NAMESPACE: &str = "app:v1";
 pub enum CacheKey<'a> {
     LocalUserByUsername(&'a str),
     UserByApId(&'a Url),
+    LocalUserCounts,
+    ListingById(Uuid),
+    LocalListingCount,
+    CategoryTree,
 }
 
 impl CacheKey<'_> {
@@ -20,6 +25,14 @@ impl CacheKey<'_> {
             Self::UserByApId(ap_id) => {
                 format!("{CACHE_NAMESPACE}:user:ap-id:{ap_id}")
             }
+
+            Self::LocalUserCounts => format!("{CACHE_NAMESPACE}:user:local:counts"),
+
+            Self::ListingById(id) => format!("{CACHE_NAMESPACE}:listing:id:{id}"),
+
+            Self::LocalListingCount => format!("{CACHE_NAMESPACE}:listing:local:count"),
+
+            Self::CategoryTree => format!("{CACHE_NAMESPACE}:category:tree"),
         }
     }
 }
@@ -3,3 +3,6 @@ pub mod users;
 
 #[cfg(feature = "cache-key")]
 pub mod cache_key;
+
+#[cfg(feature = "crypto")]
+pub mod crypto;
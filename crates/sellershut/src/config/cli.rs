@@ -1,6 +1,7 @@
 use std::path::PathBuf;
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+use sellershut_core::activity::BlockSeverity;
 
 /// A federated marketplace platform
 #[derive(Parser, Debug)]
@@ -21,4 +22,53 @@ pub enum Commands {
         /// The filepath to write the config file to
         output: PathBuf,
     },
+    /// Block a remote domain and its subdomains
+    BlockDomain {
+        /// The domain to block
+        domain: String,
+        #[arg(short, long, value_enum, default_value_t = Severity::Suspend)]
+        /// How the domain is restricted
+        severity: Severity,
+        #[arg(short, long)]
+        /// Reason shown on the public list of blocks
+        reason: Option<String>,
+    },
+    /// Remove a domain block
+    UnblockDomain {
+        /// The blocked domain
+        domain: String,
+    },
+    /// Give a local user, or the instance actor, a new signing key
+    ///
+    /// Followers are sent the new key by the running server's delivery queue. Restart the
+    /// server after rotating the instance actor's key.
+    RotateKey {
+        /// Username of the local user, the instance name for the instance actor
+        username: String,
+    },
+    /// Encrypt all private keys again with the configured master key
+    ///
+    /// Run after changing `master-key`, with the old key still listed in
+    /// `previous-master-keys`, or after setting a master key for the first time.
+    ReencryptKeys,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum Severity {
+    /// Accept activities but hide the domain's content from public views
+    Silence,
+    /// Refuse all federation with the domain
+    Suspend,
+    /// Federate, but never fetch media from the domain
+    RejectMedia,
+}
+
+impl From<Severity> for BlockSeverity {
+    fn from(value: Severity) -> Self {
+        match value {
+            Severity::Silence => BlockSeverity::Silence,
+            Severity::Suspend => BlockSeverity::Suspend,
+            Severity::RejectMedia => BlockSeverity::RejectMedia,
+        }
+    }
 }
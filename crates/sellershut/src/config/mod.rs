@@ -13,6 +13,7 @@ pub struct Configuration {
     pub log: log::Log,
     pub database: sellershut_svc::database::Config,
     pub cache: sellershut_svc::cache::Config,
+    pub media: sellershut_media::config::Config,
 }
 
 pub fn load(cli: Option<&PathBuf>) -> Configuration {
@@ -1,6 +1,7 @@
 use std::collections::{HashMap, HashSet};
 
 use sellershut_core::auth::OauthProvider;
+use sellershut_utilities::crypto::{KeyError, Keyring};
 use serde::{Deserialize, Serialize};
 use url::Url;
 
@@ -14,6 +15,14 @@ pub struct Server {
     pub cors: Cors,
     pub oauth: OauthConfig,
     pub url: FrontendUrl,
+    pub delivery: Delivery,
+    pub actor_refresh: ActorRefresh,
+    pub key_rotation: KeyRotation,
+    pub key_encryption: KeyEncryption,
+    pub federation: Federation,
+    pub resolve_limit: RateLimit,
+    /// Usernames of local users allowed to use the admin API
+    pub admins: Vec<String>,
     #[serde(default = "instance_name")]
     pub instance_name: String,
 }
@@ -27,6 +36,13 @@ impl Default for Server {
             cors: Default::default(),
             oauth: Default::default(),
             url: Default::default(),
+            delivery: Default::default(),
+            actor_refresh: Default::default(),
+            key_rotation: Default::default(),
+            key_encryption: Default::default(),
+            federation: Default::default(),
+            resolve_limit: Default::default(),
+            admins: Default::default(),
             instance_name: instance_name(),
         }
     }
@@ -46,6 +62,12 @@ pub struct Port(u16);
 #[derive(Clone, Deserialize, Serialize, Debug)]
 pub struct FrontendUrl(Url);
 
+impl From<FrontendUrl> for Url {
+    fn from(value: FrontendUrl) -> Self {
+        value.0
+    }
+}
+
 impl Default for FrontendUrl {
     fn default() -> Self {
         Self(Url::parse("http://localhost:5173").expect("frontend url"))
@@ -81,6 +103,120 @@ pub struct Request {
     pub timeout_duration: u64,
 }
 
+/// Outgoing activity delivery queue
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct Delivery {
+    /// Seconds between polls of the queue
+    pub poll_interval: u64,
+    /// Deliveries sent per poll
+    pub batch_size: i64,
+    /// Deliveries sent to the same host at once
+    pub per_host_concurrency: usize,
+    /// Hours after which a failing delivery is given up and its inbox marked dead
+    pub max_age: u64,
+}
+
+impl Default for Delivery {
+    fn default() -> Self {
+        Self {
+            poll_interval: 5,
+            batch_size: 100,
+            per_host_concurrency: 4,
+            max_age: 48,
+        }
+    }
+}
+
+/// Re-fetching of remote actors
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct ActorRefresh {
+    /// Hours after which a remote actor is fetched again on its next use
+    pub stale_after: u64,
+    /// Minutes between sweeps refreshing the stalest actors
+    pub sweep_interval: u64,
+    /// Actors refreshed per sweep
+    pub batch_size: i64,
+}
+
+impl Default for ActorRefresh {
+    fn default() -> Self {
+        Self {
+            stale_after: 24,
+            sweep_interval: 15,
+            batch_size: 50,
+        }
+    }
+}
+
+/// Replacing the signing keys of local actors
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct KeyRotation {
+    /// Hours a replaced public key is still published, for signatures made before the rotation
+    pub grace_period: u64,
+}
+
+impl Default for KeyRotation {
+    fn default() -> Self {
+        Self { grace_period: 48 }
+    }
+}
+
+/// Encryption of actor private keys in the database
+#[derive(Clone, Deserialize, Serialize, Debug, Default)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct KeyEncryption {
+    /// 32 random bytes as url-safe base64. Private keys are stored unencrypted without it
+    pub master_key: Option<String>,
+    /// Master keys used before, needed until `reencrypt-keys` ran with the current one
+    pub previous_master_keys: Vec<String>,
+}
+
+impl KeyEncryption {
+    pub fn keyring(&self) -> Result<Keyring, KeyError> {
+        Keyring::new(self.master_key.as_deref(), &self.previous_master_keys)
+    }
+}
+
+/// Which remote domains we federate with
+#[derive(Clone, Deserialize, Serialize, Debug, Default)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct Federation {
+    pub mode: FederationMode,
+    /// Domains federated with in allowlist mode, their subdomains are allowed as well
+    pub allowed_domains: Vec<String>,
+}
+
+#[derive(Clone, Copy, Deserialize, Serialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum FederationMode {
+    /// Federate with every domain that is not blocked
+    #[default]
+    Blocklist,
+    /// Only federate with `allowed-domains`
+    Allowlist,
+}
+
+/// Requests a user can make per window
+#[derive(Clone, Deserialize, Serialize, Debug)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct RateLimit {
+    pub requests: u32,
+    /// Length of the window in seconds
+    pub window: u64,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            requests: 30,
+            window: 60,
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 #[serde(rename_all = "kebab-case", default)]
 pub struct Cors {
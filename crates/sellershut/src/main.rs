@@ -9,26 +9,33 @@ use std::{
     sync::Arc,
 };
 
+use activitypub_federation::config::FederationConfig;
 use anyhow::Result;
 use clap::Parser;
 use futures_util::TryFutureExt;
 use sellershut_auth::OauthDriver;
+use sellershut_categories::CategoryService;
+use sellershut_federation::{FederationDriver, FederationService};
+use sellershut_listings::ListingService;
 use sellershut_svc::cache::Cache;
-use sellershut_users::UserService;
+use sellershut_users::{UserDriver, UserService};
+use sellershut_utilities::crypto::Keyring;
 use tokio::net::TcpListener;
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::{
     config::cli::{Args, Commands},
-    server::state::State,
+    server::state::{AppState, State},
 };
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
-    if let Some(Commands::GenerateConfig { output }) = args.command {
-        let str = toml::to_string_pretty(&config::Configuration::default())?;
-        std::fs::write(&output, &str)?;
+    if let Some(Commands::GenerateConfig { output }) = &args.command {
+        let mut config = config::Configuration::default();
+        config.server.key_encryption.master_key = Some(Keyring::generate_master_key());
+        let str = toml::to_string_pretty(&config)?;
+        std::fs::write(output, &str)?;
         println!("Config written to: {:?}", output);
         return Ok(());
     }
@@ -36,6 +43,30 @@ async fn main() -> Result<()> {
 
     let (_log_handle, _log_guard) = logger::log(&config.log)?;
 
+    match args.command {
+        Some(Commands::BlockDomain {
+            domain,
+            severity,
+            reason,
+        }) => {
+            let federation = FederationService::new(config.database.connect().await?);
+            let block = federation
+                .block_domain(&domain, severity.into(), reason.as_deref())
+                .await?;
+            println!("Blocked {} ({:?})", block.domain, block.severity);
+            return Ok(());
+        }
+        Some(Commands::UnblockDomain { domain }) => {
+            let federation = FederationService::new(config.database.connect().await?);
+            match federation.unblock_domain(&domain).await? {
+                Some(block) => println!("Unblocked {}", block.domain),
+                None => println!("{domain} is not blocked"),
+            }
+            return Ok(());
+        }
+        _ => {}
+    }
+
     let addr = SocketAddr::from((Ipv6Addr::UNSPECIFIED, config.server.port.into()));
 
     let (database, cache) = futures_util::try_join!(
@@ -43,11 +74,47 @@ async fn main() -> Result<()> {
         Cache::connect(&config.cache).map_err(anyhow::Error::from),
     )?;
 
-    let user = UserService::new(database.clone(), cache);
+    let keyring = config.server.key_encryption.keyring()?;
+    if !keyring.is_enabled() {
+        warn!("no master key is configured, private keys are stored unencrypted");
+    }
+    let user = UserService::new(database.clone(), cache.clone(), keyring);
+
+    if let Some(Commands::ReencryptKeys) = &args.command {
+        anyhow::ensure!(
+            config.server.key_encryption.master_key.is_some(),
+            "set a master key to encrypt private keys with"
+        );
+        let count = user.reseal_private_keys().await?;
+        println!("Encrypted {count} private keys again");
+        return Ok(());
+    }
 
-    let state = State::new(&config, user, database.clone()).await?;
+    let listing = ListingService::new(database.clone(), cache.clone());
+    let category = CategoryService::new(database.clone(), cache);
+    let state = State::new(&config, user, listing, category, database.clone()).await?;
 
-    let app = server::router::router(Arc::clone(&state), config).await?;
+    let federation_config = server::router::federation_config(Arc::clone(&state), &config).await?;
+
+    if let Some(Commands::RotateKey { username }) = &args.command {
+        return rotate_key(username, &federation_config).await;
+    }
+
+    let delivery_task = tokio::spawn(server::delivery::run(
+        federation_config.clone(),
+        config.server.delivery.clone(),
+    ));
+    let probe_task = tokio::spawn(server::delivery::probe::run(federation_config.clone()));
+    let refresh_task = tokio::spawn(server::delivery::refresh::run(
+        federation_config.clone(),
+        config.server.actor_refresh.clone(),
+    ));
+    let processing_task = tokio::spawn(server::processing::run(
+        federation_config.clone(),
+        config.media.processing.clone(),
+    ));
+
+    let app = server::router::router(federation_config, config).await?;
 
     let maintenance_task = tokio::spawn(auth_housekeeping(Arc::clone(&state.auth)));
     let listener = TcpListener::bind(addr).await?;
@@ -56,6 +123,29 @@ async fn main() -> Result<()> {
     axum::serve(listener, app).await?;
 
     maintenance_task.abort();
+    delivery_task.abort();
+    probe_task.abort();
+    refresh_task.abort();
+    processing_task.abort();
+
+    Ok(())
+}
+
+async fn rotate_key(username: &str, federation_config: &FederationConfig<AppState>) -> Result<()> {
+    let data = federation_config.to_request_data();
+    let Some(user) = data.user.get_user(username).await? else {
+        anyhow::bail!("no local user named {username}");
+    };
+
+    let (user, retired) = server::activities::update::rotate_key(&user.into(), &data)
+        .await
+        .map_err(|e| anyhow::anyhow!("rotating the key failed: {e}"))?;
+    println!(
+        "Rotated the key of {}, the old key {} is published until {}",
+        user.data().username,
+        user.versioned_key_id(retired.id),
+        retired.expires_at
+    );
 
     Ok(())
 }
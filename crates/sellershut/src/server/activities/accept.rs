@@ -0,0 +1,122 @@
+use activitypub_federation::{
+    config::Data,
+    fetch::object_id::ObjectId,
+    kinds::activity::AcceptType,
+    protocol::verification::{verify_domains_match, verify_urls_match},
+    traits::{Activity, Actor},
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::server::{
+    AppError,
+    activities::{self, follow::Follow},
+    entities::user::User,
+    state::AppState,
+    utilities,
+};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Accept {
+    pub(crate) actor: ObjectId<User>,
+    pub(crate) object: Follow,
+    #[serde(rename = "type")]
+    kind: AcceptType,
+    id: Url,
+}
+
+impl Accept {
+    pub fn new(actor: ObjectId<User>, object: Follow, id: Url) -> Self {
+        Self {
+            actor,
+            object,
+            kind: Default::default(),
+            id,
+        }
+    }
+}
+
+/// Accept a pending follow of `followee` by `follower`.
+///
+/// Remote followers are sent an `Accept`. Accepting an already accepted follow sends it again,
+/// which lets a follower that missed the first one catch up.
+pub async fn accept(
+    follower: &User,
+    followee: &User,
+    data: &Data<AppState>,
+) -> Result<Option<sellershut_core::user::Follow>, AppError> {
+    let Some(follow) = data
+        .user
+        .accept_follow(follower.data().id, followee.data().id)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    if !follower.data().is_local {
+        let activity = Accept::new(
+            followee.data().ap_id.inner().into(),
+            Follow::new(
+                follower.data().ap_id.inner().into(),
+                followee.data().ap_id.inner().into(),
+                follow.activity_id.inner(),
+            ),
+            utilities::activity_url(data.port, data.domain())?,
+        );
+        activities::send(
+            activity,
+            followee,
+            vec![follower.shared_inbox_or_inbox()],
+            data,
+        )
+        .await?;
+    }
+
+    Ok(Some(follow))
+}
+
+#[async_trait::async_trait]
+impl Activity for Accept {
+    type DataType = AppState;
+    type Error = AppError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn verify(&self, _data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        verify_domains_match(&self.id, self.actor.inner())?;
+        // Only the account that was followed may answer the follow request.
+        verify_urls_match(self.actor.inner(), self.object.object.inner())?;
+        Ok(())
+    }
+
+    async fn receive(self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        let Some((follower, followee)) = follow_pair(&self.object, data).await? else {
+            tracing::debug!(actor = %self.actor, "accept for an unknown follow");
+            return Ok(());
+        };
+
+        data.user.accept_follow(follower.id, followee.id).await?;
+        Ok(())
+    }
+}
+
+/// Load both sides of a follow sent by a local user.
+pub(crate) async fn follow_pair(
+    follow: &Follow,
+    data: &Data<AppState>,
+) -> Result<Option<(sellershut_core::user::User, sellershut_core::user::User)>, AppError> {
+    let follower = match data.user.get_user_by_id(follow.actor.inner()).await? {
+        Some(user) if user.is_local => user,
+        _ => return Ok(None),
+    };
+    let followee = data.user.get_user_by_id(follow.object.inner()).await?;
+
+    Ok(followee.map(|followee| (follower, followee)))
+}
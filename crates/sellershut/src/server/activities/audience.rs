@@ -0,0 +1,54 @@
+use std::collections::HashSet;
+
+use activitypub_federation::{config::Data, protocol::helpers::deserialize_one_or_many};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::server::{AppError, state::AppState, utilities};
+
+/// The `to` and `cc` addressing of an activity.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Audience {
+    #[serde(
+        default,
+        deserialize_with = "deserialize_one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub(crate) to: Vec<Url>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub(crate) cc: Vec<Url>,
+}
+
+impl Audience {
+    /// Resolve the local users an activity is addressed to.
+    ///
+    /// Activities arriving through the shared inbox are processed once, so this is what fans
+    /// them out to each addressed local user. Every user is returned at most once.
+    pub async fn local_recipients(
+        &self,
+        data: &Data<AppState>,
+    ) -> Result<Vec<sellershut_core::user::User>, AppError> {
+        let origin = utilities::base_url(data.port, data.domain())?.origin();
+        let mut seen = HashSet::new();
+        let mut recipients = Vec::new();
+
+        for id in self.to.iter().chain(&self.cc) {
+            if id.origin() != origin || !seen.insert(id) {
+                continue;
+            }
+
+            if let Some(user) = data.user.get_user_by_id(id).await?
+                && user.is_local
+                && user.deleted_at.is_none()
+            {
+                recipients.push(user);
+            }
+        }
+
+        Ok(recipients)
+    }
+}
@@ -0,0 +1,107 @@
+use activitypub_federation::{
+    config::Data,
+    fetch::object_id::ObjectId,
+    kinds::{activity::CreateType, public},
+    protocol::verification::verify_domains_match,
+    traits::{Activity, Object},
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::server::{
+    AppError,
+    activities::{self, ObjectRef, audience::Audience},
+    entities::{listing::Listing, user::User},
+    state::AppState,
+    utilities,
+};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Create {
+    pub(crate) actor: ObjectId<User>,
+    pub(crate) object: ObjectRef,
+    #[serde(rename = "type")]
+    kind: CreateType,
+    id: Url,
+    #[serde(flatten)]
+    pub(crate) audience: Audience,
+}
+
+impl Create {
+    pub fn new(actor: ObjectId<User>, object: ObjectRef, id: Url, audience: Audience) -> Self {
+        Self {
+            actor,
+            object,
+            kind: Default::default(),
+            id,
+            audience,
+        }
+    }
+}
+
+/// Send a listing that just became public to the servers of its owner's followers.
+pub async fn create_listing(
+    owner: &User,
+    listing: Listing,
+    data: &Data<AppState>,
+) -> Result<(), AppError> {
+    let object = listing.into_json(data).await?;
+
+    let activity = Create::new(
+        owner.data().ap_id.inner().into(),
+        ObjectRef::embed(&object)?,
+        utilities::activity_url(data.port, data.domain())?,
+        Audience {
+            to: vec![public()],
+            cc: vec![owner.collection_url("followers")?],
+        },
+    );
+    let inboxes = data.user.follower_inboxes(owner.data().id).await?;
+
+    activities::send(activity, owner, inboxes, data).await
+}
+
+#[async_trait::async_trait]
+impl Activity for Create {
+    type DataType = AppState;
+    type Error = AppError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn verify(&self, _data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        verify_domains_match(&self.id, self.actor.inner())?;
+        verify_domains_match(self.object.id(), self.actor.inner())?;
+        Ok(())
+    }
+
+    async fn receive(self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        if let Some(listing) = self.object.as_listing() {
+            return Listing::receive(listing, self.actor.inner(), data).await;
+        }
+
+        let recipients = self.audience.local_recipients(data).await?;
+
+        if recipients.is_empty() {
+            tracing::debug!(id = %self.id, "create is not addressed to any local user");
+            return Ok(());
+        }
+
+        for recipient in recipients {
+            tracing::debug!(
+                actor = %self.actor,
+                object = %self.object.id(),
+                recipient = %recipient.username,
+                "ignoring create for unsupported object"
+            );
+        }
+
+        Ok(())
+    }
+}
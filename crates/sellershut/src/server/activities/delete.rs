@@ -0,0 +1,153 @@
+use activitypub_federation::{
+    config::Data,
+    fetch::object_id::ObjectId,
+    kinds::{activity::DeleteType, public},
+    protocol::verification::verify_domains_match,
+    traits::Activity,
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::server::{
+    AppError,
+    activities::{self, ObjectRef, audience::Audience},
+    entities::{listing::Listing, user::User},
+    state::AppState,
+    utilities,
+};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Delete {
+    pub(crate) actor: ObjectId<User>,
+    pub(crate) object: ObjectRef,
+    #[serde(rename = "type")]
+    kind: DeleteType,
+    id: Url,
+    #[serde(flatten)]
+    pub(crate) audience: Audience,
+}
+
+impl Delete {
+    pub fn new(actor: ObjectId<User>, object: ObjectRef, id: Url, audience: Audience) -> Self {
+        Self {
+            actor,
+            object,
+            kind: Default::default(),
+            id,
+            audience,
+        }
+    }
+}
+
+/// Delete the account of a local `user` and tell their followers' servers about it.
+///
+/// The `Delete` is queued before the follows go away with the account. The account itself is
+/// kept as a tombstone, so the queued deliveries can still be signed with its key, and its
+/// listings are archived.
+pub async fn delete_actor(user: &User, data: &Data<AppState>) -> Result<(), AppError> {
+    let activity = Delete::new(
+        user.data().ap_id.inner().into(),
+        ObjectRef::Id(user.data().ap_id.inner()),
+        utilities::activity_url(data.port, data.domain())?,
+        Audience {
+            to: vec![public()],
+            cc: vec![user.collection_url("followers")?],
+        },
+    );
+    let inboxes = data.user.follower_inboxes(user.data().id).await?;
+
+    activities::send(activity, user, inboxes, data).await?;
+    data.user.delete_user(&user.data().ap_id.inner()).await?;
+    data.listing
+        .archive_listings_by_owner(user.data().id)
+        .await?;
+
+    Ok(())
+}
+
+/// Tell the servers of a listing owner's followers that the listing is gone.
+pub async fn delete_listing(
+    owner: &User,
+    listing: &Listing,
+    data: &Data<AppState>,
+) -> Result<(), AppError> {
+    let activity = Delete::new(
+        owner.data().ap_id.inner().into(),
+        ObjectRef::Id(listing.data().ap_id.inner()),
+        utilities::activity_url(data.port, data.domain())?,
+        Audience {
+            to: vec![public()],
+            cc: vec![owner.collection_url("followers")?],
+        },
+    );
+    let inboxes = data.user.follower_inboxes(owner.data().id).await?;
+
+    activities::send(activity, owner, inboxes, data).await
+}
+
+#[async_trait::async_trait]
+impl Activity for Delete {
+    type DataType = AppState;
+    type Error = AppError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn verify(&self, _data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        verify_domains_match(&self.id, self.actor.inner())?;
+        verify_domains_match(self.object.id(), self.actor.inner())?;
+        Ok(())
+    }
+
+    async fn receive(self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        // An actor deleting its own account.
+        if self.object.id() == self.actor.inner() {
+            if let Some(user) = data.user.get_user_by_id(self.actor.inner()).await?
+                && !user.is_local
+            {
+                data.user.delete_user(self.actor.inner()).await?;
+                data.listing.archive_listings_by_owner(user.id).await?;
+            }
+            return Ok(());
+        }
+
+        // A seller taking down one of their listings.
+        if let Some(listing) = data.listing.get_listing_by_ap_id(self.object.id()).await? {
+            if let Some(owner) = data.user.get_user_by_id(self.actor.inner()).await?
+                && !listing.is_local
+                && listing.owner_id == owner.id
+            {
+                // Their files only link to the other instance, nothing else uses them.
+                data.media
+                    .replace_remote_media(listing.id, owner.id, &[])
+                    .await?;
+                data.listing.delete_listing(listing.id).await?;
+            }
+            return Ok(());
+        }
+
+        let recipients = self.audience.local_recipients(data).await?;
+
+        if recipients.is_empty() {
+            tracing::debug!(id = %self.id, "delete is not addressed to any local user");
+            return Ok(());
+        }
+
+        for recipient in recipients {
+            tracing::debug!(
+                actor = %self.actor,
+                object = %self.object.id(),
+                recipient = %recipient.username,
+                "ignoring delete for unsupported object"
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+use activitypub_federation::{
+    config::Data,
+    fetch::object_id::ObjectId,
+    kinds::activity::FollowType,
+    protocol::verification::verify_domains_match,
+    traits::{Activity, Actor},
+};
+use sellershut_core::user::FollowState;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::server::{
+    AppError,
+    activities::{self, accept},
+    entities::user::User,
+    state::AppState,
+    utilities,
+};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Follow {
+    pub(crate) actor: ObjectId<User>,
+    pub(crate) object: ObjectId<User>,
+    #[serde(rename = "type")]
+    kind: FollowType,
+    id: Url,
+}
+
+impl Follow {
+    pub fn new(actor: ObjectId<User>, object: ObjectId<User>, id: Url) -> Self {
+        Self {
+            actor,
+            object,
+            kind: Default::default(),
+            id,
+        }
+    }
+}
+
+/// Make `follower` follow `followee`.
+///
+/// Follows between local users are accepted straight away, remote users are sent a `Follow`
+/// and the relationship stays pending until they answer it.
+pub async fn follow(
+    follower: &User,
+    followee: &User,
+    data: &Data<AppState>,
+) -> Result<sellershut_core::user::Follow, AppError> {
+    let activity_id = utilities::activity_url(data.port, data.domain())?;
+    let state = if followee.data().is_local {
+        FollowState::Accepted
+    } else {
+        FollowState::Pending
+    };
+
+    let follow = data
+        .user
+        .follow(follower.data().id, followee.data().id, &activity_id, state)
+        .await?;
+
+    if !followee.data().is_local && follow.state == FollowState::Pending {
+        let activity = Follow::new(
+            follower.data().ap_id.inner().into(),
+            followee.data().ap_id.inner().into(),
+            activity_id,
+        );
+        activities::send(
+            activity,
+            follower,
+            vec![followee.shared_inbox_or_inbox()],
+            data,
+        )
+        .await?;
+    }
+
+    Ok(follow)
+}
+
+#[async_trait::async_trait]
+impl Activity for Follow {
+    type DataType = AppState;
+    type Error = AppError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn verify(&self, _data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        verify_domains_match(&self.id, self.actor.inner())?;
+        Ok(())
+    }
+
+    async fn receive(self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        let followee = match data.user.get_user_by_id(self.object.inner()).await? {
+            Some(user) if user.is_local => User::from(user),
+            _ => {
+                tracing::debug!(object = %self.object, "follow for an unknown local user");
+                return Ok(());
+            }
+        };
+        let follower = self.actor.dereference(data).await?;
+
+        data.user
+            .follow(
+                follower.data().id,
+                followee.data().id,
+                &self.id,
+                FollowState::Pending,
+            )
+            .await?;
+
+        // There is no manual approval of followers, every request is accepted.
+        accept::accept(&follower, &followee, data).await?;
+        Ok(())
+    }
+}
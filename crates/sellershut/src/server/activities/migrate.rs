@@ -0,0 +1,157 @@
+use activitypub_federation::{
+    config::Data,
+    fetch::object_id::ObjectId,
+    kinds::{activity::MoveType, public},
+    protocol::verification::{verify_domains_match, verify_urls_match},
+    traits::Activity,
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::server::{
+    AppError,
+    activities::{self, audience::Audience, follow, undo},
+    entities::user::User,
+    state::AppState,
+    utilities,
+};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Move {
+    pub(crate) actor: ObjectId<User>,
+    pub(crate) object: ObjectId<User>,
+    pub(crate) target: ObjectId<User>,
+    #[serde(rename = "type")]
+    kind: MoveType,
+    id: Url,
+    #[serde(flatten)]
+    pub(crate) audience: Audience,
+}
+
+impl Move {
+    pub fn new(actor: ObjectId<User>, target: ObjectId<User>, id: Url, audience: Audience) -> Self {
+        Self {
+            object: actor.clone(),
+            actor,
+            target,
+            kind: Default::default(),
+            id,
+            audience,
+        }
+    }
+}
+
+/// Move a local `user` to the account `target`.
+///
+/// Nothing happens unless the target lists `user` in its `alsoKnownAs`. Followers' servers are
+/// sent a `Move` so they can follow the new account, local followers are moved straight away.
+pub async fn move_account(
+    user: &User,
+    target: &User,
+    data: &Data<AppState>,
+) -> Result<Option<User>, AppError> {
+    let origin = user.data().ap_id.inner();
+
+    if !target.is_also_known_as(&origin) {
+        return Ok(None);
+    }
+
+    let moved = User::from(
+        data.user
+            .set_moved_to(user.data().id, &target.data().ap_id.inner())
+            .await?,
+    );
+
+    let activity = Move::new(
+        origin.into(),
+        target.data().ap_id.inner().into(),
+        utilities::activity_url(data.port, data.domain())?,
+        Audience {
+            to: vec![public()],
+            cc: vec![user.collection_url("followers")?],
+        },
+    );
+    let inboxes = data.user.follower_inboxes(user.data().id).await?;
+    activities::send(activity, user, inboxes, data).await?;
+
+    migrate_followers(&moved, target, data).await?;
+
+    Ok(Some(moved))
+}
+
+/// Move the follows local users have of `origin` over to `target`.
+async fn migrate_followers(
+    origin: &User,
+    target: &User,
+    data: &Data<AppState>,
+) -> Result<(), AppError> {
+    let followers = data.user.local_followers(origin.data().id).await?;
+
+    for follower in followers {
+        let follower = User::from(follower);
+        if follower.data().id == target.data().id {
+            continue;
+        }
+
+        undo::unfollow(&follower, origin, data).await?;
+        follow::follow(&follower, target, data).await?;
+
+        tracing::debug!(
+            follower = %follower.data().username,
+            origin = %origin.data().ap_id,
+            target = %target.data().ap_id,
+            "migrated follow"
+        );
+    }
+
+    Ok(())
+}
+
+#[async_trait::async_trait]
+impl Activity for Move {
+    type DataType = AppState;
+    type Error = AppError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn verify(&self, _data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        verify_domains_match(&self.id, self.actor.inner())?;
+        // Only an account can move itself.
+        verify_urls_match(self.actor.inner(), self.object.inner())?;
+        Ok(())
+    }
+
+    async fn receive(self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        let origin = self.actor.dereference(data).await?;
+        if origin.data().is_local {
+            tracing::debug!(actor = %self.actor, "ignoring move of a local account");
+            return Ok(());
+        }
+
+        // The target is fetched again, its aliases may have changed since we last saw it.
+        let target = self.target.dereference_forced(data).await?;
+        if !target.is_also_known_as(self.actor.inner()) {
+            tracing::debug!(
+                actor = %self.actor,
+                target = %self.target,
+                "move target does not list the actor as an alias"
+            );
+            return Ok(());
+        }
+
+        let origin = User::from(
+            data.user
+                .set_moved_to(origin.data().id, &target.data().ap_id.inner())
+                .await?,
+        );
+
+        migrate_followers(&origin, &target, data).await
+    }
+}
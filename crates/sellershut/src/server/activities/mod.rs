@@ -0,0 +1,425 @@
+pub mod accept;
+pub mod audience;
+pub mod create;
+pub mod delete;
+pub mod follow;
+pub mod migrate;
+pub mod reject;
+pub mod undo;
+pub mod update;
+
+use std::fmt::Debug;
+
+use activitypub_federation::{
+    axum::inbox::{ActivityData, receive_activity},
+    config::Data,
+    error::Error as FederationError,
+    protocol::context::WithContext,
+    traits::Activity,
+};
+use axum::{
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use sellershut_federation::CreateActivity;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use url::Url;
+
+use crate::server::{
+    AppError,
+    activities::{
+        accept::Accept, create::Create, delete::Delete, follow::Follow, migrate::Move,
+        reject::Reject, undo::Undo, update::Update,
+    },
+    entities::{listing::ListingObject, user::User},
+    state::AppState,
+};
+
+/// Every activity type we accept in an inbox.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum InboxActivities {
+    Follow(Follow),
+    Undo(Undo),
+    Accept(Accept),
+    Reject(Reject),
+    Create(Create),
+    Update(Update),
+    Delete(Delete),
+    Move(Move),
+}
+
+/// An object nested in an activity, either embedded or referenced by its id.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum ObjectRef {
+    Id(Url),
+    Object {
+        id: Url,
+        #[serde(flatten)]
+        rest: serde_json::Map<String, serde_json::Value>,
+    },
+}
+
+impl ObjectRef {
+    /// Embed an object, which must serialize to a JSON object with an `id`.
+    pub fn embed<T: Serialize>(object: &T) -> Result<Self, AppError> {
+        Ok(serde_json::from_value(serde_json::to_value(object)?)?)
+    }
+
+    pub fn id(&self) -> &Url {
+        match self {
+            ObjectRef::Id(id) => id,
+            ObjectRef::Object { id, .. } => id,
+        }
+    }
+
+    /// The embedded object, if it is a listing.
+    pub fn as_listing(&self) -> Option<ListingObject> {
+        let ObjectRef::Object { id, rest } = self else {
+            return None;
+        };
+
+        let mut json = rest.clone();
+        json.insert("id".to_owned(), id.as_str().into());
+        serde_json::from_value(json.into()).ok()
+    }
+}
+
+#[async_trait::async_trait]
+impl Activity for InboxActivities {
+    type DataType = AppState;
+    type Error = AppError;
+
+    fn id(&self) -> &Url {
+        match self {
+            InboxActivities::Follow(activity) => activity.id(),
+            InboxActivities::Undo(activity) => activity.id(),
+            InboxActivities::Accept(activity) => activity.id(),
+            InboxActivities::Reject(activity) => activity.id(),
+            InboxActivities::Create(activity) => activity.id(),
+            InboxActivities::Update(activity) => activity.id(),
+            InboxActivities::Delete(activity) => activity.id(),
+            InboxActivities::Move(activity) => activity.id(),
+        }
+    }
+
+    fn actor(&self) -> &Url {
+        match self {
+            InboxActivities::Follow(activity) => activity.actor(),
+            InboxActivities::Undo(activity) => activity.actor(),
+            InboxActivities::Accept(activity) => activity.actor(),
+            InboxActivities::Reject(activity) => activity.actor(),
+            InboxActivities::Create(activity) => activity.actor(),
+            InboxActivities::Update(activity) => activity.actor(),
+            InboxActivities::Delete(activity) => activity.actor(),
+            InboxActivities::Move(activity) => activity.actor(),
+        }
+    }
+
+    async fn verify(&self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        match self {
+            InboxActivities::Follow(activity) => activity.verify(data).await,
+            InboxActivities::Undo(activity) => activity.verify(data).await,
+            InboxActivities::Accept(activity) => activity.verify(data).await,
+            InboxActivities::Reject(activity) => activity.verify(data).await,
+            InboxActivities::Create(activity) => activity.verify(data).await,
+            InboxActivities::Update(activity) => activity.verify(data).await,
+            InboxActivities::Delete(activity) => activity.verify(data).await,
+            InboxActivities::Move(activity) => activity.verify(data).await,
+        }
+    }
+
+    async fn receive(self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        // A verified activity is proof the sending instance is up.
+        if let Some(domain) = sellershut_federation::instance_domain(self.actor()) {
+            data.federation.record_instance_success(&domain).await?;
+        }
+
+        match self {
+            InboxActivities::Follow(activity) => activity.receive(data).await,
+            InboxActivities::Undo(activity) => activity.receive(data).await,
+            InboxActivities::Accept(activity) => activity.receive(data).await,
+            InboxActivities::Reject(activity) => activity.receive(data).await,
+            InboxActivities::Create(activity) => activity.receive(data).await,
+            InboxActivities::Update(activity) => activity.receive(data).await,
+            InboxActivities::Delete(activity) => activity.receive(data).await,
+            InboxActivities::Move(activity) => activity.receive(data).await,
+        }
+    }
+}
+
+/// Verify the HTTP signature of an incoming activity and hand it to its handler.
+///
+/// Successfully processed activities are answered with `202 Accepted`. When the signature does
+/// not match, the signing actor may have rotated its key, so it is fetched again the next time
+/// it is used, which is usually when the sender retries. Anyone can claim any key, so this
+/// happens at most once every ten minutes for an actor.
+pub async fn receive(
+    headers: &HeaderMap,
+    activity_data: ActivityData,
+    data: &Data<AppState>,
+) -> Response {
+    match receive_activity::<WithContext<InboxActivities>, User, AppState>(activity_data, data)
+        .await
+    {
+        Ok(()) => StatusCode::ACCEPTED.into_response(),
+        Err(error) => match rejection_status(&error) {
+            Some(status) => {
+                tracing::debug!(%status, error = %error.0, "inbox rejected activity");
+
+                if matches!(
+                    error.0.downcast_ref(),
+                    Some(FederationError::ActivitySignatureInvalid)
+                ) && let Some(actor) = signature_key_owner(headers)
+                    && data.stale_actor_limiter.check(actor.clone())
+                    && let Err(error) = data
+                        .user
+                        .set_last_refreshed_at(&actor, OffsetDateTime::UNIX_EPOCH)
+                        .await
+                {
+                    tracing::error!(%error, %actor, "marking actor stale failed");
+                }
+
+                status.into_response()
+            }
+            None => error.into_response(),
+        },
+    }
+}
+
+/// The actor owning the key an HTTP signature claims to be made with.
+///
+/// Key ids are the actor's id with a fragment, like `https://some.hut/users/seller#main-key`.
+fn signature_key_owner(headers: &HeaderMap) -> Option<Url> {
+    let signature = headers.get("signature")?.to_str().ok()?;
+
+    let key_id = signature.split(',').find_map(|param| {
+        let (name, value) = param.trim().split_once('=')?;
+        (name == "keyId").then(|| value.trim_matches('"'))
+    })?;
+
+    let mut owner = Url::parse(key_id).ok()?;
+    owner.set_fragment(None);
+
+    Some(owner)
+}
+
+/// Record `activity` as sent by `actor` and queue it for delivery to `inboxes`.
+///
+/// Every outgoing activity is recorded, public ones are listed in the actor's outbox. Delivery
+/// happens in the background, see [`crate::server::delivery`].
+pub async fn send<A>(
+    activity: A,
+    actor: &User,
+    inboxes: Vec<Url>,
+    data: &Data<AppState>,
+) -> Result<(), AppError>
+where
+    A: Activity + Serialize + Debug + Send + Sync,
+{
+    let json = serde_json::to_value(&activity)?;
+    let kind = json
+        .get("type")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or_default()
+        .to_owned();
+    let record = CreateActivity {
+        ap_id: activity.id().clone(),
+        actor_id: actor.data().id,
+        kind,
+        is_public: is_public(&json),
+        data: json,
+    };
+    data.federation.record_activity(&record).await?;
+
+    let payload = serde_json::to_value(WithContext::new_default(&activity))?;
+    data.federation
+        .enqueue_deliveries(actor.data().id, activity.id(), &payload, &inboxes)
+        .await?;
+
+    Ok(())
+}
+
+/// Whether an activity is addressed to the Public collection.
+fn is_public(activity: &serde_json::Value) -> bool {
+    const PUBLIC: [&str; 3] = [
+        "https://www.w3.org/ns/activitystreams#Public",
+        "as:Public",
+        "Public",
+    ];
+
+    ["to", "cc"]
+        .into_iter()
+        .filter_map(|field| activity.get(field))
+        .flat_map(|value| match value {
+            serde_json::Value::Array(values) => values.iter().collect(),
+            value => vec![value],
+        })
+        .filter_map(serde_json::Value::as_str)
+        .any(|id| PUBLIC.contains(&id))
+}
+
+/// Map failures caused by the sender to a client error. Anything else is our fault.
+fn rejection_status(error: &AppError) -> Option<StatusCode> {
+    match error.0.downcast_ref::<FederationError>()? {
+        // The signing actor could not be fetched, so its key could not be checked either.
+        FederationError::ActivitySignatureInvalid
+        | FederationError::ActivityBodyDigestInvalid
+        | FederationError::NotFound
+        | FederationError::ObjectDeleted { .. } => Some(StatusCode::UNAUTHORIZED),
+        FederationError::ParseReceivedActivity { .. }
+        | FederationError::UrlVerificationError { .. }
+        | FederationError::UrlParse { .. } => Some(StatusCode::BAD_REQUEST),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn check_rejection(error: FederationError, expected_result: Option<StatusCode>) {
+        let error = AppError::from(error);
+
+        assert_eq!(expected_result, rejection_status(&error));
+    }
+
+    #[track_caller]
+    fn check_signature_key_owner(signature: &str, expected_result: Option<&str>) {
+        let mut headers = HeaderMap::new();
+        headers.insert("signature", signature.parse().unwrap());
+
+        assert_eq!(
+            expected_result,
+            signature_key_owner(&headers).as_ref().map(Url::as_str)
+        );
+    }
+
+    #[track_caller]
+    fn check_as_listing(object: serde_json::Value, expected_result: bool) {
+        let object: ObjectRef = serde_json::from_value(object).unwrap();
+
+        assert_eq!(expected_result, object.as_listing().is_some());
+    }
+
+    #[test]
+    fn embedded_listing() {
+        check_as_listing(
+            serde_json::json!({
+                "type": "Note",
+                "id": "https://some.hut/listings/1",
+                "attributedTo": "https://some.hut/users/seller",
+                "name": "Bike",
+                "content": "Barely used",
+                "price": { "amount": 10000, "currency": "EUR" },
+                "condition": "LikeNew",
+                "status": "Active"
+            }),
+            true,
+        );
+        check_as_listing(
+            serde_json::json!({
+                "type": "Note",
+                "id": "https://some.hut/notes/1",
+                "attributedTo": "https://some.hut/users/seller",
+                "content": "Hello"
+            }),
+            false,
+        );
+        check_as_listing(serde_json::json!("https://some.hut/listings/1"), false);
+    }
+
+    #[test]
+    fn key_owner() {
+        check_signature_key_owner(
+            r#"keyId="https://some.hut/users/seller#main-key",algorithm="rsa-sha256",headers="(request-target) host date",signature="abc=""#,
+            Some("https://some.hut/users/seller"),
+        );
+        check_signature_key_owner(
+            r#"algorithm="hs2019", keyId="https://some.hut/actor""#,
+            Some("https://some.hut/actor"),
+        );
+        check_signature_key_owner(r#"algorithm="hs2019",signature="abc=""#, None);
+        check_signature_key_owner(r#"keyId="not a url""#, None);
+    }
+
+    #[test]
+    fn invalid_signature() {
+        check_rejection(
+            FederationError::ActivitySignatureInvalid,
+            Some(StatusCode::UNAUTHORIZED),
+        );
+        check_rejection(
+            FederationError::ActivityBodyDigestInvalid,
+            Some(StatusCode::UNAUTHORIZED),
+        );
+    }
+
+    #[test]
+    fn malformed_activity() {
+        check_rejection(
+            FederationError::UrlVerificationError("Domains do not match"),
+            Some(StatusCode::BAD_REQUEST),
+        );
+    }
+
+    #[test]
+    fn server_error() {
+        check_rejection(FederationError::Other("database".to_owned()), None);
+        assert_eq!(
+            None,
+            rejection_status(&AppError::from(anyhow::anyhow!("database")))
+        );
+    }
+
+    #[track_caller]
+    fn check_is_public(activity: serde_json::Value, expected_result: bool) {
+        assert_eq!(expected_result, is_public(&activity));
+    }
+
+    #[test]
+    fn public_activity() {
+        check_is_public(
+            serde_json::json!({ "to": "https://www.w3.org/ns/activitystreams#Public" }),
+            true,
+        );
+        check_is_public(
+            serde_json::json!({
+                "to": ["https://some.hut/users/alice/followers"],
+                "cc": ["as:Public"]
+            }),
+            true,
+        );
+    }
+
+    #[test]
+    fn private_activity() {
+        check_is_public(serde_json::json!({ "type": "Follow" }), false);
+        check_is_public(
+            serde_json::json!({ "to": ["https://some.hut/users/alice"] }),
+            false,
+        );
+    }
+
+    #[track_caller]
+    fn check_object_ref(input: serde_json::Value, expected_result: &str) {
+        let object: ObjectRef = serde_json::from_value(input).unwrap();
+
+        assert_eq!(expected_result, object.id().as_str());
+    }
+
+    #[test]
+    fn object_ref() {
+        check_object_ref(
+            serde_json::json!("https://some.hut/notes/1"),
+            "https://some.hut/notes/1",
+        );
+        check_object_ref(
+            serde_json::json!({ "id": "https://some.hut/notes/1", "type": "Tombstone" }),
+            "https://some.hut/notes/1",
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use activitypub_federation::{
+    config::Data,
+    fetch::object_id::ObjectId,
+    kinds::activity::RejectType,
+    protocol::verification::{verify_domains_match, verify_urls_match},
+    traits::{Activity, Actor},
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::server::{
+    AppError,
+    activities::{self, accept::follow_pair, follow::Follow},
+    entities::user::User,
+    state::AppState,
+    utilities,
+};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Reject {
+    pub(crate) actor: ObjectId<User>,
+    pub(crate) object: Follow,
+    #[serde(rename = "type")]
+    kind: RejectType,
+    id: Url,
+}
+
+impl Reject {
+    pub fn new(actor: ObjectId<User>, object: Follow, id: Url) -> Self {
+        Self {
+            actor,
+            object,
+            kind: Default::default(),
+            id,
+        }
+    }
+}
+
+/// Turn down a follow of `followee` by `follower`, or remove `follower` if it was accepted.
+///
+/// Remote followers are sent a `Reject`.
+pub async fn reject(
+    follower: &User,
+    followee: &User,
+    data: &Data<AppState>,
+) -> Result<Option<sellershut_core::user::Follow>, AppError> {
+    let Some(follow) = data
+        .user
+        .reject_follow(follower.data().id, followee.data().id)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    if !follower.data().is_local {
+        let activity = Reject::new(
+            followee.data().ap_id.inner().into(),
+            Follow::new(
+                follower.data().ap_id.inner().into(),
+                followee.data().ap_id.inner().into(),
+                follow.activity_id.inner(),
+            ),
+            utilities::activity_url(data.port, data.domain())?,
+        );
+        activities::send(
+            activity,
+            followee,
+            vec![follower.shared_inbox_or_inbox()],
+            data,
+        )
+        .await?;
+    }
+
+    Ok(Some(follow))
+}
+
+#[async_trait::async_trait]
+impl Activity for Reject {
+    type DataType = AppState;
+    type Error = AppError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn verify(&self, _data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        verify_domains_match(&self.id, self.actor.inner())?;
+        // Only the account that was followed may answer the follow request.
+        verify_urls_match(self.actor.inner(), self.object.object.inner())?;
+        Ok(())
+    }
+
+    async fn receive(self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        let Some((follower, followee)) = follow_pair(&self.object, data).await? else {
+            tracing::debug!(actor = %self.actor, "reject for an unknown follow");
+            return Ok(());
+        };
+
+        data.user.reject_follow(follower.id, followee.id).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,112 @@
+use activitypub_federation::{
+    config::Data,
+    fetch::object_id::ObjectId,
+    kinds::activity::UndoType,
+    protocol::verification::{verify_domains_match, verify_urls_match},
+    traits::{Activity, Actor},
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::server::{
+    AppError,
+    activities::{self, follow::Follow},
+    entities::user::User,
+    state::AppState,
+    utilities,
+};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Undo {
+    pub(crate) actor: ObjectId<User>,
+    pub(crate) object: Follow,
+    #[serde(rename = "type")]
+    kind: UndoType,
+    id: Url,
+}
+
+impl Undo {
+    pub fn new(actor: ObjectId<User>, object: Follow, id: Url) -> Self {
+        Self {
+            actor,
+            object,
+            kind: Default::default(),
+            id,
+        }
+    }
+}
+
+/// Stop `follower` from following `followee`.
+///
+/// Remote followees are sent an `Undo` of the original `Follow`.
+pub async fn unfollow(
+    follower: &User,
+    followee: &User,
+    data: &Data<AppState>,
+) -> Result<Option<sellershut_core::user::Follow>, AppError> {
+    let Some(follow) = data
+        .user
+        .unfollow(follower.data().id, followee.data().id)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    if !followee.data().is_local {
+        let activity = Undo::new(
+            follower.data().ap_id.inner().into(),
+            Follow::new(
+                follower.data().ap_id.inner().into(),
+                followee.data().ap_id.inner().into(),
+                follow.activity_id.inner(),
+            ),
+            utilities::activity_url(data.port, data.domain())?,
+        );
+        activities::send(
+            activity,
+            follower,
+            vec![followee.shared_inbox_or_inbox()],
+            data,
+        )
+        .await?;
+    }
+
+    Ok(Some(follow))
+}
+
+#[async_trait::async_trait]
+impl Activity for Undo {
+    type DataType = AppState;
+    type Error = AppError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn verify(&self, _data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        verify_domains_match(&self.id, self.actor.inner())?;
+        // Only the account that sent the follow may take it back.
+        verify_urls_match(self.actor.inner(), self.object.actor.inner())?;
+        Ok(())
+    }
+
+    async fn receive(self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        let follower = data.user.get_user_by_id(self.object.actor.inner()).await?;
+        let followee = match data.user.get_user_by_id(self.object.object.inner()).await? {
+            Some(user) if user.is_local => Some(user),
+            _ => None,
+        };
+        let (Some(follower), Some(followee)) = (follower, followee) else {
+            tracing::debug!(actor = %self.actor, "undo for an unknown follow");
+            return Ok(());
+        };
+
+        data.user.unfollow(follower.id, followee.id).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,169 @@
+use activitypub_federation::{
+    config::Data,
+    fetch::object_id::ObjectId,
+    http_signatures::generate_actor_keypair,
+    kinds::{activity::UpdateType, public},
+    protocol::verification::verify_domains_match,
+    traits::{Activity, Object},
+};
+use sellershut_core::{RedactedSecret, user::RetiredKey};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::server::{
+    AppError,
+    activities::{self, ObjectRef, audience::Audience},
+    entities::{
+        listing::Listing,
+        user::{Person, User},
+    },
+    state::AppState,
+    utilities,
+};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Update {
+    pub(crate) actor: ObjectId<User>,
+    pub(crate) object: ObjectRef,
+    #[serde(rename = "type")]
+    kind: UpdateType,
+    id: Url,
+    #[serde(flatten)]
+    pub(crate) audience: Audience,
+}
+
+impl Update {
+    pub fn new(actor: ObjectId<User>, object: ObjectRef, id: Url, audience: Audience) -> Self {
+        Self {
+            actor,
+            object,
+            kind: Default::default(),
+            id,
+            audience,
+        }
+    }
+}
+
+/// Tell the servers of `user`'s followers that their profile changed.
+pub async fn update_actor(user: &User, data: &Data<AppState>) -> Result<(), AppError> {
+    let person = user.clone().into_json(data).await?;
+
+    let activity = Update::new(
+        user.data().ap_id.inner().into(),
+        ObjectRef::embed(&person)?,
+        utilities::activity_url(data.port, data.domain())?,
+        Audience {
+            to: vec![public()],
+            cc: vec![user.collection_url("followers")?],
+        },
+    );
+    let inboxes = data.user.follower_inboxes(user.data().id).await?;
+
+    activities::send(activity, user, inboxes, data).await
+}
+
+/// Send the changes to a public listing to the servers of its owner's followers.
+pub async fn update_listing(
+    owner: &User,
+    listing: Listing,
+    data: &Data<AppState>,
+) -> Result<(), AppError> {
+    let object = listing.into_json(data).await?;
+
+    let activity = Update::new(
+        owner.data().ap_id.inner().into(),
+        ObjectRef::embed(&object)?,
+        utilities::activity_url(data.port, data.domain())?,
+        Audience {
+            to: vec![public()],
+            cc: vec![owner.collection_url("followers")?],
+        },
+    );
+    let inboxes = data.user.follower_inboxes(owner.data().id).await?;
+
+    activities::send(activity, owner, inboxes, data).await
+}
+
+/// Give a local `user` a new keypair and send their followers' servers the new `publicKey`.
+///
+/// The old public key is still published with the user during the grace period, under the id
+/// signatures made with it name.
+/// Queued deliveries are signed when they are sent, so they go out with the new key.
+pub async fn rotate_key(
+    user: &User,
+    data: &Data<AppState>,
+) -> Result<(User, RetiredKey), AppError> {
+    let keypair = generate_actor_keypair()?;
+    let (user, retired) = data
+        .user
+        .rotate_key(
+            user.data().id,
+            &keypair.public_key,
+            &RedactedSecret::from(keypair.private_key),
+            data.key_grace_period,
+        )
+        .await?;
+    let user = User::from(user);
+
+    update_actor(&user, data).await?;
+
+    Ok((user, retired))
+}
+
+#[async_trait::async_trait]
+impl Activity for Update {
+    type DataType = AppState;
+    type Error = AppError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn verify(&self, _data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        verify_domains_match(&self.id, self.actor.inner())?;
+        verify_domains_match(self.object.id(), self.actor.inner())?;
+        Ok(())
+    }
+
+    async fn receive(self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        // An actor updating its own profile.
+        if let ObjectRef::Object { id, rest } = &self.object
+            && id == self.actor.inner()
+        {
+            let mut json = rest.clone();
+            json.insert("id".to_owned(), id.as_str().into());
+            let person: Person = serde_json::from_value(json.into())?;
+
+            User::verify(&person, self.actor.inner(), data).await?;
+            User::from_json(person, data).await?;
+            return Ok(());
+        }
+
+        if let Some(listing) = self.object.as_listing() {
+            return Listing::receive(listing, self.actor.inner(), data).await;
+        }
+
+        let recipients = self.audience.local_recipients(data).await?;
+
+        if recipients.is_empty() {
+            tracing::debug!(id = %self.id, "update is not addressed to any local user");
+            return Ok(());
+        }
+
+        for recipient in recipients {
+            tracing::debug!(
+                actor = %self.actor,
+                object = %self.object.id(),
+                recipient = %recipient.username,
+                "ignoring update for unsupported object"
+            );
+        }
+
+        Ok(())
+    }
+}
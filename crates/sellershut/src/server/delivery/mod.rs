@@ -0,0 +1,211 @@
+pub mod probe;
+pub mod refresh;
+mod signature;
+
+use std::{collections::HashMap, sync::Arc, time::SystemTime};
+
+use activitypub_federation::{
+    FEDERATION_CONTENT_TYPE,
+    config::{Data, FederationConfig},
+};
+use reqwest::{StatusCode, header::CONTENT_TYPE};
+use sellershut_core::activity::Delivery;
+use time::OffsetDateTime;
+use tokio::{sync::Semaphore, task::JoinSet};
+use url::Url;
+
+use crate::{
+    config::server,
+    server::{AppError, entities::user::User, state::AppState, utilities},
+};
+
+/// How long claimed deliveries stay locked to this worker.
+const LEASE: time::Duration = time::Duration::minutes(5);
+
+/// Client for requests to other instances, which doesn't follow redirects: a redirect could lead
+/// anywhere, including to addresses on our own network.
+pub(crate) fn federation_client() -> reqwest::Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(10))
+        .redirect(reqwest::redirect::Policy::none())
+        .user_agent(concat!(
+            env!("CARGO_PKG_NAME"),
+            "/",
+            env!("CARGO_PKG_VERSION")
+        ))
+        .build()
+}
+
+/// Drain the delivery queue until the task is aborted.
+pub async fn run(federation_config: FederationConfig<AppState>, config: server::Delivery) {
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(
+        config.poll_interval.max(1),
+    ));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    let max_age = time::Duration::hours(config.max_age as i64);
+
+    let client = match federation_client() {
+        Ok(client) => client,
+        Err(error) => {
+            tracing::error!(%error, "delivery client could not be built");
+            return;
+        }
+    };
+
+    loop {
+        interval.tick().await;
+
+        let data = federation_config.to_request_data();
+        let deliveries = match data
+            .federation
+            .claim_deliveries(config.batch_size, LEASE)
+            .await
+        {
+            Ok(deliveries) => deliveries,
+            Err(error) => {
+                tracing::error!(%error, "claiming deliveries failed");
+                continue;
+            }
+        };
+
+        if deliveries.is_empty() {
+            continue;
+        }
+
+        let mut hosts = HashMap::new();
+        let mut tasks = JoinSet::new();
+
+        for delivery in deliveries {
+            let host = delivery
+                .inbox
+                .inner()
+                .host_str()
+                .unwrap_or_default()
+                .to_owned();
+            let permits =
+                Arc::clone(hosts.entry(host).or_insert_with(|| {
+                    Arc::new(Semaphore::new(config.per_host_concurrency.max(1)))
+                }));
+            let data = federation_config.to_request_data();
+            let client = client.clone();
+
+            tasks.spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                deliver(&client, delivery, &data, max_age).await;
+            });
+        }
+
+        tasks.join_all().await;
+    }
+}
+
+async fn deliver(
+    client: &reqwest::Client,
+    delivery: Delivery,
+    data: &Data<AppState>,
+    max_age: time::Duration,
+) {
+    let result = match send(client, &delivery, data).await {
+        Ok(()) => data.federation.complete_delivery(&delivery).await,
+        Err(error) => {
+            let error = error.0.to_string();
+            let next_attempt_at = sellershut_federation::next_attempt_at(
+                delivery.attempts,
+                delivery.created_at,
+                OffsetDateTime::now_utc(),
+                max_age,
+            );
+
+            match next_attempt_at {
+                Some(at) => data.federation.retry_delivery(&delivery, &error, at).await,
+                None => data.federation.abandon_delivery(&delivery, &error).await,
+            }
+        }
+    };
+
+    if let Err(error) = result {
+        tracing::error!(%error, id = %delivery.id, "updating delivery failed");
+    }
+}
+
+/// Sign and send a delivery. The actor is loaded for every attempt, so deliveries queued before
+/// a key rotation are signed with the new key.
+///
+/// Like the federation library does, inboxes we don't federate with are skipped, and a rejection
+/// by the receiving instance counts as delivered.
+async fn send(
+    client: &reqwest::Client,
+    delivery: &Delivery,
+    data: &Data<AppState>,
+) -> Result<(), AppError> {
+    let inbox = delivery.inbox.inner();
+    if let Some(reason) = inbox_rejection(&inbox, data).await? {
+        tracing::warn!(reason, %inbox, id = %delivery.id, "skipping delivery");
+        return Ok(());
+    }
+
+    let Some(actor) = data.user.get_user_by_id(&delivery.actor.inner()).await? else {
+        return Err(anyhow::anyhow!("unknown actor {}", delivery.actor.inner()).into());
+    };
+    let actor = User::from(actor);
+    let private_key = data
+        .user
+        .private_key(actor.data().id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("actor {} has no private key", actor.data().ap_id))?;
+
+    let body = serde_json::to_vec(&delivery.payload)?;
+    let headers = signature::signed_headers(
+        &inbox,
+        &body,
+        &actor.key_id(data),
+        &private_key.expose(),
+        SystemTime::now(),
+    )?;
+
+    let response = client
+        .post(inbox.clone())
+        .header(CONTENT_TYPE, FEDERATION_CONTENT_TYPE)
+        .headers(headers)
+        .body(body)
+        .send()
+        .await?;
+
+    match response.status() {
+        status if status.is_success() => Ok(()),
+        status
+            if status.is_client_error()
+                && status != StatusCode::REQUEST_TIMEOUT
+                && status != StatusCode::TOO_MANY_REQUESTS =>
+        {
+            tracing::debug!(%status, %inbox, id = %delivery.id, "delivery rejected");
+            Ok(())
+        }
+        status => Err(anyhow::anyhow!("delivery to {inbox} failed with status {status}").into()),
+    }
+}
+
+/// Why we don't deliver to `inbox`, `None` if we do.
+async fn inbox_rejection(
+    inbox: &Url,
+    data: &Data<AppState>,
+) -> Result<Option<&'static str>, AppError> {
+    // An instance on http, like one built for development, delivers to others like it.
+    if inbox.scheme() != "https"
+        && utilities::base_url(data.port, data.domain())?.scheme() == "https"
+    {
+        return Ok(Some("inbox is not https"));
+    }
+    if let Some(reason) = data
+        .domain_rejection(inbox.host_str().unwrap_or_default())
+        .await?
+    {
+        return Ok(Some(reason));
+    }
+    if data.is_valid_ip(inbox).await.is_err() {
+        return Ok(Some("inbox is on a local address"));
+    }
+
+    Ok(None)
+}
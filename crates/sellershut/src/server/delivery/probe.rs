@@ -0,0 +1,203 @@
+use activitypub_federation::config::{Data, FederationConfig};
+use reqwest::StatusCode;
+use sellershut_core::activity::Instance;
+use sellershut_federation::instance_domain;
+use serde::de::DeserializeOwned;
+use url::Url;
+
+use crate::server::{
+    AppError,
+    delivery::federation_client,
+    entities::nodeinfo::{NodeInfo, NodeInfoLinks},
+    state::AppState,
+};
+
+/// Instances probed per run.
+const BATCH_SIZE: i64 = 20;
+
+/// Periodically probe instances whose suspension ran out or whose software is unknown.
+///
+/// An instance is probed by fetching its NodeInfo, which also tells us what software it runs.
+pub async fn run(federation_config: FederationConfig<AppState>) {
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_mins(15));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    let client = match federation_client() {
+        Ok(client) => client,
+        Err(error) => {
+            tracing::error!(%error, "instance probe client could not be built");
+            return;
+        }
+    };
+
+    loop {
+        interval.tick().await;
+
+        let data = federation_config.to_request_data();
+        let instances = match data.federation.instances_to_probe(BATCH_SIZE).await {
+            Ok(instances) => instances,
+            Err(error) => {
+                tracing::error!(%error, "claiming instances to probe failed");
+                continue;
+            }
+        };
+
+        for instance in instances {
+            if let Err(error) = probe(&client, &instance, &data).await {
+                tracing::error!(error = %error.0, domain = instance.domain, "recording probe failed");
+            }
+        }
+    }
+}
+
+async fn probe(
+    client: &reqwest::Client,
+    instance: &Instance,
+    data: &Data<AppState>,
+) -> Result<(), AppError> {
+    let base = Url::parse(&format!("https://{}/", instance.domain))?;
+    if let Some(reason) = data
+        .domain_rejection(base.host_str().unwrap_or_default())
+        .await?
+    {
+        tracing::debug!(
+            reason,
+            domain = instance.domain,
+            "not probing rejected instance"
+        );
+        return Ok(());
+    }
+
+    match fetch_nodeinfo(client, &base, &instance.domain).await {
+        Ok(nodeinfo) => {
+            data.federation
+                .record_instance_success(&instance.domain)
+                .await?;
+            data.federation
+                .set_instance_software(
+                    &instance.domain,
+                    &nodeinfo.software.name,
+                    &nodeinfo.software.version,
+                )
+                .await?;
+        }
+        Err(ProbeError::NoNodeInfo(error)) => {
+            tracing::debug!(%error, domain = instance.domain, "instance has no usable nodeinfo");
+            data.federation
+                .record_instance_success(&instance.domain)
+                .await?;
+        }
+        Err(ProbeError::Unreachable(error)) => {
+            tracing::debug!(%error, domain = instance.domain, "instance probe failed");
+            data.federation
+                .record_instance_failure(&instance.domain)
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Why a probe found no NodeInfo.
+enum ProbeError {
+    /// The instance didn't answer, or answered that it can't right now.
+    Unreachable(anyhow::Error),
+    /// The instance answered, just not with a NodeInfo we can use.
+    NoNodeInfo(anyhow::Error),
+}
+
+async fn fetch_nodeinfo(
+    client: &reqwest::Client,
+    base: &Url,
+    domain: &str,
+) -> Result<NodeInfo, ProbeError> {
+    let links: NodeInfoLinks = get_json(client, base.join("/.well-known/nodeinfo")?).await?;
+
+    let link = links
+        .newest()
+        .ok_or_else(|| ProbeError::NoNodeInfo(anyhow::anyhow!("no nodeinfo link")))?;
+    // Only ever fetch from the instance itself, never from wherever it points us.
+    if !is_same_instance(&link.href, domain) {
+        return Err(ProbeError::NoNodeInfo(anyhow::anyhow!(
+            "nodeinfo link {} is not on the instance",
+            link.href
+        )));
+    }
+
+    get_json(client, link.href.clone()).await
+}
+
+async fn get_json<T: DeserializeOwned>(
+    client: &reqwest::Client,
+    url: Url,
+) -> Result<T, ProbeError> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|error| ProbeError::Unreachable(error.into()))?;
+
+    let status = response.status();
+    if is_unreachable(status) {
+        return Err(ProbeError::Unreachable(anyhow::anyhow!("status {status}")));
+    }
+    if !status.is_success() {
+        return Err(ProbeError::NoNodeInfo(anyhow::anyhow!("status {status}")));
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|error| ProbeError::NoNodeInfo(error.into()))
+}
+
+/// Whether a response means the instance is down, like deliveries count them: server errors,
+/// timeouts and rate limits. Other client errors come from an instance that is up.
+fn is_unreachable(status: StatusCode) -> bool {
+    status.is_server_error()
+        || status == StatusCode::REQUEST_TIMEOUT
+        || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+fn is_same_instance(url: &Url, domain: &str) -> bool {
+    url.scheme() == "https" && instance_domain(url).as_deref() == Some(domain)
+}
+
+impl From<url::ParseError> for ProbeError {
+    fn from(value: url::ParseError) -> Self {
+        Self::NoNodeInfo(value.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn check_same_instance(url: &str, domain: &str, expected_result: bool) {
+        let url = Url::parse(url).unwrap();
+
+        assert_eq!(expected_result, is_same_instance(&url, domain));
+    }
+
+    #[test]
+    fn unreachable_statuses() {
+        assert!(is_unreachable(StatusCode::BAD_GATEWAY));
+        assert!(is_unreachable(StatusCode::REQUEST_TIMEOUT));
+        assert!(is_unreachable(StatusCode::TOO_MANY_REQUESTS));
+        assert!(!is_unreachable(StatusCode::NOT_FOUND));
+        assert!(!is_unreachable(StatusCode::FORBIDDEN));
+        assert!(!is_unreachable(StatusCode::OK));
+    }
+
+    #[test]
+    fn nodeinfo_stays_on_instance() {
+        check_same_instance("https://some.hut/nodeinfo/2.1", "some.hut", true);
+        check_same_instance("https://some.hut:8443/nodeinfo/2.1", "some.hut:8443", true);
+        check_same_instance("http://some.hut/nodeinfo/2.1", "some.hut", false);
+        check_same_instance("https://other.hut/nodeinfo/2.1", "some.hut", false);
+        check_same_instance("https://some.hut.evil/nodeinfo/2.1", "some.hut", false);
+        check_same_instance("https://127.0.0.1/nodeinfo/2.1", "some.hut", false);
+        check_same_instance("https://some.hut:8443/nodeinfo/2.1", "some.hut", false);
+    }
+}
@@ -0,0 +1,54 @@
+use activitypub_federation::{config::FederationConfig, fetch::object_id::ObjectId};
+use time::OffsetDateTime;
+
+use crate::{
+    config::server::ActorRefresh,
+    server::{entities::user::User, state::AppState},
+};
+
+/// Periodically fetch the remote actors that went longest without a refresh.
+///
+/// Actors are refreshed on use as well, this keeps the ones that are rarely used, like
+/// followers, from drifting too far from their servers.
+pub async fn run(federation_config: FederationConfig<AppState>, config: ActorRefresh) {
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_mins(
+        config.sweep_interval.max(1),
+    ));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+
+        let data = federation_config.to_request_data();
+        let refreshed_before = OffsetDateTime::now_utc() - data.actor_stale_after;
+        let actors = match data
+            .user
+            .stale_remote_users(refreshed_before, config.batch_size)
+            .await
+        {
+            Ok(actors) => actors,
+            Err(error) => {
+                tracing::error!(%error, "listing stale actors failed");
+                continue;
+            }
+        };
+
+        for actor in actors {
+            if let Err(error) = ObjectId::<User>::from(actor.clone())
+                .dereference_forced(&data)
+                .await
+            {
+                tracing::debug!(error = %error.0, %actor, "actor refresh failed");
+
+                // Try again once it is stale again rather than on every sweep.
+                if let Err(error) = data
+                    .user
+                    .set_last_refreshed_at(&actor, OffsetDateTime::now_utc())
+                    .await
+                {
+                    tracing::error!(%error, %actor, "recording actor refresh failed");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use std::time::SystemTime;
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use sellershut_federation::instance_domain;
+use sellershut_utilities::crypto::sign_rsa_sha256;
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use crate::server::AppError;
+
+/// Headers a delivery's signature covers, the ones Mastodon requires.
+const SIGNED_HEADERS: &str = "(request-target) host date digest";
+
+/// The headers of a delivery of `body` to `inbox`, signed as the key `key_id`.
+///
+/// The key id is ours to choose, unlike with the federation library, which always names
+/// `{actor}#main-key`, so signatures keep naming the key they were made with after a rotation.
+pub(super) fn signed_headers(
+    inbox: &Url,
+    body: &[u8],
+    key_id: &str,
+    private_key_pem: &str,
+    now: SystemTime,
+) -> Result<HeaderMap, AppError> {
+    let host =
+        instance_domain(inbox).ok_or_else(|| anyhow::anyhow!("inbox {inbox} has no host"))?;
+    let date = httpdate::fmt_http_date(now);
+    let digest = format!("SHA-256={}", STANDARD.encode(Sha256::digest(body)));
+
+    let signature = sign_rsa_sha256(
+        private_key_pem,
+        signing_string(inbox, &host, &date, &digest).as_bytes(),
+    )?;
+
+    let mut headers = HeaderMap::new();
+    for (name, value) in [
+        ("host", host),
+        ("date", date),
+        ("digest", digest),
+        ("signature", signature_header(key_id, &signature)),
+    ] {
+        headers.insert(HeaderName::from_static(name), HeaderValue::try_from(value)?);
+    }
+
+    Ok(headers)
+}
+
+fn signing_string(inbox: &Url, host: &str, date: &str, digest: &str) -> String {
+    let target = match inbox.query() {
+        Some(query) => format!("{}?{query}", inbox.path()),
+        None => inbox.path().to_owned(),
+    };
+
+    format!("(request-target): post {target}\nhost: {host}\ndate: {date}\ndigest: {digest}")
+}
+
+fn signature_header(key_id: &str, signature: &[u8]) -> String {
+    format!(
+        r#"keyId="{key_id}",algorithm="rsa-sha256",headers="{SIGNED_HEADERS}",signature="{}""#,
+        STANDARD.encode(signature)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signs_request_target_and_headers() {
+        let inbox = Url::parse("https://other.hut:8443/users/bob/inbox?page=1").unwrap();
+
+        assert_eq!(
+            "(request-target): post /users/bob/inbox?page=1\nhost: other.hut:8443\ndate: Sun, 18 Oct 2026 12:00:00 GMT\ndigest: SHA-256=abc",
+            signing_string(
+                &inbox,
+                "other.hut:8443",
+                "Sun, 18 Oct 2026 12:00:00 GMT",
+                "SHA-256=abc"
+            )
+        );
+    }
+
+    #[test]
+    fn names_key() {
+        assert_eq!(
+            r#"keyId="https://some.hut/users/alice#key-1",algorithm="rsa-sha256",headers="(request-target) host date digest",signature="AQI=""#,
+            signature_header("https://some.hut/users/alice#key-1", &[1, 2])
+        );
+    }
+
+    #[test]
+    fn invalid_private_key() {
+        let inbox = Url::parse("https://other.hut/inbox").unwrap();
+
+        assert!(
+            signed_headers(
+                &inbox,
+                b"{}",
+                "https://some.hut/users/alice#key-1",
+                "not a key",
+                SystemTime::now()
+            )
+            .is_err()
+        );
+    }
+}
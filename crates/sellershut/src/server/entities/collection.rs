@@ -0,0 +1,151 @@
+use std::num::NonZeroU32;
+
+use activitypub_federation::kinds::collection::{OrderedCollectionPageType, OrderedCollectionType};
+use serde::{Deserialize, Serialize};
+use url::Url;
+use utoipa::{IntoParams, ToSchema};
+
+/// Number of items on a collection page.
+pub const PAGE_SIZE: u32 = 20;
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct PageQuery {
+    /// Page to return, starting at 1. The collection itself is returned without it.
+    #[param(value_type = Option<u32>, minimum = 1)]
+    pub page: Option<NonZeroU32>,
+}
+
+impl PageQuery {
+    /// Offset of the first item on `page`.
+    pub fn offset(page: NonZeroU32) -> i64 {
+        i64::from(page.get() - 1) * i64::from(PAGE_SIZE)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderedCollection {
+    #[serde(rename = "type")]
+    #[schema(value_type = String)]
+    kind: OrderedCollectionType,
+    id: Url,
+    total_items: i64,
+    /// Left out when the items are private.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    first: Option<Url>,
+}
+
+impl OrderedCollection {
+    pub fn new(id: Url, total_items: i64, public: bool) -> Self {
+        let first = public.then(|| page_url(&id, NonZeroU32::MIN));
+
+        Self {
+            kind: Default::default(),
+            id,
+            total_items,
+            first,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderedCollectionPage<T> {
+    #[serde(rename = "type")]
+    kind: OrderedCollectionPageType,
+    id: Url,
+    part_of: Url,
+    total_items: i64,
+    ordered_items: Vec<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next: Option<Url>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prev: Option<Url>,
+}
+
+impl<T> OrderedCollectionPage<T> {
+    pub fn new(collection: Url, page: NonZeroU32, total_items: i64, ordered_items: Vec<T>) -> Self {
+        let seen = PageQuery::offset(page) + ordered_items.len() as i64;
+        let next = (seen < total_items)
+            .then(|| page.checked_add(1))
+            .flatten()
+            .map(|next| page_url(&collection, next));
+        let prev = NonZeroU32::new(page.get() - 1).map(|prev| page_url(&collection, prev));
+
+        Self {
+            kind: Default::default(),
+            id: page_url(&collection, page),
+            part_of: collection,
+            total_items,
+            ordered_items,
+            next,
+            prev,
+        }
+    }
+}
+
+fn page_url(collection: &Url, page: NonZeroU32) -> Url {
+    let mut url = collection.clone();
+    url.query_pairs_mut()
+        .clear()
+        .append_pair("page", &page.to_string());
+    url
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn check_page(page: u32, total_items: i64, items: usize, expected_result: (bool, bool)) {
+        let collection = Url::parse("https://some.hut/users/alice/followers").unwrap();
+        let page = OrderedCollectionPage::new(
+            collection,
+            NonZeroU32::new(page).unwrap(),
+            total_items,
+            vec![(); items],
+        );
+
+        assert_eq!(expected_result, (page.prev.is_some(), page.next.is_some()));
+    }
+
+    #[test]
+    fn single_page() {
+        check_page(1, 3, 3, (false, false));
+    }
+
+    #[test]
+    fn first_of_many() {
+        check_page(1, 45, 20, (false, true));
+    }
+
+    #[test]
+    fn middle_page() {
+        check_page(2, 45, 20, (true, true));
+    }
+
+    #[test]
+    fn last_page() {
+        check_page(3, 45, 5, (true, false));
+    }
+
+    #[test]
+    fn past_the_end() {
+        check_page(9, 45, 0, (true, false));
+    }
+
+    #[test]
+    fn page_urls() {
+        let collection = Url::parse("https://some.hut/users/alice/followers").unwrap();
+        let page = OrderedCollectionPage::new(collection, NonZeroU32::MIN, 45, vec![(); 20]);
+
+        assert_eq!(
+            "https://some.hut/users/alice/followers?page=1",
+            page.id.as_str()
+        );
+        assert_eq!(
+            Some("https://some.hut/users/alice/followers?page=2"),
+            page.next.as_ref().map(Url::as_str)
+        );
+    }
+}
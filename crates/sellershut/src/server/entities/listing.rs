@@ -0,0 +1,327 @@
+use activitypub_federation::{
+    config::Data,
+    fetch::object_id::ObjectId,
+    kinds::{
+        object::{ImageType, NoteType},
+        public,
+    },
+    protocol::{
+        helpers::deserialize_one_or_many,
+        verification::{verify_domains_match, verify_urls_match},
+    },
+    traits::Object,
+};
+use sellershut_core::{
+    Money,
+    listing::{ListingCondition, ListingStatus},
+    media::MediaStatus,
+};
+use sellershut_listings::{CreateListing, error::ListingError};
+use sellershut_media::CreateRemoteMedia;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use url::Url;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::server::{AppError, entities::user::User, state::AppState, utilities};
+
+#[derive(Debug, Clone)]
+pub struct Listing {
+    data: sellershut_core::listing::Listing,
+    id: ObjectId<Listing>,
+}
+
+/// A listing as it is sent to other instances.
+///
+/// It is a `Note`, so software that knows nothing about listings still shows its title and
+/// description. The price, condition and status are extension properties.
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ListingObject {
+    #[serde(rename = "type")]
+    #[schema(value_type = String)]
+    kind: NoteType,
+    #[schema(value_type = String)]
+    id: ObjectId<Listing>,
+    #[schema(value_type = String)]
+    attributed_to: ObjectId<User>,
+    /// The title
+    name: String,
+    /// The description
+    #[serde(default)]
+    content: String,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    attachment: Vec<ListingAttachment>,
+    price: Money,
+    condition: ListingCondition,
+    status: ListingStatus,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    #[schema(value_type = Vec<String>)]
+    to: Vec<Url>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    #[schema(value_type = Vec<String>)]
+    cc: Vec<Url>,
+    #[serde(
+        default,
+        with = "time::serde::rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schema(value_type = Option<String>, format = DateTime)]
+    published: Option<OffsetDateTime>,
+    #[serde(
+        default,
+        with = "time::serde::rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schema(value_type = Option<String>, format = DateTime)]
+    updated: Option<OffsetDateTime>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ListingAttachment {
+    #[serde(rename = "type")]
+    #[schema(value_type = String)]
+    kind: ImageType,
+    url: Url,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    media_type: Option<String>,
+    /// Alt text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    width: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    height: Option<i32>,
+    /// Placeholder to show while the image loads, as Mastodon does
+    #[serde(skip_serializing_if = "Option::is_none")]
+    blurhash: Option<String>,
+}
+
+impl From<ListingAttachment> for CreateRemoteMedia {
+    fn from(value: ListingAttachment) -> Self {
+        Self {
+            url: value.url,
+            content_type: value.media_type,
+            description: value.name,
+            width: value.width,
+            height: value.height,
+            blurhash: value.blurhash,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Object for Listing {
+    type DataType = AppState;
+    type Kind = ListingObject;
+    type Error = AppError;
+
+    fn id(&self) -> &Url {
+        self.id.inner()
+    }
+
+    async fn read_from_id(
+        object_id: Url,
+        data: &Data<Self::DataType>,
+    ) -> Result<Option<Self>, Self::Error> {
+        let listing = data.listing.get_listing_by_ap_id(&object_id).await?;
+        Ok(listing.map(Listing::from))
+    }
+
+    async fn into_json(self, data: &Data<Self::DataType>) -> Result<Self::Kind, Self::Error> {
+        let owner = data
+            .user
+            .get_user_by_uuid(self.data.owner_id)
+            .await?
+            .map(User::from)
+            .ok_or_else(|| anyhow::anyhow!("listing owner not found"))?;
+        let attachment = data
+            .media
+            .listing_media(self.data.id)
+            .await?
+            .into_iter()
+            .filter(|media| media.status == MediaStatus::Ready)
+            .map(|media| {
+                Ok(ListingAttachment {
+                    kind: Default::default(),
+                    url: match media.remote_url {
+                        Some(url) => url.inner(),
+                        None => utilities::media_url(data.port, data.domain(), media.id)?,
+                    },
+                    media_type: Some(media.content_type),
+                    name: media.description,
+                    width: media.width,
+                    height: media.height,
+                    blurhash: media.blurhash,
+                })
+            })
+            .collect::<Result<_, url::ParseError>>()?;
+
+        Ok(ListingObject {
+            kind: Default::default(),
+            id: self.id,
+            attributed_to: owner.data().ap_id.inner().into(),
+            name: self.data.title,
+            content: self.data.description,
+            attachment,
+            price: self.data.price,
+            condition: self.data.condition,
+            status: self.data.status,
+            to: vec![public()],
+            cc: vec![owner.collection_url("followers")?],
+            published: self.data.published_at,
+            updated: Some(self.data.updated_at),
+        })
+    }
+
+    async fn verify(
+        json: &Self::Kind,
+        expected_domain: &Url,
+        _data: &Data<Self::DataType>,
+    ) -> Result<(), Self::Error> {
+        verify_domains_match(json.id.inner(), expected_domain)?;
+        verify_domains_match(json.id.inner(), json.attributed_to.inner())?;
+        Ok(())
+    }
+
+    async fn from_json(json: Self::Kind, data: &Data<Self::DataType>) -> Result<Self, Self::Error> {
+        let owner = json.attributed_to.dereference(data).await?;
+
+        // Attachments are stored as links, like avatars.
+        let mut attachment = Vec::with_capacity(json.attachment.len());
+        if !data.rejects_media(json.id.inner()).await? {
+            for file in json.attachment {
+                if matches!(file.url.scheme(), "http" | "https")
+                    && !data.rejects_media(&file.url).await?
+                {
+                    attachment.push(CreateRemoteMedia::from(file));
+                }
+            }
+        }
+
+        let req = CreateListing {
+            id: Uuid::now_v7(),
+            ap_id: json.id.into(),
+            owner_id: owner.data().id,
+            title: json.name,
+            description: json.content,
+            price: json.price,
+            condition: json.condition,
+            status: json.status,
+            is_local: false,
+            published_at: json.published,
+            // Categories are this instance's own, other instances don't know them.
+            category_id: None,
+        };
+        let listing = data.listing.upsert_listing(&req).await?;
+        data.media
+            .replace_remote_media(listing.id, listing.owner_id, &attachment)
+            .await?;
+        Ok(listing.into())
+    }
+}
+
+impl Listing {
+    pub fn data(&self) -> &sellershut_core::listing::Listing {
+        &self.data
+    }
+
+    /// Store a listing `actor` sent in a `Create` or `Update`.
+    ///
+    /// Listings that are invalid, or that belong to someone else, are dropped.
+    pub async fn receive(
+        json: ListingObject,
+        actor: &Url,
+        data: &Data<AppState>,
+    ) -> Result<(), AppError> {
+        verify_urls_match(json.attributed_to.inner(), actor)?;
+        Listing::verify(&json, actor, data).await?;
+
+        let ap_id = json.id.inner().clone();
+        match Listing::from_json(json, data).await {
+            Ok(listing) => {
+                tracing::debug!(%ap_id, listing_id = %listing.data.id, "stored remote listing");
+                Ok(())
+            }
+            Err(e) => match e.0.downcast_ref::<ListingError>() {
+                Some(
+                    ListingError::Invalid(_) | ListingError::Price(_) | ListingError::OwnerMismatch,
+                ) => {
+                    tracing::debug!(%ap_id, error = %e.0, "dropping remote listing");
+                    Ok(())
+                }
+                _ => Err(e),
+            },
+        }
+    }
+}
+
+impl From<sellershut_core::listing::Listing> for Listing {
+    fn from(value: sellershut_core::listing::Listing) -> Self {
+        let id = value.ap_id.inner().into();
+        Self { data: value, id }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "requires a live db"]
+    async fn attachments_survive_federation(pool: PgPool) -> anyhow::Result<()> {
+        sqlx::raw_sql(
+            r#"
+            insert into "user" (id, ap_id, username, inbox, public_key, private_key, is_local)
+            values
+                ('00000000-0000-0000-0000-000000000001', 'http://localhost/users/alice', 'alice', 'http://localhost/users/alice/inbox', 'key', 'key', true),
+                ('00000000-0000-0000-0000-000000000002', 'http://remote.example/users/bob', 'bob', 'http://remote.example/users/bob/inbox', 'key', null, false);
+
+            insert into listing (id, ap_id, owner_id, title, price, currency, condition, status, is_local)
+            values ('00000000-0000-0000-0000-000000000003', 'http://localhost/listings/lamp', '00000000-0000-0000-0000-000000000001', 'Lamp', 2500, 'EUR', 'Good', 'Active', true);
+
+            insert into media (id, owner_id, content_type, size, storage_key, description, listing_id, position, status, width, height, blurhash)
+            values
+                (gen_random_uuid(), '00000000-0000-0000-0000-000000000001', 'image/png', 10, 'lamp-front', 'From the front', '00000000-0000-0000-0000-000000000003', 1, 'Ready', 640, 480, 'LEHV6nWB2yk8'),
+                (gen_random_uuid(), '00000000-0000-0000-0000-000000000001', 'image/jpeg', 10, 'lamp-back', null, '00000000-0000-0000-0000-000000000003', 2, 'Ready', null, null, null);
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+
+        let data = crate::test::test_data(pool).await;
+        let listing = Listing::read_from_id(Url::parse("http://localhost/listings/lamp")?, &data)
+            .await
+            .expect("read listing")
+            .expect("listing exists");
+        let mut json = serde_json::to_value(listing.into_json(&data).await.expect("into json"))?;
+        json["id"] = "http://remote.example/listings/lamp".into();
+        json["attributedTo"] = "http://remote.example/users/bob".into();
+        let attachment = json["attachment"].clone();
+
+        let remote = Listing::from_json(serde_json::from_value(json)?, &data)
+            .await
+            .expect("from json");
+        let json = serde_json::to_value(remote.into_json(&data).await.expect("into json"))?;
+
+        assert_eq!(Some(2), attachment.as_array().map(Vec::len));
+        assert_eq!(attachment, json["attachment"]);
+        Ok(())
+    }
+}
@@ -1 +1,4 @@
+pub mod collection;
+pub mod listing;
+pub mod nodeinfo;
 pub mod user;
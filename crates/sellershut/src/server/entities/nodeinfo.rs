@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// Prefix of the `rel` of every NodeInfo schema version.
+pub const NODEINFO_REL_PREFIX: &str = "http://nodeinfo.diaspora.software/ns/schema/";
+
+/// The `/.well-known/nodeinfo` document, pointing at the NodeInfo documents a server offers.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NodeInfoLinks {
+    pub links: Vec<NodeInfoLink>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NodeInfoLink {
+    pub rel: String,
+    pub href: Url,
+}
+
+impl NodeInfoLinks {
+    /// The link to the newest NodeInfo schema version on offer.
+    pub fn newest(&self) -> Option<&NodeInfoLink> {
+        self.links
+            .iter()
+            .filter(|link| link.rel.starts_with(NODEINFO_REL_PREFIX))
+            .max_by(|a, b| a.rel.cmp(&b.rel))
+    }
+}
+
+/// Rel of the NodeInfo version we serve.
+pub const NODEINFO_2_1_REL: &str = "http://nodeinfo.diaspora.software/ns/schema/2.1";
+
+/// Content type of a NodeInfo 2.1 document.
+pub const NODEINFO_2_1_CONTENT_TYPE: &str =
+    "application/json; profile=\"http://nodeinfo.diaspora.software/ns/schema/2.1#\"";
+
+/// A NodeInfo document.
+///
+/// Only `software` is required when reading other servers' documents, which vary in the
+/// version and extensions they send.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeInfo {
+    #[serde(default)]
+    pub version: String,
+    pub software: Software,
+    #[serde(default)]
+    pub protocols: Vec<String>,
+    #[serde(default)]
+    pub services: Services,
+    #[serde(default)]
+    pub open_registrations: bool,
+    #[serde(default)]
+    pub usage: Usage,
+    #[serde(default)]
+    pub metadata: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Software {
+    pub name: String,
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository: Option<Url>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub homepage: Option<Url>,
+}
+
+/// Third party sites a server can publish to or receive from.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Services {
+    pub inbound: Vec<String>,
+    pub outbound: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Usage {
+    pub users: UsageUsers,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub local_posts: Option<i64>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageUsers {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_month: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_halfyear: Option<i64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn newest_link() {
+        let links: NodeInfoLinks = serde_json::from_value(serde_json::json!({
+            "links": [
+                {
+                    "rel": "http://nodeinfo.diaspora.software/ns/schema/2.0",
+                    "href": "https://some.hut/nodeinfo/2.0"
+                },
+                {
+                    "rel": "https://www.w3.org/ns/activitystreams#Application",
+                    "href": "https://some.hut/actor"
+                },
+                {
+                    "rel": "http://nodeinfo.diaspora.software/ns/schema/2.1",
+                    "href": "https://some.hut/nodeinfo/2.1"
+                }
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(
+            Some("https://some.hut/nodeinfo/2.1"),
+            links.newest().map(|link| link.href.as_str())
+        );
+    }
+
+    #[test]
+    fn reads_minimal_document() {
+        let info: NodeInfo = serde_json::from_value(serde_json::json!({
+            "version": "2.0",
+            "software": { "name": "mastodon", "version": "4.3.0" }
+        }))
+        .unwrap();
+
+        assert_eq!("mastodon", info.software.name);
+        assert_eq!(None, info.usage.users.total);
+    }
+}
@@ -1,19 +1,25 @@
 use activitypub_federation::{
     config::Data,
     fetch::object_id::ObjectId,
-    protocol::verification::verify_domains_match,
+    protocol::{
+        helpers::deserialize_one_or_many, public_key::PublicKey as PublicKeyData,
+        verification::verify_domains_match,
+    },
     traits::{Actor, Object},
 };
+use chrono::{DateTime, Utc};
 use sellershut_core::user::ActorType;
 use sellershut_users::CreateUser;
-use serde::{Deserialize, Serialize};
+use sellershut_utilities::crypto::key_version;
+use serde::{Deserialize, Serialize, Serializer};
 use url::Url;
 use utoipa::{
     PartialSchema, ToSchema,
     openapi::{ObjectBuilder, schema::SchemaType},
 };
+use uuid::Uuid;
 
-use crate::server::{AppError, state::AppState};
+use crate::server::{AppError, state::AppState, utilities};
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct User {
@@ -32,9 +38,41 @@ pub struct Person {
     #[schema(value_type = String)]
     id: ObjectId<User>,
     inbox: Url,
-    public_key: PublicKey,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    outbox: Option<Url>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    followers: Option<Url>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    following: Option<Url>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    endpoints: Option<Endpoints>,
+    /// The current key, followed by retired keys that are still in their grace period. A single
+    /// key is sent as an object, which is what most software expects.
+    #[serde(
+        deserialize_with = "deserialize_one_or_many",
+        serialize_with = "serialize_one_or_many"
+    )]
+    public_key: Vec<PublicKey>,
     #[serde(skip_serializing_if = "Option::is_none")]
     icon: Option<UserIcon>,
+    /// Other actors this one says it also is, required on the target of a `Move`.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    #[schema(value_type = Vec<String>)]
+    also_known_as: Vec<Url>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    moved_to: Option<Url>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Endpoints {
+    /// Inbox that accepts activities addressed to any actor on the instance.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shared_inbox: Option<Url>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
@@ -72,16 +110,47 @@ impl Object for User {
         object_id: Url,
         data: &Data<Self::DataType>,
     ) -> Result<Option<Self>, Self::Error> {
-        let user = data.user.get_user_by_id(&object_id).await?.map(User::from);
-        Ok(user)
+        let user = data.user.get_user_by_id(&object_id).await?;
+        Ok(user.map(User::from))
+    }
+
+    #[doc = " Time when this object was last updated from its original instance."]
+    #[doc = ""]
+    #[doc = " A stale remote actor is fetched again, and the stored copy is used if that fails."]
+    #[doc = " Deleted ones stay deleted."]
+    fn last_refreshed_at(&self) -> Option<DateTime<Utc>> {
+        if self.data.deleted_at.is_some() {
+            return None;
+        }
+
+        let refreshed_at = self.data.last_refreshed_at;
+        DateTime::from_timestamp(refreshed_at.unix_timestamp(), refreshed_at.nanosecond())
+    }
+
+    #[doc = " Returns true if the object was deleted"]
+    fn is_deleted(&self) -> bool {
+        self.data.deleted_at.is_some()
     }
 
     #[doc = " Convert database type to Activitypub type."]
     #[doc = ""]
     #[doc = " Called when a local object gets fetched by another instance over HTTP, or when an object"]
     #[doc = " gets sent in an activity."]
-    async fn into_json(self, _data: &Data<Self::DataType>) -> Result<Self::Kind, Self::Error> {
-        Ok(Self::Kind::try_from(self)?)
+    async fn into_json(self, data: &Data<Self::DataType>) -> Result<Self::Kind, Self::Error> {
+        let public_keys = self.public_keys(data).await?;
+        let mut person = Self::Kind::try_from(self)?;
+        person.public_key = public_keys;
+
+        // The size of avatars uploaded here is known.
+        if let Some(icon) = person.icon.as_mut()
+            && let Some(id) = utilities::local_media_id(data.port, data.domain(), &icon.url)
+            && let Some(media) = data.media.get_media(id).await?
+        {
+            icon.width = media.width.and_then(|width| u16::try_from(width).ok());
+            icon.height = media.height.and_then(|height| u16::try_from(height).ok());
+        }
+
+        Ok(person)
     }
 
     #[doc = " Verifies that the received object is valid."]
@@ -106,16 +175,36 @@ impl Object for User {
     #[doc = " should write the received object to database. Note that there is no distinction between"]
     #[doc = " create and update, so an `upsert` operation should be used."]
     async fn from_json(json: Self::Kind, data: &Data<Self::DataType>) -> Result<Self, Self::Error> {
+        let public_key = json
+            .public_key
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("actor {} has no public key", json.id))?;
+
+        // An avatar is stored as a link, which is as good as storing it when clients load it.
+        let avatar = match json.icon {
+            Some(icon)
+                if !data.rejects_media(json.id.inner()).await?
+                    && !data.rejects_media(&icon.url).await? =>
+            {
+                Some(icon.url)
+            }
+            _ => None,
+        };
+
         let req = CreateUser {
             kind: json.kind,
             username: json.preferred_username,
             ap_id: json.id.into(),
             name: json.name,
             inbox: json.inbox,
-            public_key: json.public_key.0.public_key_pem,
+            shared_inbox: json.endpoints.and_then(|endpoints| endpoints.shared_inbox),
+            public_key: public_key.0.public_key_pem,
             private_key: None,
             is_local: false,
-            avatar: None,
+            avatar,
+            also_known_as: json.also_known_as,
+            moved_to: json.moved_to,
         };
         let user = data.user.upsert_user(&req, None).await?;
         Ok(user.into())
@@ -134,11 +223,25 @@ impl Actor for User {
     fn inbox(&self) -> url::Url {
         self.data.inbox.inner()
     }
+
+    fn shared_inbox(&self) -> Option<url::Url> {
+        self.data.shared_inbox.as_ref().map(|url| url.inner())
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
-pub struct PublicKey(activitypub_federation::protocol::public_key::PublicKey);
+pub struct PublicKey(PublicKeyData);
+
+fn serialize_one_or_many<S: Serializer>(
+    keys: &[PublicKey],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match keys {
+        [key] => key.serialize(serializer),
+        keys => keys.serialize(serializer),
+    }
+}
 
 impl ToSchema for PublicKey {}
 
@@ -172,6 +275,73 @@ impl PartialSchema for PublicKey {
     }
 }
 
+impl User {
+    pub fn data(&self) -> &sellershut_core::user::User {
+        &self.data
+    }
+
+    /// Whether this actor lists `ap_id` among the actors it also is.
+    pub fn is_also_known_as(&self, ap_id: &Url) -> bool {
+        self.data
+            .also_known_as
+            .iter()
+            .any(|alias| alias.inner() == *ap_id)
+    }
+
+    /// Id of the current public key, the one signatures name.
+    ///
+    /// Key ids carry the key's version, so a rotated key gets a new id and signatures made before
+    /// the rotation still name the old key. The instance actor's stays `#main-key`, which the
+    /// federation library names when it signs fetches.
+    pub fn key_id(&self, data: &Data<AppState>) -> String {
+        if self.data.id == data.system_user.data().id {
+            return format!("{}#main-key", self.id.inner());
+        }
+
+        self.versioned_key_id(key_version(&self.data.public_key))
+    }
+
+    /// Id of the key with `version`, like a retired one.
+    pub fn versioned_key_id(&self, version: Uuid) -> String {
+        format!("{}#key-{version}", self.id.inner())
+    }
+
+    /// The keys published with a local actor: the current one, then the retired ones, which
+    /// signatures made before a rotation still name.
+    async fn public_keys(&self, data: &Data<AppState>) -> Result<Vec<PublicKey>, AppError> {
+        let owner = self.id.inner().clone();
+        if !self.data.is_local {
+            return Ok(vec![PublicKey(self.public_key())]);
+        }
+
+        let current = PublicKey(PublicKeyData {
+            id: self.key_id(data),
+            owner: owner.clone(),
+            public_key_pem: self.data.public_key.clone(),
+        });
+        let retired = data
+            .user
+            .retired_keys(self.data.id)
+            .await?
+            .into_iter()
+            .map(|key| {
+                PublicKey(PublicKeyData {
+                    id: self.versioned_key_id(key.id),
+                    owner: owner.clone(),
+                    public_key_pem: key.public_key,
+                })
+            });
+
+        Ok(std::iter::once(current).chain(retired).collect())
+    }
+
+    /// Url of a collection nested under the actor, like `followers`.
+    pub fn collection_url(&self, name: &str) -> Result<Url, url::ParseError> {
+        let id = self.id.inner().as_str().trim_end_matches('/');
+        Url::parse(&format!("{id}/{name}"))
+    }
+}
+
 impl From<sellershut_core::user::User> for User {
     fn from(value: sellershut_core::user::User) -> Self {
         let id = value.ap_id.inner().into();
@@ -198,14 +368,42 @@ impl TryFrom<User> for Person {
             None
         };
 
+        // Remote actors' collections are not stored, so they are only known for local ones.
+        let (outbox, followers, following) = if value.data.is_local {
+            (
+                Some(value.collection_url("outbox")?),
+                Some(value.collection_url("followers")?),
+                Some(value.collection_url("following")?),
+            )
+        } else {
+            (None, None, None)
+        };
+
         Ok(Self {
             kind: value.data.kind,
             preferred_username,
             id: value.id.clone(),
             inbox: value.data.inbox.inner(),
-            public_key: PublicKey(value.public_key()),
+            outbox,
+            followers,
+            following,
+            endpoints: value.data.shared_inbox.as_ref().map(|url| Endpoints {
+                shared_inbox: Some(url.inner()),
+            }),
+            public_key: vec![PublicKey(value.public_key())],
             name: value.data.name,
             icon,
+            also_known_as: value
+                .data
+                .also_known_as
+                .iter()
+                .map(sellershut_core::Url::inner)
+                .collect(),
+            moved_to: value
+                .data
+                .moved_to
+                .as_ref()
+                .map(sellershut_core::Url::inner),
         })
     }
 }
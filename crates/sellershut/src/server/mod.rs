@@ -3,7 +3,10 @@ use axum::{
     response::{IntoResponse, Response},
 };
 
+pub mod activities;
+pub mod delivery;
 pub mod entities;
+pub mod processing;
 pub mod router;
 pub mod state;
 pub mod utilities;
@@ -12,6 +15,12 @@ pub mod utilities;
 #[derive(Debug)]
 pub struct AppError(anyhow::Error);
 
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
 // Tell axum how to convert `AppError` into a response.
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
@@ -0,0 +1,117 @@
+use activitypub_federation::config::{Data, FederationConfig};
+use sellershut_core::media::Media;
+use sellershut_media::{
+    MAX_PROCESSING_ATTEMPTS, ProcessedMedia, config::Processing, error::MediaError, process,
+};
+use time::OffsetDateTime;
+
+use crate::server::state::AppState;
+
+/// How long claimed files stay locked to this worker.
+const LEASE: time::Duration = time::Duration::minutes(5);
+
+/// Process uploaded files until the task is aborted.
+///
+/// Uploads are only served once their metadata is stripped, so this runs away from the
+/// request that uploaded them.
+pub async fn run(federation_config: FederationConfig<AppState>, config: Processing) {
+    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(
+        config.poll_interval.max(1),
+    ));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+
+        let data = federation_config.to_request_data();
+        let media = match data
+            .media
+            .claim_unprocessed_media(config.batch_size, LEASE)
+            .await
+        {
+            Ok(media) => media,
+            Err(error) => {
+                tracing::error!(%error, "claiming media failed");
+                continue;
+            }
+        };
+
+        for media in media {
+            process_media(media, &data).await;
+        }
+    }
+}
+
+async fn process_media(media: Media, data: &Data<AppState>) {
+    let result = match process_file(&media, data).await {
+        Ok(processed) => data.media.complete_processing(media.id, &processed).await,
+        Err(error) => {
+            let permanent = matches!(error, MediaError::Unprocessable(_))
+                || media.attempts >= MAX_PROCESSING_ATTEMPTS;
+            let error = error.to_string();
+            tracing::debug!(%error, media_id = %media.id, permanent, "processing media failed");
+
+            if permanent {
+                // The original still has its metadata, it must never be served.
+                if let Some(key) = &media.storage_key
+                    && let Err(error) = data.media_store.delete(key).await
+                {
+                    tracing::error!(%error, media_id = %media.id, "deleting unprocessed file failed");
+                }
+                data.media.fail_processing(media.id, &error).await
+            } else {
+                data.media
+                    .retry_processing(media.id, &error, retry_at(media.attempts))
+                    .await
+            }
+        }
+    };
+
+    if let Err(error) = result {
+        tracing::error!(%error, media_id = %media.id, "updating media failed");
+    }
+}
+
+async fn process_file(media: &Media, data: &Data<AppState>) -> Result<ProcessedMedia, MediaError> {
+    // Files of remote listings are never pending, they are not kept here.
+    let storage_key = media
+        .storage_key
+        .as_deref()
+        .ok_or_else(|| MediaError::Unprocessable("file is not stored here".into()))?;
+    let bytes = data
+        .media_store
+        .get(storage_key)
+        .await?
+        .ok_or_else(|| MediaError::Unprocessable("file is missing".into()))?;
+
+    let content_type = media.content_type.clone();
+    let processed = tokio::task::spawn_blocking(move || process::process(&bytes, &content_type))
+        .await
+        .map_err(|e| MediaError::Unprocessable(e.to_string()))??;
+
+    let thumbnail_key = format!("{}_thumbnail", media.id);
+    data.media_store
+        .put(
+            &thumbnail_key,
+            process::THUMBNAIL_CONTENT_TYPE,
+            &processed.thumbnail,
+        )
+        .await?;
+    data.media_store
+        .put(storage_key, processed.content_type, &processed.bytes)
+        .await?;
+
+    Ok(ProcessedMedia {
+        content_type: processed.content_type.to_owned(),
+        size: processed.bytes.len() as i64,
+        width: processed.width as i32,
+        height: processed.height as i32,
+        blurhash: processed.blurhash,
+        thumbnail_key,
+    })
+}
+
+/// Minutes double with every attempt.
+fn retry_at(attempts: i32) -> OffsetDateTime {
+    OffsetDateTime::now_utc() + time::Duration::minutes(1 << attempts.clamp(0, 10))
+}
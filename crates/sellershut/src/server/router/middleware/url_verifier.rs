@@ -1,12 +1,12 @@
-use activitypub_federation::config::UrlVerifier;
+use activitypub_federation::{config::UrlVerifier, error::Error};
 use async_trait::async_trait;
 use url::Url;
 
 use crate::server::state::AppState;
 
-/// Use this to store your federation blocklist, or a database connection needed to retrieve it.
+/// Checks every url we fetch from or receive activities from against the allowlist and the
+/// domain blocks.
 #[derive(Clone)]
-#[allow(dead_code)]
 pub struct MyUrlVerifier(AppState);
 
 impl From<AppState> for MyUrlVerifier {
@@ -17,13 +17,15 @@ impl From<AppState> for MyUrlVerifier {
 
 #[async_trait]
 impl UrlVerifier for MyUrlVerifier {
-    async fn verify(&self, url: &Url) -> Result<(), activitypub_federation::error::Error> {
-        if url.domain() == Some("malicious.com") {
-            Err(activitypub_federation::error::Error::Other(
-                "malicious domain".into(),
-            ))
-        } else {
-            Ok(())
+    async fn verify(&self, url: &Url) -> Result<(), Error> {
+        let Some(domain) = url.host_str() else {
+            return Err(Error::UrlVerificationError("url has no host"));
+        };
+
+        match self.0.domain_rejection(domain).await {
+            Ok(Some(reason)) => Err(Error::UrlVerificationError(reason)),
+            Ok(None) => Ok(()),
+            Err(error) => Err(Error::Other(error.to_string())),
         }
     }
 }
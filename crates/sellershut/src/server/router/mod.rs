@@ -18,7 +18,11 @@ use crate::{
         router::{
             middleware::url_verifier::MyUrlVerifier,
             routes::{
+                admin::{self, AdminDoc},
                 auth::{self, AuthDoc},
+                categories::{self, CategoriesDoc},
+                listings::{self, ListingsDoc},
+                media::{self, MediaDoc},
                 users::{self, UsersDoc},
             },
         },
@@ -48,24 +52,49 @@ impl Modify for SecurityAddon {
     }
 }
 
-pub async fn router(state: AppState, config: Configuration) -> anyhow::Result<Router> {
+pub async fn federation_config(
+    state: AppState,
+    config: &Configuration,
+) -> anyhow::Result<FederationConfig<AppState>> {
     let federation_config = FederationConfig::builder()
-        .domain(config.server.domain)
+        .domain(config.server.domain.clone())
         .url_verifier(Box::new(MyUrlVerifier::from(state.clone())))
         .signed_fetch_actor(&*state.system_user)
-        .app_data(state.clone())
+        .app_data(state)
         .build()
         .await?;
 
+    Ok(federation_config)
+}
+
+pub async fn router(
+    federation_config: FederationConfig<AppState>,
+    config: Configuration,
+) -> anyhow::Result<Router> {
     let mut doc = ApiDoc::openapi();
     doc.merge(AuthDoc::openapi());
     doc.merge(UsersDoc::openapi());
+    doc.merge(CategoriesDoc::openapi());
+    doc.merge(ListingsDoc::openapi());
+    doc.merge(MediaDoc::openapi());
+    doc.merge(AdminDoc::openapi());
 
     let stubs = OpenApiRouter::with_openapi(doc)
         .routes(utoipa_axum::routes!(routes::health))
         .routes(utoipa_axum::routes!(routes::webfinger))
+        .routes(utoipa_axum::routes!(routes::host_meta))
+        .routes(utoipa_axum::routes!(routes::host_meta_json))
+        .routes(utoipa_axum::routes!(routes::shared_inbox))
+        .routes(utoipa_axum::routes!(routes::domain_blocks))
+        .routes(utoipa_axum::routes!(routes::nodeinfo_links))
+        .routes(utoipa_axum::routes!(routes::nodeinfo))
+        .routes(utoipa_axum::routes!(routes::search))
         .nest("/auth", auth::router())
-        .nest("/users", users::router());
+        .nest("/users", users::router())
+        .nest("/categories", categories::router())
+        .nest("/listings", listings::router())
+        .nest("/media", media::router(config.media.max_size))
+        .nest("/admin", admin::router());
 
     let (router, api) = stubs.split_for_parts();
 
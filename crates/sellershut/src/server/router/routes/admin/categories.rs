@@ -0,0 +1,248 @@
+use std::collections::BTreeMap;
+
+use activitypub_federation::config::Data;
+use axum::{
+    Json,
+    extract::Path,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use axum_extra::{
+    TypedHeader,
+    headers::{Authorization, authorization::Bearer},
+};
+use sellershut_categories::{CreateCategory, UpdateCategory, error::CategoryError};
+use serde::Deserialize;
+use tracing::debug;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::server::{
+    AppError,
+    router::routes::{
+        admin::{ADMIN_TAG, require_admin},
+        categories::CategoryResponse,
+        present,
+    },
+    state::AppState,
+};
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NewCategory {
+    /// Left out for a top level category
+    #[schema(value_type = Option<String>)]
+    parent_id: Option<Uuid>,
+    /// Lowercase words joined by hyphens, like `road-bikes`
+    slug: String,
+    /// Order among its siblings
+    #[serde(default)]
+    position: i32,
+    /// Names by locale, one in `en` is required
+    names: BTreeMap<String, String>,
+}
+
+/// Create a category
+#[utoipa::path(
+    post,
+    path = "/categories",
+    security(
+        ("bearer_auth" = [])
+    ),
+    request_body = NewCategory,
+    responses(
+        (status = 201, description = "Created category", body = CategoryResponse,
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Not an admin"),
+        (status = 409, description = "The slug is taken"),
+        (status = 422, description = "The category is invalid"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = ADMIN_TAG,
+)]
+pub async fn create_category(
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    state: Data<AppState>,
+    Json(body): Json<NewCategory>,
+) -> Result<Response, AppError> {
+    if let Err(status) = require_admin(bearer.token(), &state).await {
+        return Ok(status.into_response());
+    }
+
+    let data = CreateCategory {
+        id: Uuid::now_v7(),
+        parent_id: body.parent_id,
+        slug: body.slug,
+        position: body.position,
+        names: body.names,
+    };
+
+    match state.category.create_category(&data).await {
+        Ok(category) => {
+            Ok((StatusCode::CREATED, Json(CategoryResponse::from(category))).into_response())
+        }
+        Err(e) => rejected(e),
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryChanges {
+    /// `null` makes it a top level category
+    #[serde(default, deserialize_with = "present")]
+    #[schema(value_type = Option<String>)]
+    parent_id: Option<Option<Uuid>>,
+    slug: Option<String>,
+    position: Option<i32>,
+    /// Replaces all of its names
+    names: Option<BTreeMap<String, String>>,
+}
+
+/// Update a category
+///
+/// Only the fields that are set are changed. Moving a category moves everything below it.
+#[utoipa::path(
+    patch,
+    path = "/categories/{id}",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Id of the category")
+    ),
+    request_body = CategoryChanges,
+    responses(
+        (status = 200, description = "Updated category", body = CategoryResponse,
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Not an admin"),
+        (status = 404, description = "No such category"),
+        (status = 409, description = "The slug is taken"),
+        (status = 422, description = "The changes are invalid"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = ADMIN_TAG,
+)]
+pub async fn update_category(
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    Path(id): Path<Uuid>,
+    state: Data<AppState>,
+    Json(body): Json<CategoryChanges>,
+) -> Result<Response, AppError> {
+    if let Err(status) = require_admin(bearer.token(), &state).await {
+        return Ok(status.into_response());
+    }
+
+    let data = UpdateCategory {
+        parent_id: body.parent_id,
+        slug: body.slug,
+        position: body.position,
+        names: body.names,
+    };
+
+    match state.category.update_category(id, &data).await {
+        Ok(Some(category)) => Ok(Json(CategoryResponse::from(category)).into_response()),
+        Ok(None) => Ok(StatusCode::NOT_FOUND.into_response()),
+        Err(e) => rejected(e),
+    }
+}
+
+/// Delete a category
+///
+/// Only categories without children can be deleted. Their listings are left without a category.
+#[utoipa::path(
+    delete,
+    path = "/categories/{id}",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Id of the category")
+    ),
+    responses(
+        (status = 204, description = "Category deleted",
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Not an admin"),
+        (status = 404, description = "No such category"),
+        (status = 409, description = "The category has children"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = ADMIN_TAG,
+)]
+pub async fn delete_category(
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    Path(id): Path<Uuid>,
+    state: Data<AppState>,
+) -> Result<Response, AppError> {
+    if let Err(status) = require_admin(bearer.token(), &state).await {
+        return Ok(status.into_response());
+    }
+
+    match state.category.delete_category(id).await {
+        Ok(Some(_)) => Ok(StatusCode::NO_CONTENT.into_response()),
+        Ok(None) => Ok(StatusCode::NOT_FOUND.into_response()),
+        Err(e) => rejected(e),
+    }
+}
+
+/// Answer mistakes of the admin with 409 or 422, anything else is a server error.
+fn rejected(e: CategoryError) -> Result<Response, AppError> {
+    match e {
+        CategoryError::Invalid(reason) => {
+            debug!(reason, "invalid category");
+            Ok((StatusCode::UNPROCESSABLE_ENTITY, reason).into_response())
+        }
+        CategoryError::SlugTaken | CategoryError::HasChildren => {
+            Ok((StatusCode::CONFLICT, e.to_string()).into_response())
+        }
+        e => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "requires a live db"]
+    async fn create_requires_session(pool: PgPool) -> anyhow::Result<()> {
+        let app = crate::test::test_app(pool).await;
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/admin/categories")
+                    .header("authorization", "Bearer not-a-session")
+                    .header("content-type", "application/json")
+                    .body(Body::from(r#"{"slug":"bikes","names":{"en":"Bikes"}}"#))?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::UNAUTHORIZED, response.status());
+        Ok(())
+    }
+}
@@ -0,0 +1,146 @@
+use activitypub_federation::config::Data;
+use axum::{
+    Json,
+    extract::Path,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use axum_extra::{
+    TypedHeader,
+    headers::{Authorization, authorization::Bearer},
+};
+use sellershut_core::activity::BlockSeverity;
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use crate::server::{
+    AppError,
+    router::routes::{
+        admin::{ADMIN_TAG, require_admin},
+        domain_blocks::DomainBlockResponse,
+    },
+    state::AppState,
+};
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockDomainRequest {
+    severity: BlockSeverity,
+    /// Shown publicly next to the block
+    public_reason: Option<String>,
+}
+
+/// Block a domain
+///
+/// Blocks the domain and its subdomains, or changes the severity and reason of an existing
+/// block. Federation with suspended domains stops right away.
+#[utoipa::path(
+    put,
+    path = "/domain-blocks/{domain}",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("domain" = String, Path, description = "Domain to block")
+    ),
+    request_body = BlockDomainRequest,
+    responses(
+        (status = 200, description = "Domain block", body = DomainBlockResponse,
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Not an admin"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = ADMIN_TAG,
+)]
+pub async fn block_domain(
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    Path(domain): Path<String>,
+    state: Data<AppState>,
+    Json(body): Json<BlockDomainRequest>,
+) -> Result<Response, AppError> {
+    if let Err(status) = require_admin(bearer.token(), &state).await {
+        return Ok(status.into_response());
+    }
+
+    let block = state
+        .federation
+        .block_domain(&domain, body.severity, body.public_reason.as_deref())
+        .await?;
+
+    Ok(Json(DomainBlockResponse::from(block)).into_response())
+}
+
+/// Unblock a domain
+#[utoipa::path(
+    delete,
+    path = "/domain-blocks/{domain}",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("domain" = String, Path, description = "Blocked domain")
+    ),
+    responses(
+        (status = 204, description = "Block removed",
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Not an admin"),
+        (status = 404, description = "Domain is not blocked"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = ADMIN_TAG,
+)]
+pub async fn unblock_domain(
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    Path(domain): Path<String>,
+    state: Data<AppState>,
+) -> Result<Response, AppError> {
+    if let Err(status) = require_admin(bearer.token(), &state).await {
+        return Ok(status.into_response());
+    }
+
+    match state.federation.unblock_domain(&domain).await? {
+        Some(_) => Ok(StatusCode::NO_CONTENT.into_response()),
+        None => Ok(StatusCode::NOT_FOUND.into_response()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "requires a live db"]
+    async fn requires_session(pool: PgPool) -> anyhow::Result<()> {
+        let app = crate::test::test_app(pool).await;
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("DELETE")
+                    .uri("/admin/domain-blocks/example.com")
+                    .header("authorization", "Bearer not-a-session")
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::UNAUTHORIZED, response.status());
+        Ok(())
+    }
+}
@@ -0,0 +1,190 @@
+use activitypub_federation::config::Data;
+use axum::{
+    Json,
+    extract::{Path, Query},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use axum_extra::{
+    TypedHeader,
+    headers::{Authorization, authorization::Bearer},
+};
+use sellershut_core::activity::Instance;
+use serde::Serialize;
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+
+use crate::server::{
+    AppError,
+    entities::collection::{PAGE_SIZE, PageQuery},
+    router::routes::admin::{ADMIN_TAG, require_admin},
+    state::AppState,
+};
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceResponse {
+    domain: String,
+    software_name: Option<String>,
+    software_version: Option<String>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    #[schema(value_type = Option<String>, format = DateTime)]
+    last_success_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    #[schema(value_type = Option<String>, format = DateTime)]
+    last_failure_at: Option<OffsetDateTime>,
+    /// When the instance started failing, `null` while it is reachable
+    #[serde(with = "time::serde::rfc3339::option")]
+    #[schema(value_type = Option<String>, format = DateTime)]
+    first_failure_at: Option<OffsetDateTime>,
+    consecutive_failures: i32,
+    /// Deliveries to the instance are skipped until then
+    #[serde(with = "time::serde::rfc3339::option")]
+    #[schema(value_type = Option<String>, format = DateTime)]
+    suspended_until: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    #[schema(value_type = Option<String>, format = DateTime)]
+    last_probed_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    created_at: OffsetDateTime,
+}
+
+impl From<Instance> for InstanceResponse {
+    fn from(value: Instance) -> Self {
+        Self {
+            domain: value.domain,
+            software_name: value.software_name,
+            software_version: value.software_version,
+            last_success_at: value.last_success_at,
+            last_failure_at: value.last_failure_at,
+            first_failure_at: value.first_failure_at,
+            consecutive_failures: value.consecutive_failures,
+            suspended_until: value.suspended_until,
+            last_probed_at: value.last_probed_at,
+            created_at: value.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct InstancesResponse {
+    total_items: i64,
+    instances: Vec<InstanceResponse>,
+}
+
+/// List known instances
+///
+/// Instances are sorted by domain, one page at a time.
+#[utoipa::path(
+    get,
+    path = "/instances",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(PageQuery),
+    responses(
+        (status = 200, description = "Known instances", body = InstancesResponse,
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Not an admin"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = ADMIN_TAG,
+)]
+pub async fn list_instances(
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    Query(query): Query<PageQuery>,
+    state: Data<AppState>,
+) -> Result<Response, AppError> {
+    if let Err(status) = require_admin(bearer.token(), &state).await {
+        return Ok(status.into_response());
+    }
+
+    let offset = query.page.map(PageQuery::offset).unwrap_or_default();
+    let total_items = state.federation.count_instances().await?;
+    let instances = state
+        .federation
+        .list_instances(i64::from(PAGE_SIZE), offset)
+        .await?;
+
+    Ok(Json(InstancesResponse {
+        total_items,
+        instances: instances.into_iter().map(InstanceResponse::from).collect(),
+    })
+    .into_response())
+}
+
+/// Get an instance
+#[utoipa::path(
+    get,
+    path = "/instances/{domain}",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("domain" = String, Path, description = "Domain of the instance, with the port if it has one")
+    ),
+    responses(
+        (status = 200, description = "Instance", body = InstanceResponse,
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Not an admin"),
+        (status = 404, description = "Instance is not known"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = ADMIN_TAG,
+)]
+pub async fn get_instance(
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    Path(domain): Path<String>,
+    state: Data<AppState>,
+) -> Result<Response, AppError> {
+    if let Err(status) = require_admin(bearer.token(), &state).await {
+        return Ok(status.into_response());
+    }
+
+    match state.federation.get_instance(&domain).await? {
+        Some(instance) => Ok(Json(InstanceResponse::from(instance)).into_response()),
+        None => Ok(StatusCode::NOT_FOUND.into_response()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "requires a live db"]
+    async fn requires_session(pool: PgPool) -> anyhow::Result<()> {
+        let app = crate::test::test_app(pool).await;
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/admin/instances")
+                    .header("authorization", "Bearer not-a-session")
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::UNAUTHORIZED, response.status());
+        Ok(())
+    }
+}
@@ -0,0 +1,55 @@
+use activitypub_federation::config::Data;
+use axum::http::StatusCode;
+use sellershut_core::user::User;
+use tracing::debug;
+use utoipa::OpenApi;
+use utoipa_axum::router::OpenApiRouter;
+
+use crate::server::{router::routes::session_user, state::AppState};
+
+pub mod categories;
+pub mod domain_blocks;
+pub mod instances;
+pub mod users;
+
+pub fn router() -> OpenApiRouter {
+    let router = OpenApiRouter::new();
+
+    router
+        .routes(utoipa_axum::routes!(instances::list_instances))
+        .routes(utoipa_axum::routes!(instances::get_instance))
+        .routes(utoipa_axum::routes!(
+            domain_blocks::block_domain,
+            domain_blocks::unblock_domain
+        ))
+        .routes(utoipa_axum::routes!(users::rotate_key))
+        .routes(utoipa_axum::routes!(categories::create_category))
+        .routes(utoipa_axum::routes!(
+            categories::update_category,
+            categories::delete_category
+        ))
+}
+
+const ADMIN_TAG: &str = "Admin";
+
+#[derive(OpenApi)]
+#[openapi(tags((name = ADMIN_TAG, description = "Instance administration")))]
+pub struct AdminDoc;
+
+/// Resolve a session to a user listed in the `admins` config.
+async fn require_admin(token: &str, state: &Data<AppState>) -> Result<User, StatusCode> {
+    let Some(user) = session_user(token, state).await else {
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+
+    if state
+        .admins
+        .iter()
+        .any(|admin| admin.eq_ignore_ascii_case(&user.username))
+    {
+        Ok(user)
+    } else {
+        debug!(user_id = %user.id, "not an admin");
+        Err(StatusCode::FORBIDDEN)
+    }
+}
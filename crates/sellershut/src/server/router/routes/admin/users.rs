@@ -0,0 +1,110 @@
+use activitypub_federation::config::Data;
+use axum::{
+    Json,
+    extract::Path,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use axum_extra::{
+    TypedHeader,
+    headers::{Authorization, authorization::Bearer},
+};
+use serde::Serialize;
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+
+use crate::server::{
+    AppError,
+    activities::update,
+    entities::user::User,
+    router::routes::admin::{ADMIN_TAG, require_admin},
+    state::AppState,
+};
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RotateKeyResponse {
+    /// Id of the replaced public key, published with the user until it expires
+    retired_key_id: String,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    expires_at: OffsetDateTime,
+}
+
+/// Rotate a user's signing key
+///
+/// Generates a new keypair and sends the user's followers an `Update` with the new public key.
+/// Rotating the instance actor's key takes effect for signed fetches after a restart.
+#[utoipa::path(
+    post,
+    path = "/users/{username}/rotate-key",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("username" = String, Path, description = "Username of a local user")
+    ),
+    responses(
+        (status = 200, description = "Key rotated", body = RotateKeyResponse,
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Not an admin"),
+        (status = 404, description = "No local user with that username"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = ADMIN_TAG,
+)]
+pub async fn rotate_key(
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    Path(username): Path<String>,
+    state: Data<AppState>,
+) -> Result<Response, AppError> {
+    if let Err(status) = require_admin(bearer.token(), &state).await {
+        return Ok(status.into_response());
+    }
+
+    let Some(user) = state.user.get_user(&username).await? else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+
+    let (user, retired) = update::rotate_key(&User::from(user), &state).await?;
+
+    Ok(Json(RotateKeyResponse {
+        retired_key_id: user.versioned_key_id(retired.id),
+        expires_at: retired.expires_at,
+    })
+    .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "requires a live db"]
+    async fn requires_session(pool: PgPool) -> anyhow::Result<()> {
+        let app = crate::test::test_app(pool).await;
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/admin/users/seller/rotate-key")
+                    .header("authorization", "Bearer not-a-session")
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::UNAUTHORIZED, response.status());
+        Ok(())
+    }
+}
@@ -60,6 +60,7 @@ pub async fn complete_onboarding(
 
     let ap_id = utilities::users_url(port, domain, &request.username)?;
     let inbox = utilities::inbox_url(port, domain, &request.username)?;
+    let shared_inbox = utilities::shared_inbox_url(port, domain)?;
     tracing::debug!(id =%ap_id, inbox=%inbox,"creating user");
 
     let keypair = generate_actor_keypair()?;
@@ -70,10 +71,13 @@ pub async fn complete_onboarding(
         username: request.username,
         name: None,
         inbox,
+        shared_inbox: Some(shared_inbox),
         avatar: None,
         public_key: keypair.public_key,
         private_key: Some(RedactedSecret::from(keypair.private_key)),
         is_local: true,
+        also_known_as: Vec::new(),
+        moved_to: None,
     };
 
     let AuthenticatedSession { token, user } = state
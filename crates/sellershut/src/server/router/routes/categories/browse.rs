@@ -0,0 +1,68 @@
+use activitypub_federation::config::Data;
+use axum::{
+    Json,
+    extract::{Path, Query},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use sellershut_core::category::descendant_ids;
+
+use crate::server::{
+    AppError,
+    entities::collection::{PAGE_SIZE, PageQuery},
+    router::routes::{
+        categories::CATEGORIES_TAG,
+        listings::{listing_responses, mine::ListingsResponse},
+    },
+    state::AppState,
+};
+
+/// Browse the listings of a category
+///
+/// Active listings in the category and every category below it, most recently published
+/// first, one page at a time.
+#[utoipa::path(
+    get,
+    path = "/{slug}/listings",
+    params(
+        ("slug" = String, Path, description = "Slug of the category"),
+        PageQuery
+    ),
+    responses(
+        (status = 200, description = "Listings of the category", body = ListingsResponse,
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 404, description = "No such category"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = CATEGORIES_TAG,
+)]
+pub async fn category_listings(
+    Path(slug): Path<String>,
+    Query(query): Query<PageQuery>,
+    state: Data<AppState>,
+) -> Result<Response, AppError> {
+    let categories = state.category.category_tree().await?;
+    let Some(category) = categories.iter().find(|category| category.slug == slug) else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+    let ids = descendant_ids(&categories, category.id);
+
+    let offset = query.page.map(PageQuery::offset).unwrap_or_default();
+    let total_items = state.listing.count_listings_in_categories(&ids).await?;
+    let listings = state
+        .listing
+        .listings_in_categories(&ids, i64::from(PAGE_SIZE), offset)
+        .await?;
+
+    Ok(Json(ListingsResponse::new(
+        total_items,
+        listing_responses(listings, &state).await?,
+    ))
+    .into_response())
+}
@@ -0,0 +1,149 @@
+use std::collections::BTreeMap;
+
+use sellershut_core::category::Category;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use utoipa::{IntoParams, OpenApi, ToSchema};
+use utoipa_axum::router::OpenApiRouter;
+use uuid::Uuid;
+
+pub mod browse;
+pub mod tree;
+
+pub fn router() -> OpenApiRouter {
+    let router = OpenApiRouter::new();
+
+    router
+        .routes(utoipa_axum::routes!(tree::category_tree))
+        .routes(utoipa_axum::routes!(tree::get_category))
+        .routes(utoipa_axum::routes!(browse::category_listings))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct LocaleQuery {
+    /// Locale to name categories in, like `de` or `pt-BR`. Defaults to `en`
+    locale: Option<String>,
+}
+
+/// A category as administrators manage it.
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryResponse {
+    #[schema(value_type = String)]
+    id: Uuid,
+    #[schema(value_type = Option<String>)]
+    parent_id: Option<Uuid>,
+    slug: String,
+    position: i32,
+    /// Names by locale
+    names: BTreeMap<String, String>,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    updated_at: OffsetDateTime,
+}
+
+impl From<Category> for CategoryResponse {
+    fn from(value: Category) -> Self {
+        Self {
+            id: value.id,
+            parent_id: value.parent_id,
+            slug: value.slug,
+            position: value.position,
+            names: value.names,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+        }
+    }
+}
+
+/// A category with the ones below it.
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryNode {
+    #[schema(value_type = String)]
+    id: Uuid,
+    slug: String,
+    /// In the locale asked for
+    name: String,
+    #[schema(no_recursion)]
+    children: Vec<CategoryNode>,
+}
+
+/// The nodes below `parent_id`, or the top level ones when it is `None`, in order.
+fn nodes(
+    categories: &[Category],
+    parent_id: Option<Uuid>,
+    locale: Option<&str>,
+) -> Vec<CategoryNode> {
+    categories
+        .iter()
+        .filter(|category| category.parent_id == parent_id)
+        .map(|category| node(categories, category, locale))
+        .collect()
+}
+
+fn node(categories: &[Category], category: &Category, locale: Option<&str>) -> CategoryNode {
+    CategoryNode {
+        id: category.id,
+        slug: category.slug.clone(),
+        name: category.name(locale).to_owned(),
+        children: nodes(categories, Some(category.id), locale),
+    }
+}
+
+const CATEGORIES_TAG: &str = "Categories";
+
+#[derive(OpenApi)]
+#[openapi(tags((name = CATEGORIES_TAG, description = "Categories listings are sorted into")))]
+pub struct CategoriesDoc;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn category(id: u128, parent_id: Option<u128>, position: i32) -> Category {
+        Category {
+            id: Uuid::from_u128(id),
+            parent_id: parent_id.map(Uuid::from_u128),
+            slug: format!("category-{id}"),
+            position,
+            names: [
+                ("en".to_owned(), format!("Category {id}")),
+                ("de".to_owned(), format!("Kategorie {id}")),
+            ]
+            .into(),
+            created_at: OffsetDateTime::UNIX_EPOCH,
+            updated_at: OffsetDateTime::UNIX_EPOCH,
+        }
+    }
+
+    #[test]
+    fn tree() {
+        // Ordered by position, like the tree is loaded.
+        let categories = [
+            category(1, None, 0),
+            category(3, Some(1), 0),
+            category(2, Some(1), 1),
+            category(4, Some(3), 0),
+            category(5, None, 1),
+        ];
+
+        let tree = nodes(&categories, None, Some("de"));
+
+        let slugs: Vec<_> = tree.iter().map(|node| node.slug.as_str()).collect();
+        assert_eq!(vec!["category-1", "category-5"], slugs);
+        assert_eq!("Kategorie 1", tree[0].name);
+
+        let children: Vec<_> = tree[0]
+            .children
+            .iter()
+            .map(|node| node.slug.as_str())
+            .collect();
+        assert_eq!(vec!["category-3", "category-2"], children);
+        assert_eq!("category-4", tree[0].children[0].children[0].slug);
+        assert!(tree[1].children.is_empty());
+    }
+}
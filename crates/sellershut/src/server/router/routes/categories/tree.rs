@@ -0,0 +1,101 @@
+use activitypub_federation::config::Data;
+use axum::{
+    Json,
+    extract::{Path, Query},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+
+use crate::server::{
+    AppError,
+    router::routes::categories::{CATEGORIES_TAG, CategoryNode, LocaleQuery, node, nodes},
+    state::AppState,
+};
+
+/// List the category tree
+#[utoipa::path(
+    get,
+    path = "/",
+    params(LocaleQuery),
+    responses(
+        (status = 200, description = "Top level categories, with the ones below them",
+            body = Vec<CategoryNode>,
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = CATEGORIES_TAG,
+)]
+pub async fn category_tree(
+    Query(query): Query<LocaleQuery>,
+    state: Data<AppState>,
+) -> Result<Response, AppError> {
+    let categories = state.category.category_tree().await?;
+
+    Ok(Json(nodes(&categories, None, query.locale.as_deref())).into_response())
+}
+
+/// Get a category
+#[utoipa::path(
+    get,
+    path = "/{slug}",
+    params(
+        ("slug" = String, Path, description = "Slug of the category"),
+        LocaleQuery
+    ),
+    responses(
+        (status = 200, description = "The category, with the ones below it", body = CategoryNode,
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 404, description = "No such category"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = CATEGORIES_TAG,
+)]
+pub async fn get_category(
+    Path(slug): Path<String>,
+    Query(query): Query<LocaleQuery>,
+    state: Data<AppState>,
+) -> Result<Response, AppError> {
+    let categories = state.category.category_tree().await?;
+    let Some(category) = categories.iter().find(|category| category.slug == slug) else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+
+    Ok(Json(node(&categories, category, query.locale.as_deref())).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "requires a live db"]
+    async fn unknown_category(pool: PgPool) -> anyhow::Result<()> {
+        let app = crate::test::test_app(pool).await;
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/categories/not-a-category")
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+        Ok(())
+    }
+}
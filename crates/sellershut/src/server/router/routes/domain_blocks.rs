@@ -0,0 +1,83 @@
+use activitypub_federation::config::Data;
+use axum::{Json, response::IntoResponse};
+use sellershut_core::activity::{BlockSeverity, DomainBlock};
+use serde::Serialize;
+use time::OffsetDateTime;
+use utoipa::ToSchema;
+
+use crate::server::{AppError, state::AppState};
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DomainBlockResponse {
+    domain: String,
+    severity: BlockSeverity,
+    public_reason: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    created_at: OffsetDateTime,
+}
+
+impl From<DomainBlock> for DomainBlockResponse {
+    fn from(value: DomainBlock) -> Self {
+        Self {
+            domain: value.domain,
+            severity: value.severity,
+            public_reason: value.public_reason,
+            created_at: value.created_at,
+        }
+    }
+}
+
+/// List blocked domains
+///
+/// Moderation is public: every block is listed with its severity and the reason given for it.
+#[utoipa::path(
+    get,
+    path = "/domain-blocks",
+    responses(
+        (status = 200, description = "Blocked domains", body = [DomainBlockResponse],
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = env!("CARGO_PKG_NAME"),
+)]
+pub async fn domain_blocks(state: Data<AppState>) -> Result<impl IntoResponse, AppError> {
+    let blocks = state.federation.list_domain_blocks().await?;
+
+    Ok(Json(
+        blocks
+            .into_iter()
+            .map(DomainBlockResponse::from)
+            .collect::<Vec<_>>(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request, http::StatusCode};
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "requires a live db"]
+    async fn lists_blocks_publicly(pool: PgPool) -> anyhow::Result<()> {
+        let app = crate::test::test_app(pool).await;
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/domain-blocks")
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+        Ok(())
+    }
+}
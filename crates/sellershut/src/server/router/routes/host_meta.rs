@@ -0,0 +1,162 @@
+use activitypub_federation::config::Data;
+use axum::{
+    Json,
+    http::{HeaderMap, header},
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::server::{AppError, state::AppState, utilities::webfinger_template};
+
+const XRD_CONTENT_TYPE: &str = "application/xrd+xml";
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HostMeta {
+    links: Vec<HostMetaLink>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HostMetaLink {
+    #[schema(example = "lrdd")]
+    rel: String,
+    #[serde(rename = "type")]
+    #[schema(example = "application/jrd+json")]
+    kind: String,
+    #[schema(example = "https://some.hut/.well-known/webfinger?resource={uri}")]
+    template: String,
+}
+
+/// Host metadata
+///
+/// Points clients at the WebFinger endpoint. Served as XRD, or as JSON when asked for
+/// `application/json`.
+#[utoipa::path(
+    get,
+    path = "/.well-known/host-meta",
+    responses(
+        (status = 200, description = "Host metadata",
+            content(
+                (String = XRD_CONTENT_TYPE),
+                (HostMeta = "application/json")
+            ),
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = env!("CARGO_PKG_NAME"),
+)]
+pub async fn host_meta(headers: HeaderMap, state: Data<AppState>) -> Result<Response, AppError> {
+    let template = webfinger_template(state.port, state.domain())?;
+
+    if wants_json(&headers) {
+        return Ok(Json(lrdd_links(template)).into_response());
+    }
+
+    Ok(([(header::CONTENT_TYPE, XRD_CONTENT_TYPE)], xrd(&template)).into_response())
+}
+
+/// Host metadata as JSON
+#[utoipa::path(
+    get,
+    path = "/.well-known/host-meta.json",
+    responses(
+        (status = 200, description = "Host metadata", body = HostMeta,
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = env!("CARGO_PKG_NAME"),
+)]
+pub async fn host_meta_json(state: Data<AppState>) -> Result<impl IntoResponse, AppError> {
+    let template = webfinger_template(state.port, state.domain())?;
+
+    Ok(Json(lrdd_links(template)))
+}
+
+fn lrdd_links(template: String) -> HostMeta {
+    HostMeta {
+        links: vec![HostMetaLink {
+            rel: "lrdd".to_owned(),
+            kind: "application/jrd+json".to_owned(),
+            template,
+        }],
+    }
+}
+
+fn xrd(template: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<XRD xmlns="http://docs.oasis-open.org/ns/xri/xrd-1.0">
+  <Link rel="lrdd" type="{XRD_CONTENT_TYPE}" template="{}"/>
+</XRD>
+"#,
+        escape_xml(template)
+    )
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Whether the client asked for JSON rather than XRD.
+fn wants_json(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|media_type| media_type.split(';').next().unwrap_or_default().trim())
+        .any(|media_type| {
+            media_type.eq_ignore_ascii_case("application/json")
+                || media_type.eq_ignore_ascii_case("application/jrd+json")
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    #[track_caller]
+    fn check_wants_json(accept: &[&str], expected_result: bool) {
+        let mut headers = HeaderMap::new();
+        for value in accept {
+            headers.append(header::ACCEPT, HeaderValue::from_str(value).unwrap());
+        }
+
+        assert_eq!(expected_result, wants_json(&headers));
+    }
+
+    #[test]
+    fn negotiation() {
+        check_wants_json(&[], false);
+        check_wants_json(&["application/xrd+xml"], false);
+        check_wants_json(&["application/json"], true);
+        check_wants_json(&["text/html, application/JSON;q=0.9"], true);
+    }
+
+    #[test]
+    fn xrd_escapes_template() {
+        let document = xrd("https://some.hut/.well-known/webfinger?resource={uri}&x=\"y\"");
+
+        assert!(document.contains(
+            r#"template="https://some.hut/.well-known/webfinger?resource={uri}&amp;x=&quot;y&quot;""#
+        ));
+    }
+}
@@ -0,0 +1,39 @@
+use activitypub_federation::{axum::inbox::ActivityData, config::Data};
+use axum::{http::HeaderMap, response::Response};
+
+use crate::server::{activities, state::AppState};
+
+/// Shared inbox
+///
+/// Remote servers deliver an activity here once for all of its local recipients instead of
+/// once per addressed user.
+#[utoipa::path(
+    post,
+    path = "/inbox",
+    request_body(
+        content = serde_json::Value,
+        content_type = "application/activity+json",
+        description = "A signed Follow, Undo, Accept, Reject, Create, Update or Delete activity"
+    ),
+    responses(
+        (status = 202, description = "Activity accepted",
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 400, description = "Malformed or unsupported activity"),
+        (status = 401, description = "Missing or invalid HTTP signature"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = env!("CARGO_PKG_NAME")
+)]
+pub async fn shared_inbox(
+    headers: HeaderMap,
+    state: Data<AppState>,
+    activity_data: ActivityData,
+) -> Response {
+    activities::receive(&headers, activity_data, &state).await
+}
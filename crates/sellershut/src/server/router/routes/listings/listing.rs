@@ -0,0 +1,442 @@
+use activitypub_federation::{
+    axum::json::FederationJson, config::Data, protocol::context::WithContext, traits::Object,
+};
+use axum::{
+    Json,
+    extract::Path,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use axum_extra::{
+    TypedHeader,
+    headers::{Authorization, authorization::Bearer},
+};
+use sellershut_core::{
+    Money,
+    listing::{Listing, ListingCondition, ListingStatus},
+};
+use sellershut_listings::{CreateListing, UpdateListing, error::ListingError};
+use sellershut_media::error::MediaError;
+use serde::Deserialize;
+use time::OffsetDateTime;
+use tracing::debug;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::server::{
+    AppError,
+    activities::{create, delete, update},
+    entities::{self, listing::ListingObject, user::User},
+    router::routes::{
+        listings::{LISTINGS_TAG, ListingResponse, listing_response},
+        present, session_user,
+        users::actor::wants_activity,
+    },
+    state::AppState,
+    utilities,
+};
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NewListing {
+    title: String,
+    #[serde(default)]
+    description: String,
+    price: Money,
+    condition: ListingCondition,
+    /// Defaults to `Draft`
+    status: Option<ListingStatus>,
+    #[schema(value_type = Option<String>)]
+    category_id: Option<Uuid>,
+    /// Ids of the user's uploaded files to show, in order
+    #[schema(value_type = Option<Vec<String>>)]
+    media: Option<Vec<Uuid>>,
+}
+
+/// Create a listing
+#[utoipa::path(
+    post,
+    path = "/",
+    security(
+        ("bearer_auth" = [])
+    ),
+    request_body = NewListing,
+    responses(
+        (status = 201, description = "Created listing", body = ListingResponse,
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 401, description = "Unauthorized"),
+        (status = 422, description = "The listing is invalid or a file can't be used"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = LISTINGS_TAG,
+)]
+pub async fn create_listing(
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    state: Data<AppState>,
+    Json(body): Json<NewListing>,
+) -> Result<Response, AppError> {
+    let Some(user) = session_user(bearer.token(), &state).await else {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    };
+
+    let id = Uuid::now_v7();
+    let status = body.status.unwrap_or(ListingStatus::Draft);
+    let data = CreateListing {
+        id,
+        ap_id: utilities::listing_url(state.port, state.domain(), id)?,
+        owner_id: user.id,
+        title: body.title,
+        description: body.description,
+        price: body.price,
+        condition: body.condition,
+        status,
+        is_local: true,
+        published_at: (status == ListingStatus::Active).then(OffsetDateTime::now_utc),
+        category_id: body.category_id,
+    };
+
+    let listing = match state.listing.create_listing(&data).await {
+        Ok(listing) => listing,
+        Err(e) => return invalid(e),
+    };
+    if let Some(media) = &body.media
+        && let Err(e) = state.media.attach_media(listing.id, user.id, media).await
+    {
+        state.listing.delete_listing(listing.id).await?;
+        return unusable_media(e);
+    }
+    federate(&User::from(user), None, Some(&listing), &state).await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(listing_response(listing, &state).await?),
+    )
+        .into_response())
+}
+
+/// Get a listing
+///
+/// Drafts are only visible to their owner. Clients asking for ActivityPub JSON get the object
+/// other instances see, which only exists for public local listings.
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    security(
+        (),
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Id of the listing")
+    ),
+    responses(
+        (status = 200, description = "The listing",
+            content(
+                (ListingResponse = "application/json"),
+                (ListingObject = "application/activity+json")
+            ),
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 404, description = "No such listing"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = LISTINGS_TAG,
+)]
+pub async fn get_listing(
+    bearer: Option<TypedHeader<Authorization<Bearer>>>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+    state: Data<AppState>,
+) -> Result<Response, AppError> {
+    let Some(listing) = state.listing.get_listing(id).await? else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+
+    if wants_activity(&headers) {
+        if !listing.is_local || !listing.status.is_public() {
+            return Ok(StatusCode::NOT_FOUND.into_response());
+        }
+
+        let object = entities::listing::Listing::from(listing)
+            .into_json(&state)
+            .await?;
+        return Ok(FederationJson(WithContext::new_default(object)).into_response());
+    }
+
+    if !listing.status.is_public() {
+        let user = match bearer {
+            Some(TypedHeader(Authorization(bearer))) => session_user(bearer.token(), &state).await,
+            None => None,
+        };
+        if user.is_none_or(|user| user.id != listing.owner_id) {
+            return Ok(StatusCode::NOT_FOUND.into_response());
+        }
+    }
+
+    Ok(Json(listing_response(listing, &state).await?).into_response())
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ListingChanges {
+    title: Option<String>,
+    description: Option<String>,
+    price: Option<Money>,
+    condition: Option<ListingCondition>,
+    status: Option<ListingStatus>,
+    /// `null` takes the listing out of its category
+    #[serde(default, deserialize_with = "present")]
+    #[schema(value_type = Option<String>)]
+    category_id: Option<Option<Uuid>>,
+    /// Replaces the listing's files
+    #[schema(value_type = Option<Vec<String>>)]
+    media: Option<Vec<Uuid>>,
+}
+
+/// Update a listing
+///
+/// Only the fields that are set are changed.
+#[utoipa::path(
+    patch,
+    path = "/{id}",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Id of the listing")
+    ),
+    request_body = ListingChanges,
+    responses(
+        (status = 200, description = "Updated listing", body = ListingResponse,
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "No listing of the current user with that id"),
+        (status = 422, description = "The changes are invalid or a file can't be used"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = LISTINGS_TAG,
+)]
+pub async fn update_listing(
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    Path(id): Path<Uuid>,
+    state: Data<AppState>,
+    Json(body): Json<ListingChanges>,
+) -> Result<Response, AppError> {
+    let (owner, listing) = match owned_listing(bearer.token(), id, &state).await? {
+        Ok(owned) => owned,
+        Err(status) => return Ok(status.into_response()),
+    };
+
+    let data = UpdateListing {
+        title: body.title,
+        description: body.description,
+        price: body.price,
+        condition: body.condition,
+        status: body.status,
+        category_id: body.category_id,
+    };
+    if let Err(e) = data.validate() {
+        return invalid(e);
+    }
+    if let Some(media) = &body.media
+        && let Err(e) = state
+            .media
+            .check_media(listing.id, listing.owner_id, media)
+            .await
+    {
+        return unusable_media(e);
+    }
+
+    let updated = match state.listing.update_listing(listing.id, &data).await {
+        Ok(Some(updated)) => updated,
+        Ok(None) => return Ok(StatusCode::NOT_FOUND.into_response()),
+        Err(e) => return invalid(e),
+    };
+    if let Some(media) = &body.media
+        && let Err(e) = state
+            .media
+            .attach_media(listing.id, listing.owner_id, media)
+            .await
+    {
+        // The files were taken in the meantime, the other changes are kept.
+        federate(&owner, Some(&listing), Some(&updated), &state).await?;
+        return unusable_media(e);
+    }
+    federate(&owner, Some(&listing), Some(&updated), &state).await?;
+
+    Ok(Json(listing_response(updated, &state).await?).into_response())
+}
+
+/// Delete a listing
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Id of the listing")
+    ),
+    responses(
+        (status = 204, description = "Listing deleted",
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "No listing of the current user with that id"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = LISTINGS_TAG,
+)]
+pub async fn delete_listing(
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    Path(id): Path<Uuid>,
+    state: Data<AppState>,
+) -> Result<Response, AppError> {
+    let (owner, listing) = match owned_listing(bearer.token(), id, &state).await? {
+        Ok(owned) => owned,
+        Err(status) => return Ok(status.into_response()),
+    };
+
+    let Some(deleted) = state.listing.delete_listing(listing.id).await? else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+    federate(&owner, Some(&deleted), None, &state).await?;
+
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+/// A listing of the session's user, other people's listings are reported as missing.
+async fn owned_listing(
+    token: &str,
+    id: Uuid,
+    state: &Data<AppState>,
+) -> Result<Result<(User, Listing), StatusCode>, AppError> {
+    let Some(user) = session_user(token, state).await else {
+        return Ok(Err(StatusCode::UNAUTHORIZED));
+    };
+
+    match state.listing.get_listing(id).await? {
+        Some(listing) if listing.owner_id == user.id && listing.is_local => {
+            Ok(Ok((User::from(user), listing)))
+        }
+        _ => Ok(Err(StatusCode::NOT_FOUND)),
+    }
+}
+
+/// Tell the owner's followers' servers about a listing changing from `before` to `after`.
+///
+/// Drafts are never sent, so a listing is created remotely when it is first made public and
+/// deleted remotely when it is deleted or turned back into a draft.
+async fn federate(
+    owner: &User,
+    before: Option<&Listing>,
+    after: Option<&Listing>,
+    state: &Data<AppState>,
+) -> Result<(), AppError> {
+    let before = before.filter(|listing| listing.status.is_public());
+    let after = after.filter(|listing| listing.status.is_public());
+
+    match (before, after) {
+        (None, Some(after)) => create::create_listing(owner, after.clone().into(), state).await,
+        (Some(_), Some(after)) => update::update_listing(owner, after.clone().into(), state).await,
+        (Some(before), None) => delete::delete_listing(owner, &before.clone().into(), state).await,
+        (None, None) => Ok(()),
+    }
+}
+
+/// Answer validation errors with 422, anything else is a server error.
+fn invalid(e: ListingError) -> Result<Response, AppError> {
+    match e {
+        ListingError::Invalid(reason) => {
+            debug!(reason, "invalid listing");
+            Ok((StatusCode::UNPROCESSABLE_ENTITY, reason).into_response())
+        }
+        ListingError::Price(e) => {
+            debug!(error = %e, "invalid listing price");
+            Ok((StatusCode::UNPROCESSABLE_ENTITY, e.to_string()).into_response())
+        }
+        e => Err(e.into()),
+    }
+}
+
+/// Answer files that can't be attached with 422, anything else is a server error.
+fn unusable_media(e: MediaError) -> Result<Response, AppError> {
+    match e {
+        MediaError::Invalid(reason) => {
+            debug!(reason, "invalid listing media");
+            Ok((StatusCode::UNPROCESSABLE_ENTITY, reason).into_response())
+        }
+        MediaError::Unavailable => {
+            debug!("unavailable listing media");
+            Ok((StatusCode::UNPROCESSABLE_ENTITY, e.to_string()).into_response())
+        }
+        e => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "requires a live db"]
+    async fn create_requires_session(pool: PgPool) -> anyhow::Result<()> {
+        let app = crate::test::test_app(pool).await;
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/listings")
+                    .header("authorization", "Bearer not-a-session")
+                    .header("content-type", "application/json")
+                    .body(Body::from(
+                        r#"{"title":"Bike","price":{"amount":10000,"currency":"EUR"},"condition":"Good"}"#,
+                    ))?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::UNAUTHORIZED, response.status());
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "requires a live db"]
+    async fn unknown_listing(pool: PgPool) -> anyhow::Result<()> {
+        let app = crate::test::test_app(pool).await;
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/listings/{}", Uuid::now_v7()))
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+        Ok(())
+    }
+}
@@ -0,0 +1,123 @@
+use activitypub_federation::config::Data;
+use axum::{
+    Json,
+    extract::Query,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use axum_extra::{
+    TypedHeader,
+    headers::{Authorization, authorization::Bearer},
+};
+use sellershut_core::listing::ListingStatus;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::server::{
+    AppError,
+    entities::collection::{PAGE_SIZE, PageQuery},
+    router::routes::{
+        listings::{LISTINGS_TAG, ListingResponse, listing_responses},
+        session_user,
+    },
+    state::AppState,
+};
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct StatusQuery {
+    /// Only return listings with this status
+    status: Option<ListingStatus>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ListingsResponse {
+    total_items: i64,
+    listings: Vec<ListingResponse>,
+}
+
+impl ListingsResponse {
+    pub fn new(total_items: i64, listings: Vec<ListingResponse>) -> Self {
+        Self {
+            total_items,
+            listings,
+        }
+    }
+}
+
+/// List the current user's listings
+///
+/// Newest first, drafts included, one page at a time.
+#[utoipa::path(
+    get,
+    path = "/mine",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(StatusQuery, PageQuery),
+    responses(
+        (status = 200, description = "The current user's listings", body = ListingsResponse,
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = LISTINGS_TAG,
+)]
+pub async fn my_listings(
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    Query(filter): Query<StatusQuery>,
+    Query(query): Query<PageQuery>,
+    state: Data<AppState>,
+) -> Result<Response, AppError> {
+    let Some(user) = session_user(bearer.token(), &state).await else {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    };
+
+    let offset = query.page.map(PageQuery::offset).unwrap_or_default();
+    let total_items = state
+        .listing
+        .count_listings_by_owner(user.id, filter.status)
+        .await?;
+    let listings = state
+        .listing
+        .listings_by_owner(user.id, filter.status, i64::from(PAGE_SIZE), offset)
+        .await?;
+
+    Ok(Json(ListingsResponse {
+        total_items,
+        listings: listing_responses(listings, &state).await?,
+    })
+    .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "requires a live db"]
+    async fn requires_session(pool: PgPool) -> anyhow::Result<()> {
+        let app = crate::test::test_app(pool).await;
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/listings/mine?status=Draft")
+                    .header("authorization", "Bearer not-a-session")
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::UNAUTHORIZED, response.status());
+        Ok(())
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use activitypub_federation::config::Data;
+use sellershut_core::{
+    Money,
+    listing::{Listing, ListingCondition, ListingStatus},
+};
+use serde::Serialize;
+use time::OffsetDateTime;
+use url::Url;
+use utoipa::{OpenApi, ToSchema};
+use utoipa_axum::router::OpenApiRouter;
+use uuid::Uuid;
+
+use crate::server::{AppError, router::routes::media::MediaResponse, state::AppState};
+
+pub mod listing;
+pub mod mine;
+
+pub fn router() -> OpenApiRouter {
+    let router = OpenApiRouter::new();
+
+    router
+        .routes(utoipa_axum::routes!(listing::create_listing))
+        .routes(utoipa_axum::routes!(mine::my_listings))
+        .routes(utoipa_axum::routes!(
+            listing::get_listing,
+            listing::update_listing,
+            listing::delete_listing
+        ))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ListingResponse {
+    #[schema(value_type = String)]
+    id: Uuid,
+    #[schema(value_type = String, format = Uri)]
+    ap_id: Url,
+    #[schema(value_type = String)]
+    owner_id: Uuid,
+    title: String,
+    description: String,
+    price: Money,
+    condition: ListingCondition,
+    status: ListingStatus,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    updated_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    #[schema(value_type = Option<String>, format = DateTime)]
+    published_at: Option<OffsetDateTime>,
+    #[schema(value_type = Option<String>)]
+    category_id: Option<Uuid>,
+    /// In the order they are shown
+    media: Vec<MediaResponse>,
+}
+
+impl ListingResponse {
+    fn new(value: Listing, media: Vec<MediaResponse>) -> Self {
+        Self {
+            id: value.id,
+            ap_id: value.ap_id.inner(),
+            owner_id: value.owner_id,
+            title: value.title,
+            description: value.description,
+            price: value.price,
+            condition: value.condition,
+            status: value.status,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+            published_at: value.published_at,
+            category_id: value.category_id,
+            media,
+        }
+    }
+}
+
+/// Responses for `listings` with their files, in the same order.
+pub(super) async fn listing_responses(
+    listings: Vec<Listing>,
+    state: &Data<AppState>,
+) -> Result<Vec<ListingResponse>, AppError> {
+    let ids: Vec<_> = listings.iter().map(|listing| listing.id).collect();
+
+    let mut media: HashMap<Uuid, Vec<MediaResponse>> = HashMap::new();
+    for file in state.media.media_for_listings(&ids).await? {
+        if let Some(listing_id) = file.listing_id {
+            media
+                .entry(listing_id)
+                .or_default()
+                .push(MediaResponse::new(file, state)?);
+        }
+    }
+
+    Ok(listings
+        .into_iter()
+        .map(|listing| {
+            let media = media.remove(&listing.id).unwrap_or_default();
+            ListingResponse::new(listing, media)
+        })
+        .collect())
+}
+
+async fn listing_response(
+    listing: Listing,
+    state: &Data<AppState>,
+) -> Result<ListingResponse, AppError> {
+    let mut responses = listing_responses(vec![listing], state).await?;
+    Ok(responses.remove(0))
+}
+
+pub(super) const LISTINGS_TAG: &str = "Listings";
+
+#[derive(OpenApi)]
+#[openapi(tags((name = LISTINGS_TAG, description = "Listings")))]
+pub struct ListingsDoc;
@@ -0,0 +1,366 @@
+use activitypub_federation::config::Data;
+use axum::{
+    Json,
+    extract::{Multipart, Path},
+    http::{
+        StatusCode,
+        header::{CACHE_CONTROL, CONTENT_TYPE, X_CONTENT_TYPE_OPTIONS},
+    },
+    response::{IntoResponse, Response},
+};
+use axum_extra::{
+    TypedHeader,
+    headers::{Authorization, authorization::Bearer},
+};
+use sellershut_core::media::{Media, MediaStatus};
+use sellershut_media::{
+    CreateMedia,
+    error::MediaError,
+    process::{THUMBNAIL_CONTENT_TYPE, can_process},
+    sniff_content_type,
+};
+use tracing::{debug, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::server::{
+    AppError,
+    activities::update,
+    entities::user::User,
+    router::routes::{
+        media::{MEDIA_TAG, MediaResponse},
+        session_user,
+    },
+    state::AppState,
+};
+
+/// Files never change once uploaded, a new file gets a new id.
+const IMMUTABLE: &str = "public, max-age=31536000, immutable";
+
+#[derive(ToSchema)]
+#[allow(dead_code)]
+pub struct UploadForm {
+    /// The image, its type is told from its content
+    #[schema(value_type = String, format = Binary)]
+    file: Vec<u8>,
+    /// Alt text
+    description: Option<String>,
+}
+
+/// Upload a file
+///
+/// Only images are accepted. The file can then be attached to the user's listings. It is
+/// served once its metadata, like where a photo was taken, has been stripped in the background.
+#[utoipa::path(
+    post,
+    path = "/",
+    security(
+        ("bearer_auth" = [])
+    ),
+    request_body(content = UploadForm, content_type = "multipart/form-data"),
+    responses(
+        (status = 201, description = "Uploaded file", body = MediaResponse,
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 400, description = "The form is malformed or has no file"),
+        (status = 401, description = "Unauthorized"),
+        (status = 413, description = "The file is too large"),
+        (status = 415, description = "The file is not a supported image"),
+        (status = 422, description = "The description is invalid"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = MEDIA_TAG,
+)]
+pub async fn upload_media(
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    state: Data<AppState>,
+    mut multipart: Multipart,
+) -> Result<Response, AppError> {
+    let Some(user) = session_user(bearer.token(), &state).await else {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    };
+
+    let mut file = None;
+    let mut description = None;
+    loop {
+        let mut field = match multipart.next_field().await {
+            Ok(Some(field)) => field,
+            Ok(None) => break,
+            Err(e) => return Ok(e.into_response()),
+        };
+
+        match field.name() {
+            Some("file") => {
+                let mut bytes = Vec::new();
+                loop {
+                    match field.chunk().await {
+                        Ok(Some(chunk)) => {
+                            if bytes.len() + chunk.len() > state.max_upload_size {
+                                return Ok(StatusCode::PAYLOAD_TOO_LARGE.into_response());
+                            }
+                            bytes.extend_from_slice(&chunk);
+                        }
+                        Ok(None) => break,
+                        Err(e) => return Ok(e.into_response()),
+                    }
+                }
+                file = Some(bytes);
+            }
+            Some("description") => match field.text().await {
+                Ok(text) if !text.trim().is_empty() => description = Some(text),
+                Ok(_) => {}
+                Err(e) => return Ok(e.into_response()),
+            },
+            _ => {}
+        }
+    }
+
+    let Some(file) = file else {
+        return Ok((StatusCode::BAD_REQUEST, "no file").into_response());
+    };
+    let Some(content_type) = sniff_content_type(&file).filter(|kind| can_process(kind)) else {
+        return Ok(StatusCode::UNSUPPORTED_MEDIA_TYPE.into_response());
+    };
+
+    let id = Uuid::now_v7();
+    let data = CreateMedia {
+        id,
+        owner_id: user.id,
+        content_type: content_type.to_owned(),
+        size: file.len() as i64,
+        storage_key: id.to_string(),
+        description,
+    };
+    if let Err(MediaError::Invalid(reason)) = data.validate() {
+        debug!(reason, "invalid media");
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, reason).into_response());
+    }
+
+    state
+        .media_store
+        .put(&data.storage_key, content_type, &file)
+        .await?;
+    let media = match state.media.create_media(&data).await {
+        Ok(media) => media,
+        Err(e) => {
+            if let Err(e) = state.media_store.delete(&data.storage_key).await {
+                warn!(key = data.storage_key, error = %e, "failed to remove orphaned file");
+            }
+            return Err(e.into());
+        }
+    };
+
+    Ok((
+        StatusCode::CREATED,
+        Json(MediaResponse::new(media, &state)?),
+    )
+        .into_response())
+}
+
+/// Get a file
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    params(
+        ("id" = String, Path, description = "Id of the file")
+    ),
+    responses(
+        (status = 200, description = "The file",
+            content(
+                (Vec<u8> = "image/*")
+            ),
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 404, description = "No such file, or it is not processed yet"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = MEDIA_TAG,
+)]
+pub async fn get_media(Path(id): Path<Uuid>, state: Data<AppState>) -> Result<Response, AppError> {
+    match state.media.get_media(id).await? {
+        Some(Media {
+            status: MediaStatus::Ready,
+            storage_key: Some(key),
+            content_type,
+            ..
+        }) => serve(&key, &content_type, &state).await,
+        _ => Ok(StatusCode::NOT_FOUND.into_response()),
+    }
+}
+
+/// Get the thumbnail of a file
+///
+/// Thumbnails are squares cut from the middle of the image.
+#[utoipa::path(
+    get,
+    path = "/{id}/thumbnail",
+    params(
+        ("id" = String, Path, description = "Id of the file")
+    ),
+    responses(
+        (status = 200, description = "The thumbnail",
+            content(
+                (Vec<u8> = "image/jpeg")
+            ),
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 404, description = "No such file, or it is not processed yet"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = MEDIA_TAG,
+)]
+pub async fn get_thumbnail(
+    Path(id): Path<Uuid>,
+    state: Data<AppState>,
+) -> Result<Response, AppError> {
+    match state.media.get_media(id).await? {
+        Some(media) if media.status == MediaStatus::Ready => match &media.thumbnail_key {
+            Some(key) => serve(key, THUMBNAIL_CONTENT_TYPE, &state).await,
+            None => Ok(StatusCode::NOT_FOUND.into_response()),
+        },
+        _ => Ok(StatusCode::NOT_FOUND.into_response()),
+    }
+}
+
+async fn serve(
+    key: &str,
+    content_type: &str,
+    state: &Data<AppState>,
+) -> Result<Response, AppError> {
+    let Some(bytes) = state.media_store.get(key).await? else {
+        warn!(key, "file is missing from the media store");
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+
+    Ok((
+        [
+            (CONTENT_TYPE, content_type),
+            (CACHE_CONTROL, IMMUTABLE),
+            (X_CONTENT_TYPE_OPTIONS, "nosniff"),
+        ],
+        bytes,
+    )
+        .into_response())
+}
+
+/// Delete a file
+///
+/// Listings using it lose it.
+#[utoipa::path(
+    delete,
+    path = "/{id}",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("id" = String, Path, description = "Id of the file")
+    ),
+    responses(
+        (status = 204, description = "File deleted",
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "No file of the current user with that id"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = MEDIA_TAG,
+)]
+pub async fn delete_media(
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    Path(id): Path<Uuid>,
+    state: Data<AppState>,
+) -> Result<Response, AppError> {
+    let Some(user) = session_user(bearer.token(), &state).await else {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    };
+
+    match state.media.get_media(id).await? {
+        Some(media) if media.owner_id == user.id => {}
+        _ => return Ok(StatusCode::NOT_FOUND.into_response()),
+    }
+
+    let Some(media) = state.media.delete_media(id).await? else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+    if let Some(key) = &media.storage_key {
+        state.media_store.delete(key).await?;
+    }
+    if let Some(key) = &media.thumbnail_key {
+        state.media_store.delete(key).await?;
+    }
+
+    // Other instances keep showing the file until they get the listing without it.
+    if let Some(listing_id) = media.listing_id
+        && let Some(listing) = state.listing.get_listing(listing_id).await?
+        && listing.is_local
+        && listing.status.is_public()
+    {
+        update::update_listing(&User::from(user), listing.into(), &state).await?;
+    }
+
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "requires a live db"]
+    async fn upload_requires_session(pool: PgPool) -> anyhow::Result<()> {
+        let app = crate::test::test_app(pool).await;
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/media")
+                    .header("authorization", "Bearer not-a-session")
+                    .header("content-type", "multipart/form-data; boundary=x")
+                    .body(Body::from("--x--\r\n"))?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::UNAUTHORIZED, response.status());
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "requires a live db"]
+    async fn unknown_media(pool: PgPool) -> anyhow::Result<()> {
+        let app = crate::test::test_app(pool).await;
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/media/{}", Uuid::now_v7()))
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+        Ok(())
+    }
+}
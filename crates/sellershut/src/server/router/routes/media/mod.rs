@@ -0,0 +1,91 @@
+use activitypub_federation::config::Data;
+use axum::extract::DefaultBodyLimit;
+use sellershut_core::media::{Media, MediaStatus};
+use serde::Serialize;
+use time::OffsetDateTime;
+use url::Url;
+use utoipa::{OpenApi, ToSchema};
+use utoipa_axum::router::OpenApiRouter;
+use uuid::Uuid;
+
+use crate::server::{state::AppState, utilities};
+
+pub mod file;
+
+/// Room for the multipart boundaries and the other fields around the file.
+const FORM_OVERHEAD: usize = 64 * 1024;
+
+pub fn router(max_upload_size: usize) -> OpenApiRouter {
+    let router = OpenApiRouter::new();
+
+    router
+        .routes(utoipa_axum::routes!(file::upload_media))
+        .routes(utoipa_axum::routes!(file::get_media, file::delete_media))
+        .routes(utoipa_axum::routes!(file::get_thumbnail))
+        .layer(DefaultBodyLimit::max(max_upload_size + FORM_OVERHEAD))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaResponse {
+    #[schema(value_type = String)]
+    id: Uuid,
+    /// On the listing's own instance for listings from other instances
+    #[schema(value_type = String, format = Uri)]
+    url: Url,
+    /// Set once the file is processed
+    #[schema(value_type = Option<String>, format = Uri)]
+    thumbnail_url: Option<Url>,
+    /// Files are only served once they are `Ready`
+    status: MediaStatus,
+    content_type: String,
+    /// In bytes, `0` for files of listings from other instances
+    size: i64,
+    /// In pixels
+    width: Option<i32>,
+    /// In pixels
+    height: Option<i32>,
+    /// Placeholder to show while the file loads
+    blurhash: Option<String>,
+    /// Alt text
+    description: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    #[schema(value_type = String, format = DateTime)]
+    created_at: OffsetDateTime,
+}
+
+impl MediaResponse {
+    pub fn new(media: Media, state: &Data<AppState>) -> Result<Self, url::ParseError> {
+        let thumbnail_url = match media.thumbnail_key {
+            Some(_) => Some(utilities::media_thumbnail_url(
+                state.port,
+                state.domain(),
+                media.id,
+            )?),
+            None => None,
+        };
+
+        Ok(Self {
+            id: media.id,
+            url: match media.remote_url {
+                Some(url) => url.inner(),
+                None => utilities::media_url(state.port, state.domain(), media.id)?,
+            },
+            thumbnail_url,
+            status: media.status,
+            content_type: media.content_type,
+            size: media.size,
+            width: media.width,
+            height: media.height,
+            blurhash: media.blurhash,
+            description: media.description,
+            created_at: media.created_at,
+        })
+    }
+}
+
+const MEDIA_TAG: &str = "Media";
+
+#[derive(OpenApi)]
+#[openapi(tags((name = MEDIA_TAG, description = "Uploaded files")))]
+pub struct MediaDoc;
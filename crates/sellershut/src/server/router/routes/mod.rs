@@ -1,6 +1,47 @@
+pub mod admin;
 pub mod auth;
+pub mod categories;
+mod domain_blocks;
 mod health;
+mod host_meta;
+mod inbox;
+pub mod listings;
+pub mod media;
+mod nodeinfo;
+mod search;
 pub mod users;
 mod webfinger;
+pub use domain_blocks::*;
 pub use health::*;
+pub use host_meta::*;
+pub use inbox::*;
+pub use nodeinfo::*;
+pub use search::*;
 pub use webfinger::*;
+
+use activitypub_federation::config::Data;
+use sellershut_core::user::User;
+use serde::{Deserialize, Deserializer};
+use tracing::debug;
+
+use crate::server::state::AppState;
+
+/// The user a session token belongs to, `None` when the session is invalid.
+async fn session_user(token: &str, state: &Data<AppState>) -> Option<User> {
+    match state.user.user_from_session(token).await {
+        Ok(user) => Some(user),
+        Err(e) => {
+            debug!(err = ?e, "unauthorised session");
+            None
+        }
+    }
+}
+
+/// Tell a field set to `null` apart from a missing one.
+fn present<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::deserialize(deserializer).map(Some)
+}
@@ -0,0 +1,149 @@
+use activitypub_federation::config::Data;
+use axum::{Json, http::header, response::IntoResponse};
+use url::Url;
+
+use crate::server::{
+    AppError,
+    entities::nodeinfo::{
+        NODEINFO_2_1_CONTENT_TYPE, NODEINFO_2_1_REL, NodeInfo, NodeInfoLink, NodeInfoLinks,
+        Services, Software, Usage, UsageUsers,
+    },
+    state::AppState,
+    utilities::base_url,
+};
+
+/// NodeInfo discovery
+///
+/// Links to the NodeInfo documents this server offers.
+#[utoipa::path(
+    get,
+    path = "/.well-known/nodeinfo",
+    responses(
+        (status = 200, description = "NodeInfo links", content_type = "application/json",
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = env!("CARGO_PKG_NAME"),
+)]
+pub async fn nodeinfo_links(state: Data<AppState>) -> Result<impl IntoResponse, AppError> {
+    let href = base_url(state.port, state.domain())?.join("nodeinfo/2.1")?;
+
+    Ok(Json(NodeInfoLinks {
+        links: vec![NodeInfoLink {
+            rel: NODEINFO_2_1_REL.to_owned(),
+            href,
+        }],
+    }))
+}
+
+/// NodeInfo 2.1
+///
+/// Software, protocols and usage statistics of this server. Counts are refreshed hourly.
+#[utoipa::path(
+    get,
+    path = "/nodeinfo/2.1",
+    responses(
+        (status = 200, description = "NodeInfo document", content_type = NODEINFO_2_1_CONTENT_TYPE,
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = env!("CARGO_PKG_NAME"),
+)]
+pub async fn nodeinfo(state: Data<AppState>) -> Result<impl IntoResponse, AppError> {
+    let users = state.user.count_local_users().await?;
+    let listings = state.listing.count_local_listings().await?;
+
+    let document = NodeInfo {
+        version: "2.1".to_owned(),
+        software: Software {
+            name: env!("CARGO_PKG_NAME").to_owned(),
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            repository: Url::parse(env!("CARGO_PKG_REPOSITORY")).ok(),
+            homepage: Url::parse(env!("CARGO_PKG_HOMEPAGE")).ok(),
+        },
+        protocols: vec!["activitypub".to_owned()],
+        services: Services::default(),
+        // Anyone can sign up by logging in with one of the configured OAuth providers
+        open_registrations: true,
+        usage: Usage {
+            users: UsageUsers {
+                total: Some(users.total),
+                active_month: Some(users.active_month),
+                active_halfyear: Some(users.active_halfyear),
+            },
+            local_posts: Some(listings),
+        },
+        metadata: serde_json::Map::from_iter([(
+            "nodeName".to_owned(),
+            serde_json::Value::String(state.system_user.data().username.clone()),
+        )]),
+    };
+
+    Ok((
+        [(header::CONTENT_TYPE, NODEINFO_2_1_CONTENT_TYPE)],
+        Json(document),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request, http::StatusCode};
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "requires a live db"]
+    async fn nodeinfo(pool: PgPool) -> anyhow::Result<()> {
+        let app = crate::test::test_app(pool).await;
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/nodeinfo/2.1")
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+        Ok(())
+    }
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "requires a live db"]
+    async fn deleted_users_are_not_counted(pool: PgPool) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            insert into "user" (id, ap_id, username, inbox, public_key, private_key, is_local, deleted_at)
+            values
+                (gen_random_uuid(), 'http://localhost/users/alice', 'alice', 'http://localhost/users/alice/inbox', 'key', 'key', true, null),
+                (gen_random_uuid(), 'http://localhost/users/bob', 'bob', 'http://localhost/users/bob/inbox', 'key', 'key', true, now())
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+
+        let app = crate::test::test_app(pool).await;
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/nodeinfo/2.1")
+                    .body(Body::empty())?,
+            )
+            .await?;
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await?;
+        let document: serde_json::Value = serde_json::from_slice(&body)?;
+
+        assert_eq!(1, document["usage"]["users"]["total"]);
+        Ok(())
+    }
+}
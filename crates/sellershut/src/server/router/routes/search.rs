@@ -0,0 +1,399 @@
+use activitypub_federation::config::Data;
+use axum::{
+    Json,
+    extract::Query,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use sellershut_core::{
+    Currency, Money,
+    category::{Category, descendant_ids},
+    listing::{ListingCondition, SearchSort},
+};
+use sellershut_listings::{
+    error::ListingError,
+    search::{CategoryCount, SearchListings, SearchPosition},
+};
+use serde::{Deserialize, Deserializer, Serialize};
+use tracing::debug;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::server::{
+    AppError,
+    entities::collection::PAGE_SIZE,
+    router::routes::listings::{LISTINGS_TAG, ListingResponse, listing_responses},
+    state::AppState,
+};
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchQuery {
+    /// Words to look for in titles and descriptions
+    q: Option<String>,
+    /// Slug of a category, the categories below it are searched too
+    category: Option<String>,
+    /// Lowest price, like `12.50 EUR`
+    #[serde(default, deserialize_with = "money")]
+    #[param(value_type = Option<String>, example = "12.50 EUR")]
+    min_price: Option<Money>,
+    /// Highest price, like `100 EUR`, in the same currency as the lowest
+    #[serde(default, deserialize_with = "money")]
+    #[param(value_type = Option<String>, example = "100 EUR")]
+    max_price: Option<Money>,
+    /// ISO 4217 code of the prices, required to sort by price without a price range
+    #[param(value_type = Option<String>, example = "EUR")]
+    currency: Option<Currency>,
+    condition: Option<ListingCondition>,
+    /// `true` for listings of this instance, `false` for ones from other instances
+    local: Option<bool>,
+    /// Id of the user selling the listings
+    #[param(value_type = Option<String>)]
+    seller: Option<Uuid>,
+    /// Defaults to `Relevance` when there are words to look for, `Newest` otherwise
+    sort: Option<SearchSort>,
+    /// `nextCursor` of the page before
+    cursor: Option<String>,
+    /// Locale to name categories in, like `de` or `pt-BR`. Defaults to `en`
+    locale: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResponse {
+    listings: Vec<ListingResponse>,
+    /// Cursor of the next page, `null` on the last one
+    next_cursor: Option<String>,
+    /// Categories of the listings found, whatever category was searched
+    categories: Vec<CategoryFacet>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryFacet {
+    #[schema(value_type = String)]
+    id: Uuid,
+    slug: String,
+    /// In the locale asked for
+    name: String,
+    /// Listings found in the category and the ones below it
+    count: i64,
+}
+
+/// Where a page of results starts, for the sort it was made for.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Cursor {
+    sort: SearchSort,
+    key: f64,
+    id: Uuid,
+}
+
+impl Cursor {
+    fn encode(&self) -> Result<String, AppError> {
+        Ok(URL_SAFE_NO_PAD.encode(serde_json::to_vec(self)?))
+    }
+
+    fn decode(cursor: &str) -> Option<Self> {
+        let bytes = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+        serde_json::from_slice(&bytes).ok()
+    }
+}
+
+/// Search listings
+///
+/// Finds active listings by the words in their titles and descriptions, allowing for typos in
+/// titles, one page at a time. Counts of the listings found in each category are returned with
+/// every page.
+#[utoipa::path(
+    get,
+    path = "/search",
+    params(SearchQuery),
+    responses(
+        (status = 200, description = "Listings found", body = SearchResponse,
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 400, description = "The search or cursor is invalid"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = LISTINGS_TAG,
+)]
+pub async fn search(
+    Query(query): Query<SearchQuery>,
+    state: Data<AppState>,
+) -> Result<Response, AppError> {
+    let text = query.q.filter(|q| !q.trim().is_empty());
+    let sort = query.sort.unwrap_or(if text.is_some() {
+        SearchSort::Relevance
+    } else {
+        SearchSort::Newest
+    });
+
+    let after = match query.cursor.as_deref().map(Cursor::decode) {
+        None => None,
+        Some(Some(cursor)) if cursor.sort == sort => Some(SearchPosition {
+            sort_key: cursor.key,
+            id: cursor.id,
+        }),
+        Some(_) => return Ok(bad_request("cursor is invalid")),
+    };
+
+    let categories = state.category.category_tree().await?;
+    let category_ids = match query.category {
+        None => None,
+        Some(slug) => match categories.iter().find(|category| category.slug == slug) {
+            Some(category) => Some(descendant_ids(&categories, category.id)),
+            None => return Ok(bad_request("category is unknown")),
+        },
+    };
+
+    let search = SearchListings {
+        text,
+        category_ids,
+        currency: query.currency,
+        min_price: query.min_price,
+        max_price: query.max_price,
+        condition: query.condition,
+        is_local: query.local,
+        owner_id: query.seller,
+        sort,
+        after,
+        // one more to tell if there is a next page
+        limit: i64::from(PAGE_SIZE) + 1,
+    };
+
+    let mut hits = match state.listing.search_listings(&search).await {
+        Ok(hits) => hits,
+        Err(ListingError::Invalid(reason)) => return Ok(bad_request(reason)),
+        Err(e) => return Err(e.into()),
+    };
+    let counts = state.listing.search_category_counts(&search).await?;
+
+    let next_cursor = if hits.len() > PAGE_SIZE as usize {
+        hits.truncate(PAGE_SIZE as usize);
+        hits.last()
+            .map(|hit| {
+                Cursor {
+                    sort,
+                    key: hit.position.sort_key,
+                    id: hit.position.id,
+                }
+                .encode()
+            })
+            .transpose()?
+    } else {
+        None
+    };
+
+    let listings = hits.into_iter().map(|hit| hit.listing).collect();
+
+    Ok(Json(SearchResponse {
+        listings: listing_responses(listings, &state).await?,
+        next_cursor,
+        categories: category_facets(&categories, &counts, query.locale.as_deref()),
+    })
+    .into_response())
+}
+
+fn bad_request(reason: &str) -> Response {
+    debug!(reason, "invalid search");
+    (StatusCode::BAD_REQUEST, reason.to_owned()).into_response()
+}
+
+/// A price in a query string, written like [`Money`] displays, e.g. `12.50 EUR`.
+fn money<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Money>, D::Error> {
+    Option::<String>::deserialize(deserializer)?
+        .map(|price| price.parse().map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+/// Facets for the categories listings were found in, counting the ones found below them, in
+/// tree order.
+fn category_facets(
+    categories: &[Category],
+    counts: &[CategoryCount],
+    locale: Option<&str>,
+) -> Vec<CategoryFacet> {
+    categories
+        .iter()
+        .filter_map(|category| {
+            let ids = descendant_ids(categories, category.id);
+            let count: i64 = counts
+                .iter()
+                .filter(|count| ids.contains(&count.category_id))
+                .map(|count| count.count)
+                .sum();
+
+            (count > 0).then(|| CategoryFacet {
+                id: category.id,
+                slug: category.slug.clone(),
+                name: category.name(locale).to_owned(),
+                count,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use sqlx::PgPool;
+    use time::OffsetDateTime;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    fn category(id: u128, parent_id: Option<u128>) -> Category {
+        Category {
+            id: Uuid::from_u128(id),
+            parent_id: parent_id.map(Uuid::from_u128),
+            slug: format!("category-{id}"),
+            position: 0,
+            names: [("en".to_owned(), format!("Category {id}"))].into(),
+            created_at: OffsetDateTime::UNIX_EPOCH,
+            updated_at: OffsetDateTime::UNIX_EPOCH,
+        }
+    }
+
+    fn count(category_id: u128, count: i64) -> CategoryCount {
+        CategoryCount {
+            category_id: Uuid::from_u128(category_id),
+            count,
+        }
+    }
+
+    #[test]
+    fn facets_count_categories_below() {
+        let categories = [
+            category(1, None),
+            category(2, Some(1)),
+            category(3, Some(1)),
+            category(4, None),
+            category(5, None),
+        ];
+        let counts = [count(1, 1), count(2, 2), count(3, 3), count(4, 4)];
+
+        let facets: Vec<_> = category_facets(&categories, &counts, None)
+            .into_iter()
+            .map(|facet| (facet.slug, facet.count))
+            .collect();
+
+        assert_eq!(
+            vec![
+                ("category-1".to_owned(), 6),
+                ("category-2".to_owned(), 2),
+                ("category-3".to_owned(), 3),
+                ("category-4".to_owned(), 4),
+            ],
+            facets
+        );
+    }
+
+    #[test]
+    fn cursor_round_trip() {
+        let cursor = Cursor {
+            sort: SearchSort::PriceAsc,
+            key: -1250.0,
+            id: Uuid::now_v7(),
+        };
+
+        let encoded = cursor.encode().unwrap();
+
+        assert_eq!(Some(cursor), Cursor::decode(&encoded));
+        assert_eq!(None, Cursor::decode("not a cursor"));
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "requires a live db"]
+    async fn searches_publicly(pool: PgPool) -> anyhow::Result<()> {
+        let app = crate::test::test_app(pool).await;
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/search?q=bike&sort=PriceAsc&currency=EUR")
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "requires a live db"]
+    async fn searches_price_range(pool: PgPool) -> anyhow::Result<()> {
+        let app = crate::test::test_app(pool).await;
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/search?minPrice=10%20EUR&maxPrice=20.50%20EUR&sort=PriceAsc")
+                    .body(Body::empty())?,
+            )
+            .await?;
+        assert_eq!(StatusCode::OK, response.status());
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/search?minPrice=10")
+                    .body(Body::empty())?,
+            )
+            .await?;
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "requires a live db"]
+    async fn rejects_price_sort_without_currency(pool: PgPool) -> anyhow::Result<()> {
+        let app = crate::test::test_app(pool).await;
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/search?sort=PriceDesc")
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "requires a live db"]
+    async fn rejects_unknown_category(pool: PgPool) -> anyhow::Result<()> {
+        let app = crate::test::test_app(pool).await;
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/search?category=no-such-category")
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "requires a live db"]
+    async fn rejects_invalid_cursor(pool: PgPool) -> anyhow::Result<()> {
+        let app = crate::test::test_app(pool).await;
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/search?cursor=nope")
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+        Ok(())
+    }
+}
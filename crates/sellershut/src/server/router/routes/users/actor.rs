@@ -0,0 +1,215 @@
+use activitypub_federation::{
+    FEDERATION_CONTENT_TYPE, axum::json::FederationJson, config::Data,
+    protocol::context::WithContext, traits::Object,
+};
+use axum::{
+    extract::Path,
+    http::{HeaderMap, StatusCode, header::ACCEPT},
+    response::{IntoResponse, Redirect},
+};
+
+use crate::server::{
+    AppError,
+    entities::user::{Person, User},
+    router::routes::users::USERS_TAG,
+    state::AppState,
+    utilities::profile_url,
+};
+
+const LD_JSON_CONTENT_TYPE: &str = "application/ld+json";
+
+/// Get a local actor
+///
+/// Serves the ActivityPub representation of a local user. Browsers asking for
+/// `text/html` are redirected to the user's profile page on the frontend.
+#[utoipa::path(
+    get,
+    path = "/{username}",
+    params(
+        ("username" = String, Path, description = "Username of a local user")
+    ),
+    responses(
+        (status = 200, description = "ActivityPub actor", body = Person,
+            content_type = "application/activity+json",
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 303, description = "Redirects browsers to the frontend profile page",
+            headers(
+                (
+                    "Location" = String,
+                    description = "Frontend profile URL"
+                ),
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 404, description = "No local user with that username"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = USERS_TAG,
+)]
+pub async fn actor(
+    Path(username): Path<String>,
+    headers: HeaderMap,
+    state: Data<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let Some(user) = state.user.get_user(&username).await? else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+
+    if wants_html(&headers) {
+        let profile = profile_url(&state.frontend_url, &user.username)?;
+        return Ok(Redirect::to(profile.as_str()).into_response());
+    }
+
+    let person = User::from(user).into_json(&state).await?;
+
+    Ok(FederationJson(WithContext::new_default(person)).into_response())
+}
+
+/// Whether the client prefers an HTML page over an ActivityPub document.
+///
+/// Federated servers send one of the ActivityPub media types, so anything asking for those gets
+/// JSON even if it also lists `text/html`. Requests without an `Accept` header get JSON as well.
+fn wants_html(headers: &HeaderMap) -> bool {
+    !wants_activity(headers)
+        && accepted_media_types(headers).any(|media_type| media_type == "text/html")
+}
+
+/// Whether the client asks for an ActivityPub document.
+pub(crate) fn wants_activity(headers: &HeaderMap) -> bool {
+    accepted_media_types(headers).any(|media_type| {
+        media_type == FEDERATION_CONTENT_TYPE || media_type == LD_JSON_CONTENT_TYPE
+    })
+}
+
+fn accepted_media_types(headers: &HeaderMap) -> impl Iterator<Item = String> {
+    headers
+        .get_all(ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|media_type| {
+            media_type
+                .split(';')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_ascii_lowercase()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::Body,
+        http::{HeaderValue, Request},
+    };
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    #[track_caller]
+    fn check_wants_html(accept: &[&str], expected_result: bool) {
+        let mut headers = HeaderMap::new();
+        for value in accept {
+            headers.append(ACCEPT, HeaderValue::from_str(value).unwrap());
+        }
+
+        assert_eq!(expected_result, wants_html(&headers));
+    }
+
+    #[track_caller]
+    fn check_wants_activity(accept: &[&str], expected_result: bool) {
+        let mut headers = HeaderMap::new();
+        for value in accept {
+            headers.append(ACCEPT, HeaderValue::from_str(value).unwrap());
+        }
+
+        assert_eq!(expected_result, wants_activity(&headers));
+    }
+
+    async fn check_status(
+        app: Router,
+        uri: &str,
+        accept: &str,
+        expected_result: StatusCode,
+    ) -> anyhow::Result<()> {
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(uri)
+                    .header(ACCEPT, accept)
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        assert_eq!(expected_result, response.status());
+        Ok(())
+    }
+
+    #[test]
+    fn no_accept_header() {
+        check_wants_html(&[], false);
+    }
+
+    #[test]
+    fn activity_json() {
+        check_wants_html(&["application/activity+json"], false);
+        check_wants_html(
+            &[r#"application/ld+json; profile="https://www.w3.org/ns/activitystreams""#],
+            false,
+        );
+    }
+
+    #[test]
+    fn browser() {
+        check_wants_html(
+            &["text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8"],
+            true,
+        );
+        check_wants_html(&["TEXT/HTML"], true);
+    }
+
+    #[test]
+    fn activity_json_wins_over_html() {
+        check_wants_html(&["text/html, application/activity+json"], false);
+        check_wants_html(&["text/html", "application/activity+json"], false);
+    }
+
+    #[test]
+    fn wildcard() {
+        check_wants_html(&["*/*"], false);
+    }
+
+    #[test]
+    fn activity_has_to_be_asked_for() {
+        check_wants_activity(&[], false);
+        check_wants_activity(&["*/*"], false);
+        check_wants_activity(&["application/json"], false);
+        check_wants_activity(&["application/activity+json"], true);
+        check_wants_activity(&["text/html", "application/ld+json"], true);
+    }
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "requires a live db"]
+    async fn unknown_user(pool: PgPool) -> anyhow::Result<()> {
+        let app = crate::test::test_app(pool).await;
+        check_status(
+            app,
+            "/users/nobody",
+            FEDERATION_CONTENT_TYPE,
+            StatusCode::NOT_FOUND,
+        )
+        .await
+    }
+}
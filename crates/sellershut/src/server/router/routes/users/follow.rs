@@ -0,0 +1,247 @@
+use activitypub_federation::{config::Data, fetch::object_id::ObjectId};
+use axum::{
+    Json,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use axum_extra::{
+    TypedHeader,
+    headers::{Authorization, authorization::Bearer},
+};
+use sellershut_core::user::FollowState;
+use sellershut_users::error::UserError;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+use url::Url;
+use utoipa::ToSchema;
+
+use crate::server::{
+    AppError,
+    activities::{accept, follow, reject, undo},
+    entities::user::User,
+    router::routes::{session_user, users::USERS_TAG},
+    state::AppState,
+};
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct FollowRequest {
+    /// ActivityPub id of the other actor
+    actor: Url,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FollowResponse {
+    state: FollowState,
+}
+
+/// Follow an actor
+///
+/// Local actors are followed straight away. Remote actors are fetched if they are not known
+/// yet and sent a `Follow`, the follow stays pending until they accept it.
+#[utoipa::path(
+    post,
+    path = "/me/following",
+    security(
+        ("bearer_auth" = [])
+    ),
+    request_body = FollowRequest,
+    responses(
+        (status = 200, description = "Follow state", body = FollowResponse,
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 400, description = "Users cannot follow themselves"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Actor could not be resolved"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = USERS_TAG,
+)]
+pub async fn follow(
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    state: Data<AppState>,
+    Json(body): Json<FollowRequest>,
+) -> Result<Response, AppError> {
+    let Some(user) = session_user(bearer.token(), &state).await.map(User::from) else {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    };
+
+    let followee = match ObjectId::<User>::from(body.actor).dereference(&state).await {
+        Ok(followee) => followee,
+        Err(e) => {
+            debug!(err = %e.0, "actor could not be resolved");
+            return Ok(StatusCode::NOT_FOUND.into_response());
+        }
+    };
+
+    match follow::follow(&user, &followee, &state).await {
+        Ok(follow) => Ok(Json(FollowResponse {
+            state: follow.state,
+        })
+        .into_response()),
+        Err(e) if matches!(e.0.downcast_ref(), Some(UserError::SelfFollow)) => {
+            Ok(StatusCode::BAD_REQUEST.into_response())
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Unfollow an actor
+#[utoipa::path(
+    delete,
+    path = "/me/following",
+    security(
+        ("bearer_auth" = [])
+    ),
+    request_body = FollowRequest,
+    responses(
+        (status = 204, description = "Follow removed",
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Not following that actor"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = USERS_TAG,
+)]
+pub async fn unfollow(
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    state: Data<AppState>,
+    Json(body): Json<FollowRequest>,
+) -> Result<Response, AppError> {
+    let Some(user) = session_user(bearer.token(), &state).await.map(User::from) else {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    };
+    let Some(followee) = state.user.get_user_by_id(&body.actor).await? else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+
+    match undo::unfollow(&user, &User::from(followee), &state).await? {
+        Some(_) => Ok(StatusCode::NO_CONTENT.into_response()),
+        None => Ok(StatusCode::NOT_FOUND.into_response()),
+    }
+}
+
+/// Accept a follower
+///
+/// Accepting an already accepted follower sends the `Accept` again.
+#[utoipa::path(
+    post,
+    path = "/me/followers/accept",
+    security(
+        ("bearer_auth" = [])
+    ),
+    request_body = FollowRequest,
+    responses(
+        (status = 200, description = "Follow state", body = FollowResponse,
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "No follow request from that actor"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = USERS_TAG,
+)]
+pub async fn accept_follower(
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    state: Data<AppState>,
+    Json(body): Json<FollowRequest>,
+) -> Result<Response, AppError> {
+    let Some(user) = session_user(bearer.token(), &state).await.map(User::from) else {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    };
+    let Some(follower) = state.user.get_user_by_id(&body.actor).await? else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+
+    match accept::accept(&User::from(follower), &user, &state).await? {
+        Some(follow) => Ok(Json(FollowResponse {
+            state: follow.state,
+        })
+        .into_response()),
+        None => Ok(StatusCode::NOT_FOUND.into_response()),
+    }
+}
+
+/// Reject a follower
+///
+/// Turns down a pending follow request, or removes a follower that was already accepted.
+#[utoipa::path(
+    post,
+    path = "/me/followers/reject",
+    security(
+        ("bearer_auth" = [])
+    ),
+    request_body = FollowRequest,
+    responses(
+        (status = 204, description = "Follow removed",
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "No follow from that actor"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = USERS_TAG,
+)]
+pub async fn reject_follower(
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    state: Data<AppState>,
+    Json(body): Json<FollowRequest>,
+) -> Result<Response, AppError> {
+    let Some(user) = session_user(bearer.token(), &state).await.map(User::from) else {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    };
+    let Some(follower) = state.user.get_user_by_id(&body.actor).await? else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+
+    match reject::reject(&User::from(follower), &user, &state).await? {
+        Some(_) => Ok(StatusCode::NO_CONTENT.into_response()),
+        None => Ok(StatusCode::NOT_FOUND.into_response()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "requires a live db"]
+    async fn follow_without_session(pool: PgPool) -> anyhow::Result<()> {
+        let app = crate::test::test_app(pool).await;
+        let response = app
+            .oneshot(
+                Request::post("/users/me/following")
+                    .header("content-type", "application/json")
+                    .header("authorization", "Bearer not-a-session")
+                    .body(Body::from(r#"{"actor":"https://other.hut/users/bob"}"#))?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::UNAUTHORIZED, response.status());
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+use activitypub_federation::{axum::inbox::ActivityData, config::Data};
+use axum::{
+    extract::Path,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+
+use crate::server::{AppError, activities, router::routes::users::USERS_TAG, state::AppState};
+
+/// Deliver an activity to a local user
+///
+/// The request must carry an HTTP signature made with the sending actor's key.
+#[utoipa::path(
+    post,
+    path = "/{username}/inbox",
+    params(
+        ("username" = String, Path, description = "Username of a local user")
+    ),
+    request_body(
+        content = serde_json::Value,
+        content_type = "application/activity+json",
+        description = "A signed Follow, Undo, Accept, Reject, Create, Update or Delete activity"
+    ),
+    responses(
+        (status = 202, description = "Activity accepted",
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 400, description = "Malformed or unsupported activity"),
+        (status = 401, description = "Missing or invalid HTTP signature"),
+        (status = 404, description = "No local user with that username"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = USERS_TAG,
+)]
+pub async fn inbox(
+    Path(username): Path<String>,
+    headers: HeaderMap,
+    state: Data<AppState>,
+    activity_data: ActivityData,
+) -> Result<Response, AppError> {
+    if state.user.get_user(&username).await?.is_none() {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    }
+
+    Ok(activities::receive(&headers, activity_data, &state).await)
+}
@@ -1,16 +1,20 @@
 use activitypub_federation::{
     axum::json::FederationJson, config::Data, protocol::context::WithContext, traits::Object,
 };
-use axum::{http::StatusCode, response::IntoResponse};
+use axum::{Json, http::StatusCode, response::IntoResponse};
 use axum_extra::{
     TypedHeader,
     headers::{Authorization, authorization::Bearer},
 };
+use serde::Deserialize;
 use tracing::debug;
+use url::Url;
+use utoipa::ToSchema;
 
 use crate::server::{
+    activities::{delete, update},
     entities::user::{Person, User},
-    router::routes::users::USERS_TAG,
+    router::routes::{present, users::USERS_TAG},
     state::AppState,
 };
 /// Get current user
@@ -58,3 +62,171 @@ pub async fn me(
         }
     }
 }
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateMe {
+    /// Keep the followers and following lists private
+    hide_network: Option<bool>,
+    /// Display name, `null` removes it
+    #[serde(default, deserialize_with = "present")]
+    #[schema(value_type = Option<String>)]
+    name: Option<Option<String>>,
+    /// Profile picture, `null` removes it
+    #[serde(default, deserialize_with = "present")]
+    #[schema(value_type = Option<String>, format = Uri)]
+    avatar: Option<Option<Url>>,
+}
+
+/// Update current user
+#[utoipa::path(
+    patch,
+    path = "/me",
+    security(
+        ("bearer_auth" = [])
+
+    ),
+    request_body = UpdateMe,
+    responses(
+        (status = 200, description = "Updated user", body = Person,
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = USERS_TAG,
+)]
+pub async fn update_me(
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    state: Data<AppState>,
+    Json(body): Json<UpdateMe>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let mut user = match state.user.user_from_session(bearer.token()).await {
+        Ok(user) => user,
+        Err(e) => {
+            debug!(err=?e, "unauthorised session");
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+    };
+
+    if let Some(hide_network) = body.hide_network {
+        user = state
+            .user
+            .set_hide_network(user.id, hide_network)
+            .await
+            .map_err(|e| {
+                tracing::error!(error=?e, "user update failed");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+    }
+
+    if body.name.is_some() || body.avatar.is_some() {
+        let name = body.name.unwrap_or_else(|| user.name.clone());
+        let avatar = match body.avatar {
+            Some(avatar) => avatar,
+            None => user.avatar.as_deref().and_then(|url| Url::parse(url).ok()),
+        };
+
+        user = state
+            .user
+            .update_profile(user.id, name.as_deref(), avatar.as_ref())
+            .await
+            .map_err(|e| {
+                tracing::error!(error=?e, "user update failed");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+
+        if let Err(e) = update::update_actor(&User::from(user.clone()), &state).await {
+            tracing::error!(error = %e.0, "sending profile update failed");
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    }
+
+    match User::from(user).into_json(&state).await {
+        Ok(u) => Ok(FederationJson(WithContext::new_default(u)).into_response()),
+        Err(e) => {
+            tracing::error!(error=?e, "user decode failed");
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Delete current user
+///
+/// Followers' servers are sent a `Delete`. The username stays taken.
+#[utoipa::path(
+    delete,
+    path = "/me",
+    security(
+        ("bearer_auth" = [])
+
+    ),
+    responses(
+        (status = 204, description = "Account deleted",
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = USERS_TAG,
+)]
+pub async fn delete_me(
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    state: Data<AppState>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let user = match state.user.user_from_session(bearer.token()).await {
+        Ok(user) => user,
+        Err(e) => {
+            debug!(err=?e, "unauthorised session");
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+    };
+
+    if let Err(e) = delete::delete_actor(&User::from(user), &state).await {
+        tracing::error!(error = %e.0, "account deletion failed");
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn null_clears_field() {
+        let body: UpdateMe = serde_json::from_value(serde_json::json!({
+            "name": null,
+            "avatar": "https://some.hut/media/avatar.png"
+        }))
+        .unwrap();
+
+        assert_eq!(Some(None), body.name);
+        assert_eq!(
+            Some(Some("https://some.hut/media/avatar.png")),
+            body.avatar
+                .as_ref()
+                .map(|avatar| avatar.as_ref().map(Url::as_str))
+        );
+        assert_eq!(None, body.hide_network);
+    }
+
+    #[test]
+    fn missing_field_is_kept() {
+        let body: UpdateMe = serde_json::from_value(serde_json::json!({})).unwrap();
+
+        assert_eq!(None, body.name);
+        assert_eq!(None, body.avatar);
+    }
+}
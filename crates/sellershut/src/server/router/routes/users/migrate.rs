@@ -0,0 +1,184 @@
+use activitypub_federation::{
+    axum::json::FederationJson, config::Data, fetch::object_id::ObjectId,
+    protocol::context::WithContext, traits::Object,
+};
+use axum::{
+    Json,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use axum_extra::{
+    TypedHeader,
+    headers::{Authorization, authorization::Bearer},
+};
+use serde::Deserialize;
+use tracing::debug;
+use url::Url;
+use utoipa::ToSchema;
+
+use crate::server::{
+    AppError,
+    activities::{migrate, update},
+    entities::user::{Person, User},
+    router::routes::{session_user, users::USERS_TAG},
+    state::AppState,
+};
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SetAliases {
+    /// Actors that are the same person as the current user, replacing the current list
+    #[schema(value_type = Vec<String>, format = Uri)]
+    aliases: Vec<Url>,
+}
+
+/// Set the current user's aliases
+///
+/// Aliases are published as `alsoKnownAs`. An account moving here has to be listed first.
+#[utoipa::path(
+    put,
+    path = "/me/aliases",
+    security(
+        ("bearer_auth" = [])
+    ),
+    request_body = SetAliases,
+    responses(
+        (status = 200, description = "Updated user", body = Person,
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 401, description = "Unauthorized"),
+        (status = 422, description = "An alias could not be fetched"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = USERS_TAG,
+)]
+pub async fn set_aliases(
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    state: Data<AppState>,
+    Json(body): Json<SetAliases>,
+) -> Result<Response, AppError> {
+    let Some(user) = session_user(bearer.token(), &state).await.map(User::from) else {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    };
+
+    let mut aliases = Vec::with_capacity(body.aliases.len());
+    for alias in body.aliases {
+        match ObjectId::<User>::from(alias.clone())
+            .dereference(&state)
+            .await
+        {
+            Ok(actor) => aliases.push(actor.data().ap_id.inner()),
+            Err(e) => {
+                debug!(%alias, err = %e.0, "alias could not be fetched");
+                return Ok(StatusCode::UNPROCESSABLE_ENTITY.into_response());
+            }
+        }
+    }
+
+    let user = User::from(state.user.set_aliases(user.data().id, &aliases).await?);
+    update::update_actor(&user, &state).await?;
+
+    let person = user.into_json(&state).await?;
+
+    Ok(FederationJson(WithContext::new_default(person)).into_response())
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct MoveAccount {
+    /// The account to move to, it must list the current user as an alias
+    #[schema(value_type = String, format = Uri)]
+    target: Url,
+}
+
+/// Move the current user to another account
+///
+/// Followers' servers are sent a `Move`, followers on this instance are moved straight away.
+#[utoipa::path(
+    post,
+    path = "/me/move",
+    security(
+        ("bearer_auth" = [])
+    ),
+    request_body = MoveAccount,
+    responses(
+        (status = 200, description = "Moved user", body = Person,
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 401, description = "Unauthorized"),
+        (status = 409, description = "The account has already moved"),
+        (status = 422, description = "The target could not be fetched or does not list the user as an alias"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = USERS_TAG,
+)]
+pub async fn move_me(
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    state: Data<AppState>,
+    Json(body): Json<MoveAccount>,
+) -> Result<Response, AppError> {
+    let Some(user) = session_user(bearer.token(), &state).await.map(User::from) else {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    };
+
+    if user.data().moved_to.is_some() {
+        return Ok(StatusCode::CONFLICT.into_response());
+    }
+
+    // Fetched again, the aliases were most likely changed just before moving.
+    let target = match ObjectId::<User>::from(body.target.clone())
+        .dereference_forced(&state)
+        .await
+    {
+        Ok(target) => target,
+        Err(e) => {
+            debug!(target = %body.target, err = %e.0, "move target could not be fetched");
+            return Ok(StatusCode::UNPROCESSABLE_ENTITY.into_response());
+        }
+    };
+
+    let Some(user) = migrate::move_account(&user, &target, &state).await? else {
+        debug!(target = %body.target, "move target does not list the user as an alias");
+        return Ok(StatusCode::UNPROCESSABLE_ENTITY.into_response());
+    };
+
+    let person = user.into_json(&state).await?;
+
+    Ok(FederationJson(WithContext::new_default(person)).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "requires a live db"]
+    async fn move_requires_session(pool: PgPool) -> anyhow::Result<()> {
+        let app = crate::test::test_app(pool).await;
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/users/me/move")
+                    .header("authorization", "Bearer not-a-session")
+                    .header("content-type", "application/json")
+                    .body(Body::from(r#"{"target":"https://other.hut/users/seller"}"#))?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::UNAUTHORIZED, response.status());
+        Ok(())
+    }
+}
@@ -1,12 +1,31 @@
 use utoipa::OpenApi;
 use utoipa_axum::router::OpenApiRouter;
 
+pub mod actor;
+pub mod follow;
+pub mod inbox;
 pub mod me;
+pub mod migrate;
+pub mod network;
+pub mod outbox;
+pub mod resolve;
 
 pub fn router() -> OpenApiRouter {
     let router = OpenApiRouter::new();
 
-    router.routes(utoipa_axum::routes!(me::me))
+    router
+        .routes(utoipa_axum::routes!(me::me, me::update_me, me::delete_me))
+        .routes(utoipa_axum::routes!(migrate::set_aliases))
+        .routes(utoipa_axum::routes!(migrate::move_me))
+        .routes(utoipa_axum::routes!(resolve::resolve))
+        .routes(utoipa_axum::routes!(actor::actor))
+        .routes(utoipa_axum::routes!(inbox::inbox))
+        .routes(utoipa_axum::routes!(network::followers))
+        .routes(utoipa_axum::routes!(network::following))
+        .routes(utoipa_axum::routes!(outbox::outbox))
+        .routes(utoipa_axum::routes!(follow::follow, follow::unfollow))
+        .routes(utoipa_axum::routes!(follow::accept_follower))
+        .routes(utoipa_axum::routes!(follow::reject_follower))
 }
 
 const USERS_TAG: &str = "Users";
@@ -0,0 +1,160 @@
+use activitypub_federation::{
+    axum::json::FederationJson, config::Data, protocol::context::WithContext,
+};
+use axum::{
+    extract::{Path, Query},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+
+use crate::server::{
+    AppError,
+    entities::{
+        collection::{OrderedCollection, OrderedCollectionPage, PAGE_SIZE, PageQuery},
+        user::User,
+    },
+    router::routes::users::USERS_TAG,
+    state::AppState,
+};
+
+#[derive(Clone, Copy)]
+enum Network {
+    Followers,
+    Following,
+}
+
+/// Get a local user's followers
+///
+/// Returns an `OrderedCollection`, or one of its `OrderedCollectionPage`s when `page` is given.
+/// Users that hide their network only expose the number of followers.
+#[utoipa::path(
+    get,
+    path = "/{username}/followers",
+    params(
+        ("username" = String, Path, description = "Username of a local user"),
+        PageQuery,
+    ),
+    responses(
+        (status = 200, description = "Followers collection", body = OrderedCollection,
+            content_type = "application/activity+json",
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 404, description = "No local user with that username"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = USERS_TAG,
+)]
+pub async fn followers(
+    Path(username): Path<String>,
+    Query(query): Query<PageQuery>,
+    state: Data<AppState>,
+) -> Result<Response, AppError> {
+    collection(&username, Network::Followers, query, &state).await
+}
+
+/// Get the users a local user follows
+///
+/// Returns an `OrderedCollection`, or one of its `OrderedCollectionPage`s when `page` is given.
+/// Users that hide their network only expose the number of followees.
+#[utoipa::path(
+    get,
+    path = "/{username}/following",
+    params(
+        ("username" = String, Path, description = "Username of a local user"),
+        PageQuery,
+    ),
+    responses(
+        (status = 200, description = "Following collection", body = OrderedCollection,
+            content_type = "application/activity+json",
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 404, description = "No local user with that username"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = USERS_TAG,
+)]
+pub async fn following(
+    Path(username): Path<String>,
+    Query(query): Query<PageQuery>,
+    state: Data<AppState>,
+) -> Result<Response, AppError> {
+    collection(&username, Network::Following, query, &state).await
+}
+
+async fn collection(
+    username: &str,
+    network: Network,
+    query: PageQuery,
+    state: &Data<AppState>,
+) -> Result<Response, AppError> {
+    let Some(user) = state.user.get_user(username).await? else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+    let user_id = user.id;
+    let public = !user.hide_network;
+    let user = User::from(user);
+
+    let (id, total_items) = match network {
+        Network::Followers => (
+            user.collection_url("followers")?,
+            state.user.count_followers(user_id).await?,
+        ),
+        Network::Following => (
+            user.collection_url("following")?,
+            state.user.count_following(user_id).await?,
+        ),
+    };
+
+    let page = match query.page {
+        Some(page) if public => page,
+        _ => {
+            let collection = OrderedCollection::new(id, total_items, public);
+            return Ok(FederationJson(WithContext::new_default(collection)).into_response());
+        }
+    };
+
+    let limit = i64::from(PAGE_SIZE);
+    let offset = PageQuery::offset(page);
+    let items = match network {
+        Network::Followers => state.user.followers(user_id, limit, offset).await?,
+        Network::Following => state.user.following(user_id, limit, offset).await?,
+    };
+    let page = OrderedCollectionPage::new(id, page, total_items, items);
+
+    Ok(FederationJson(WithContext::new_default(page)).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "requires a live db"]
+    async fn unknown_user(pool: PgPool) -> anyhow::Result<()> {
+        let app = crate::test::test_app(pool).await;
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/users/nobody/followers")
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+        Ok(())
+    }
+}
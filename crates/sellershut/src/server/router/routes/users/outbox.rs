@@ -0,0 +1,95 @@
+use activitypub_federation::{
+    axum::json::FederationJson, config::Data, protocol::context::WithContext,
+};
+use axum::{
+    extract::{Path, Query},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+
+use crate::server::{
+    AppError,
+    entities::{
+        collection::{OrderedCollection, OrderedCollectionPage, PAGE_SIZE, PageQuery},
+        user::User,
+    },
+    router::routes::users::USERS_TAG,
+    state::AppState,
+};
+
+/// Get a local user's outbox
+///
+/// Lists the public activities a user has sent, newest first. Returns an `OrderedCollection`,
+/// or one of its `OrderedCollectionPage`s when `page` is given.
+#[utoipa::path(
+    get,
+    path = "/{username}/outbox",
+    params(
+        ("username" = String, Path, description = "Username of a local user"),
+        PageQuery,
+    ),
+    responses(
+        (status = 200, description = "Outbox collection", body = OrderedCollection,
+            content_type = "application/activity+json",
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 404, description = "No local user with that username"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = USERS_TAG,
+)]
+pub async fn outbox(
+    Path(username): Path<String>,
+    Query(query): Query<PageQuery>,
+    state: Data<AppState>,
+) -> Result<Response, AppError> {
+    let Some(user) = state.user.get_user(&username).await? else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+    let user_id = user.id;
+    let id = User::from(user).collection_url("outbox")?;
+    let total_items = state.federation.count_outbox(user_id).await?;
+
+    let Some(page) = query.page else {
+        let collection = OrderedCollection::new(id, total_items, true);
+        return Ok(FederationJson(WithContext::new_default(collection)).into_response());
+    };
+
+    let items = state
+        .federation
+        .outbox(user_id, i64::from(PAGE_SIZE), PageQuery::offset(page))
+        .await?;
+    let page = OrderedCollectionPage::new(id, page, total_items, items);
+
+    Ok(FederationJson(WithContext::new_default(page)).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "requires a live db"]
+    async fn unknown_user(pool: PgPool) -> anyhow::Result<()> {
+        let app = crate::test::test_app(pool).await;
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/users/nobody/outbox")
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+use activitypub_federation::{
+    axum::json::FederationJson, config::Data, fetch::webfinger::webfinger_resolve_actor,
+    protocol::context::WithContext, traits::Object,
+};
+use axum::{
+    extract::Query,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use axum_extra::{
+    TypedHeader,
+    headers::{Authorization, authorization::Bearer},
+};
+use sellershut_federation::normalize_domain;
+use serde::Deserialize;
+use tracing::debug;
+use utoipa::IntoParams;
+
+use crate::server::{
+    AppError,
+    entities::user::{Person, User},
+    router::routes::{session_user, users::USERS_TAG},
+    state::AppState,
+};
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ResolveQuery {
+    /// Handle of the actor, with or without the leading `@`
+    #[param(example = "@seller@other.hut")]
+    handle: String,
+}
+
+/// Resolve an actor by handle
+///
+/// Local handles are looked up directly. Remote ones go through WebFinger on their domain,
+/// and the actor is fetched and stored if it is not known yet.
+#[utoipa::path(
+    get,
+    path = "/resolve",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ResolveQuery
+    ),
+    responses(
+        (status = 200, description = "The actor", body = Person,
+            headers(
+                (
+                    "x-request-id" = String,
+                    description = "Unique identifier for the request"
+                )
+            )
+         ),
+        (status = 400, description = "Not a handle"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "The actor's domain is blocked"),
+        (status = 404, description = "Actor could not be resolved"),
+        (status = 429, description = "Too many lookups"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = USERS_TAG,
+)]
+pub async fn resolve(
+    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    Query(query): Query<ResolveQuery>,
+    state: Data<AppState>,
+) -> Result<Response, AppError> {
+    let Some(user) = session_user(bearer.token(), &state).await.map(User::from) else {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    };
+
+    let Some((username, domain)) = parse_handle(&query.handle) else {
+        return Ok(StatusCode::BAD_REQUEST.into_response());
+    };
+
+    let actor = if normalize_domain(domain) == normalize_domain(state.domain()) {
+        match state.user.get_user(username).await? {
+            Some(actor) => User::from(actor),
+            None => return Ok(StatusCode::NOT_FOUND.into_response()),
+        }
+    } else {
+        if !state.resolve_limiter.check(user.data().id) {
+            return Ok(StatusCode::TOO_MANY_REQUESTS.into_response());
+        }
+
+        if let Some(reason) = state.domain_rejection(domain).await? {
+            debug!(domain, reason, "refusing to resolve actor");
+            return Ok(StatusCode::FORBIDDEN.into_response());
+        }
+
+        let handle = format!("{username}@{domain}");
+        match webfinger_resolve_actor::<AppState, User>(&handle, &state).await {
+            Ok(actor) => actor,
+            Err(e) => {
+                debug!(handle, err = %e.0, "actor could not be resolved");
+                return Ok(StatusCode::NOT_FOUND.into_response());
+            }
+        }
+    };
+
+    let person = actor.into_json(&state).await?;
+
+    Ok(FederationJson(WithContext::new_default(person)).into_response())
+}
+
+/// Split `@username@domain` into its parts. The leading `@` and an `acct:` scheme are optional.
+fn parse_handle(handle: &str) -> Option<(&str, &str)> {
+    let handle = handle.trim();
+    let handle = handle.strip_prefix("acct:").unwrap_or(handle);
+    let handle = handle.strip_prefix('@').unwrap_or(handle);
+
+    let (username, domain) = handle.split_once('@')?;
+
+    if username.is_empty() || domain.is_empty() || domain.contains(['@', '/', '?', '#']) {
+        return None;
+    }
+
+    Some((username, domain))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn check_parse_handle(handle: &str, expected_result: Option<(&str, &str)>) {
+        assert_eq!(expected_result, parse_handle(handle));
+    }
+
+    #[test]
+    fn handles() {
+        check_parse_handle("@seller@other.hut", Some(("seller", "other.hut")));
+        check_parse_handle("seller@other.hut", Some(("seller", "other.hut")));
+        check_parse_handle("acct:seller@other.hut", Some(("seller", "other.hut")));
+        check_parse_handle(
+            " @seller@localhost:2210 ",
+            Some(("seller", "localhost:2210")),
+        );
+        check_parse_handle("@seller", None);
+        check_parse_handle("@seller@", None);
+        check_parse_handle("@@other.hut", None);
+        check_parse_handle("@seller@other.hut/users", None);
+        check_parse_handle("@seller@other@hut", None);
+    }
+}
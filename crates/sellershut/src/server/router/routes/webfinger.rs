@@ -1,12 +1,17 @@
 use activitypub_federation::{
     config::Data,
-    fetch::webfinger::{build_webfinger_response, extract_webfinger_name},
+    fetch::webfinger::{WebfingerLink, build_webfinger_response, extract_webfinger_name},
 };
 use axum::{Json, extract::Query, http::StatusCode, response::IntoResponse};
 use serde::{Deserialize, Serialize};
+use url::Url;
 use utoipa::{IntoParams, ToSchema};
 
-use crate::server::{AppError, state::AppState};
+use crate::server::{AppError, state::AppState, utilities::profile_url};
+
+const PROFILE_PAGE_REL: &str = "http://webfinger.net/rel/profile-page";
+const AVATAR_REL: &str = "http://webfinger.net/rel/avatar";
+
 /// Webfinger
 #[utoipa::path(
     get,
@@ -29,7 +34,7 @@ use crate::server::{AppError, state::AppState};
         ),
         (
             status = 400,
-            description = "Webfinger name could not be extracted",
+            description = "Resource is neither an actor url nor an acct: URI on this domain",
             headers(
                 (
                     "x-request-id" = String,
@@ -39,7 +44,7 @@ use crate::server::{AppError, state::AppState};
         ),
         (
             status = 404,
-            description = "No information is available for the requested resource",
+            description = "Resource is not a local user",
             headers(
                 (
                     "x-request-id" = String,
@@ -55,28 +60,77 @@ pub async fn webfinger(
     Query(query): Query<WebFingerQuery>,
     state: Data<AppState>,
 ) -> Result<impl IntoResponse, AppError> {
-    if let Ok(name) = extract_webfinger_name(&query.resource, &state) {
-        match state.user.get_user(name).await? {
-            Some(u) => {
-                let mut resp =
-                    Json(build_webfinger_response(query.resource, u.ap_id.inner())).into_response();
-                resp.headers_mut().insert(
-                    axum::http::header::CONTENT_TYPE,
-                    "application/jrd+json".parse().unwrap(),
-                );
-                Ok(resp)
-            }
-            None => Ok((StatusCode::NOT_FOUND).into_response()),
-        }
-    } else {
-        Ok((StatusCode::BAD_REQUEST).into_response())
+    let user = match actor_url(&query.resource) {
+        // Only local actors are described here, remote ones are up to their own server
+        Some(url) => state
+            .user
+            .get_user_by_id(&url)
+            .await?
+            .filter(|user| user.is_local && user.deleted_at.is_none()),
+        None => match extract_webfinger_name(&query.resource, &state) {
+            Ok(name) => state.user.get_user(name).await?,
+            Err(_) => return Ok((StatusCode::BAD_REQUEST).into_response()),
+        },
+    };
+
+    let Some(u) = user else {
+        return Ok((StatusCode::NOT_FOUND).into_response());
+    };
+
+    let ap_id = u.ap_id.inner();
+    let profile = profile_url(&state.frontend_url, &u.username)?;
+    let subject = format!("acct:{}@{}", u.username, state.domain());
+
+    let mut webfinger = build_webfinger_response(subject, ap_id.clone());
+    webfinger.aliases = vec![ap_id, profile.clone()];
+    webfinger.links.push(WebfingerLink {
+        rel: Some(PROFILE_PAGE_REL.to_owned()),
+        kind: Some("text/html".to_owned()),
+        href: Some(profile),
+        ..Default::default()
+    });
+    if let Some(avatar) = u.avatar.as_deref().and_then(|url| Url::parse(url).ok()) {
+        webfinger.links.push(WebfingerLink {
+            rel: Some(AVATAR_REL.to_owned()),
+            kind: avatar_media_type(&avatar).map(str::to_owned),
+            href: Some(avatar),
+            ..Default::default()
+        });
+    }
+
+    let mut resp = Json(webfinger).into_response();
+    resp.headers_mut().insert(
+        axum::http::header::CONTENT_TYPE,
+        "application/jrd+json".parse().unwrap(),
+    );
+    Ok(resp)
+}
+
+/// The resource as an actor url, when it is given as one instead of an `acct:` URI.
+fn actor_url(resource: &str) -> Option<Url> {
+    Url::parse(resource)
+        .ok()
+        .filter(|url| matches!(url.scheme(), "http" | "https"))
+}
+
+/// Media type of an avatar, going by the extension of its url.
+fn avatar_media_type(url: &Url) -> Option<&'static str> {
+    let (_, extension) = url.path().rsplit_once('.')?;
+
+    match extension.to_ascii_lowercase().as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        "avif" => Some("image/avif"),
+        _ => None,
     }
 }
 
 #[derive(Debug, Deserialize, IntoParams)]
 #[into_params(parameter_in = Query)]
 pub struct WebFingerQuery {
-    /// URI identifying the resource being queried.
+    /// URI identifying the resource being queried, either an `acct:` URI or the actor's url.
     #[param(example = "acct:seller@some.hut")]
     pub resource: String,
 }
@@ -87,6 +141,10 @@ pub struct WebFingerResponse {
     #[schema(example = "acct:seller@some.hut")]
     pub subject: String,
 
+    /// Other URIs identifying the same resource, like the actor and its profile page.
+    #[schema(example = json!(["https://some.hut/users/seller", "https://some.hut/@seller"]))]
+    pub aliases: Vec<String>,
+
     /// Links associated with the requested resource.
     pub links: Vec<WebFingerLink>,
 }
@@ -102,7 +160,7 @@ pub struct WebFingerLink {
     /// The media type of the linked resource.
     #[schema(example = "application/activity+json")]
     #[serde(rename = "type")]
-    pub kind: String,
+    pub kind: Option<String>,
 
     /// The URL of the linked resource.
     #[schema(example = "https://some.hut/@user")]
@@ -113,3 +171,56 @@ pub struct WebFingerLink {
     #[schema(example = "https://some.hut/users/seller")]
     pub template: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    #[sqlx::test(migrations = "../../migrations")]
+    #[ignore = "requires a live db"]
+    async fn unknown_actor_url(pool: PgPool) -> anyhow::Result<()> {
+        let app = crate::test::test_app(pool).await;
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/.well-known/webfinger?resource=https%3A%2F%2Fother.hut%2Fusers%2Fseller")
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+        Ok(())
+    }
+
+    #[track_caller]
+    fn check_avatar_media_type(url: &str, expected_result: Option<&str>) {
+        assert_eq!(
+            expected_result,
+            avatar_media_type(&Url::parse(url).unwrap())
+        );
+    }
+
+    #[test]
+    fn actor_urls() {
+        assert_eq!(
+            Some("https://some.hut/users/seller"),
+            actor_url("https://some.hut/users/seller")
+                .as_ref()
+                .map(Url::as_str)
+        );
+        assert_eq!(None, actor_url("acct:seller@some.hut"));
+        assert_eq!(None, actor_url("mailto:seller@some.hut"));
+    }
+
+    #[test]
+    fn avatar_media_types() {
+        check_avatar_media_type("https://some.hut/media/avatar.PNG", Some("image/png"));
+        check_avatar_media_type("https://some.hut/media/avatar.jpeg", Some("image/jpeg"));
+        check_avatar_media_type("https://some.hut/media/avatar", None);
+        check_avatar_media_type("https://some.hut/media/avatar.svg", None);
+    }
+}
@@ -1,49 +1,171 @@
-use std::sync::Arc;
+mod rate_limit;
+
+use std::{collections::HashSet, sync::Arc, time::Duration};
 
 use sellershut_auth::{AuthService, OauthDriver};
-use sellershut_core::{RedactedSecret, user::ActorType};
+use sellershut_categories::CategoryDriver;
+use sellershut_core::{RedactedSecret, activity::BlockSeverity, user::ActorType};
+use sellershut_federation::{
+    FederationDriver, FederationService, domain_and_parents, error::FederationError,
+    normalize_domain,
+};
+use sellershut_listings::ListingDriver;
+use sellershut_media::{MediaDriver, MediaService, store::MediaStore};
 use sellershut_users::{CreateUser, UserDriver};
 use sqlx::PgPool;
+use url::Url;
 
 use crate::{
-    config::Configuration,
+    config::{Configuration, server::FederationMode},
     server::{self, entities::user::User},
 };
 
+pub use rate_limit::RateLimiter;
+
 #[derive(Clone)]
 pub struct State {
     pub auth: Arc<dyn OauthDriver>,
     pub user: Arc<dyn UserDriver>,
+    pub listing: Arc<dyn ListingDriver>,
+    pub category: Arc<dyn CategoryDriver>,
+    pub media: Arc<dyn MediaDriver>,
+    pub media_store: Arc<dyn MediaStore>,
+    pub federation: Arc<dyn FederationDriver>,
     pub system_user: Arc<User>,
     pub port: u16,
+    pub frontend_url: Url,
+    /// Largest file that can be uploaded, in bytes.
+    pub max_upload_size: usize,
+    /// Usernames of local users allowed to use the admin API.
+    pub admins: HashSet<String>,
+    /// Domains federated with when running in allowlist mode, `None` otherwise.
+    pub allowlist: Option<HashSet<String>>,
+    /// Age after which a remote actor is fetched again.
+    pub actor_stale_after: time::Duration,
+    /// How long a rotated out public key is still served.
+    pub key_grace_period: time::Duration,
+    /// Remote actor lookups by handle.
+    pub resolve_limiter: Arc<RateLimiter>,
+    /// Actors marked stale because a signature claiming to be theirs didn't verify.
+    pub stale_actor_limiter: Arc<RateLimiter<Url>>,
 }
 
 pub type AppState = Arc<State>;
 
+/// How often a signature that doesn't verify can get its claimed actor fetched again. The key
+/// id isn't authenticated, so anyone can send one.
+const STALE_ACTOR_WINDOW: Duration = Duration::from_secs(10 * 60);
+
 impl State {
-    pub async fn new<U: UserDriver + 'static>(
+    pub async fn new<U, L, C>(
         config: &Configuration,
         user_driver: U,
+        listing_driver: L,
+        category_driver: C,
         database: PgPool,
-    ) -> Result<AppState, anyhow::Error> {
+    ) -> Result<AppState, anyhow::Error>
+    where
+        U: UserDriver + 'static,
+        L: ListingDriver + 'static,
+        C: CategoryDriver + 'static,
+    {
         let system_user = get_system_user(&user_driver, config).await?;
         let user = Arc::new(user_driver);
+        let federation = FederationService::new(database.clone());
+        let media = MediaService::new(database.clone());
+        let media_store = config.media.connect().await?;
         let auth = AuthService::new(database, config.server.oauth.0.clone(), Arc::clone(&user))?;
 
         Ok(Arc::new(Self {
             auth: Arc::new(auth),
             user,
+            listing: Arc::new(listing_driver),
+            category: Arc::new(category_driver),
+            media: Arc::new(media),
+            media_store,
+            federation: Arc::new(federation),
             port: config.server.port.into(),
+            frontend_url: config.server.url.clone().into(),
+            max_upload_size: config.media.max_size,
+            admins: config.server.admins.iter().cloned().collect(),
+            allowlist: allowlist(config),
+            actor_stale_after: time::Duration::hours(
+                config.server.actor_refresh.stale_after as i64,
+            ),
+            key_grace_period: time::Duration::hours(config.server.key_rotation.grace_period as i64),
+            resolve_limiter: Arc::new(RateLimiter::new(
+                config.server.resolve_limit.requests,
+                Duration::from_secs(config.server.resolve_limit.window),
+            )),
+            stale_actor_limiter: Arc::new(RateLimiter::new(1, STALE_ACTOR_WINDOW)),
             system_user: Arc::new(system_user),
         }))
     }
 }
 
+impl State {
+    /// Why we refuse to federate with `domain`, `None` if we don't.
+    pub async fn domain_rejection(
+        &self,
+        domain: &str,
+    ) -> Result<Option<&'static str>, FederationError> {
+        let domain = normalize_domain(domain);
+
+        if let Some(allowlist) = &self.allowlist
+            && !domain_and_parents(&domain).any(|domain| allowlist.contains(domain))
+        {
+            return Ok(Some("domain is not allowed"));
+        }
+
+        match self.federation.domain_block_severity(&domain).await? {
+            Some(BlockSeverity::Suspend) => Ok(Some("domain is blocked")),
+            _ => Ok(None),
+        }
+    }
+
+    /// Whether media hosted at `url` must not be fetched or stored.
+    pub async fn rejects_media(&self, url: &Url) -> Result<bool, FederationError> {
+        let Some(domain) = url.host_str() else {
+            return Ok(true);
+        };
+
+        Ok(matches!(
+            self.federation.domain_block_severity(domain).await?,
+            Some(BlockSeverity::RejectMedia | BlockSeverity::Suspend)
+        ))
+    }
+}
+
+fn allowlist(config: &Configuration) -> Option<HashSet<String>> {
+    let federation = &config.server.federation;
+    if federation.mode != FederationMode::Allowlist {
+        return None;
+    }
+
+    // Our own urls go through the same checks.
+    let local = config
+        .server
+        .domain
+        .split(':')
+        .next()
+        .unwrap_or_default()
+        .to_owned();
+
+    let allowlist = federation
+        .allowed_domains
+        .iter()
+        .map(|domain| normalize_domain(domain))
+        .chain([normalize_domain(&local)])
+        .collect();
+
+    Some(allowlist)
+}
+
 pub async fn get_system_user<U>(user: &U, config: &Configuration) -> anyhow::Result<User>
 where
     U: UserDriver,
 {
-    let system_user = if let Some(user) = user.get_user(&config.server.instance_name).await? {
+    let mut system_user = if let Some(user) = user.get_user(&config.server.instance_name).await? {
         user
     } else {
         //create system user
@@ -54,20 +176,27 @@ where
             &config.server.domain,
             &config.server.instance_name,
         )?;
+        let shared_inbox =
+            server::utilities::shared_inbox_url(config.server.port.into(), &config.server.domain)?;
         let data = CreateUser {
             kind: ActorType::Service,
             ap_id: id,
             username: config.server.instance_name.clone(),
             name: None,
             inbox,
+            shared_inbox: Some(shared_inbox),
             avatar: None,
             public_key: keypair.public_key,
             private_key: Some(RedactedSecret::from(keypair.private_key)),
             is_local: true,
+            also_known_as: Vec::new(),
+            moved_to: None,
         };
         user.create_user(&data, None).await?
-    }
-    .into();
+    };
+
+    // The federation library signs fetches with it.
+    system_user.private_key = user.private_key(system_user.id).await?;
 
-    Ok(system_user)
+    Ok(system_user.into())
 }
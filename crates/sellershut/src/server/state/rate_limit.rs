@@ -0,0 +1,61 @@
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use uuid::Uuid;
+
+/// Counts requests per user, or whatever else `K` identifies, in fixed windows.
+///
+/// Kept in memory, so each replica enforces the limit on its own.
+pub struct RateLimiter<K = Uuid> {
+    limit: u32,
+    window: Duration,
+    windows: Mutex<HashMap<K, (Instant, u32)>>,
+}
+
+impl<K: Eq + Hash> RateLimiter<K> {
+    pub fn new(limit: u32, window: Duration) -> Self {
+        Self {
+            limit,
+            window,
+            windows: Default::default(),
+        }
+    }
+
+    /// Count a request by `key`, `false` once it is over the limit for the current window.
+    pub fn check(&self, key: K) -> bool {
+        self.check_at(key, Instant::now())
+    }
+
+    fn check_at(&self, key: K, now: Instant) -> bool {
+        let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+        windows.retain(|_, (started_at, _)| now.duration_since(*started_at) < self.window);
+
+        let (_, count) = windows.entry(key).or_insert((now, 0));
+        *count += 1;
+
+        *count <= self.limit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_per_window() {
+        let limiter = RateLimiter::new(2, Duration::from_secs(60));
+        let user = Uuid::now_v7();
+        let other = Uuid::now_v7();
+        let start = Instant::now();
+
+        assert!(limiter.check_at(user, start));
+        assert!(limiter.check_at(user, start + Duration::from_secs(1)));
+        assert!(!limiter.check_at(user, start + Duration::from_secs(2)));
+        assert!(limiter.check_at(other, start + Duration::from_secs(2)));
+        assert!(limiter.check_at(user, start + Duration::from_secs(60)));
+    }
+}
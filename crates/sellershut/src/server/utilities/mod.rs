@@ -13,6 +13,15 @@ pub fn inbox_url(port: u16, domain: &str, username: &str) -> Result<Url, url::Pa
     base_url(port, domain)?.join(&format!("users/{username}/inbox"))
 }
 
+pub fn shared_inbox_url(port: u16, domain: &str) -> Result<Url, url::ParseError> {
+    base_url(port, domain)?.join("inbox")
+}
+
+/// A fresh id for an activity sent by this instance.
+pub fn activity_url(port: u16, domain: &str) -> Result<Url, url::ParseError> {
+    base_url(port, domain)?.join(&format!("activities/{}", uuid::Uuid::now_v7()))
+}
+
 pub fn users_url(port: u16, domain: &str, username: &str) -> anyhow::Result<Url> {
     if validate_username(username) {
         Ok(base_url(port, domain)?.join(&format!("users/{username}"))?)
@@ -21,6 +30,39 @@ pub fn users_url(port: u16, domain: &str, username: &str) -> anyhow::Result<Url>
     }
 }
 
+pub fn listing_url(port: u16, domain: &str, id: uuid::Uuid) -> Result<Url, url::ParseError> {
+    base_url(port, domain)?.join(&format!("listings/{id}"))
+}
+
+pub fn media_url(port: u16, domain: &str, id: uuid::Uuid) -> Result<Url, url::ParseError> {
+    base_url(port, domain)?.join(&format!("media/{id}"))
+}
+
+pub fn media_thumbnail_url(
+    port: u16,
+    domain: &str,
+    id: uuid::Uuid,
+) -> Result<Url, url::ParseError> {
+    base_url(port, domain)?.join(&format!("media/{id}/thumbnail"))
+}
+
+/// The id of a file uploaded to this instance, `None` for any other url.
+pub fn local_media_id(port: u16, domain: &str, url: &Url) -> Option<uuid::Uuid> {
+    let id = url.path().strip_prefix("/media/")?.parse().ok()?;
+    (media_url(port, domain, id).ok()? == *url).then_some(id)
+}
+
+/// The frontend page showing a local user's profile.
+pub fn profile_url(frontend_url: &Url, username: &str) -> Result<Url, url::ParseError> {
+    frontend_url.join(&format!("@{username}"))
+}
+
+/// Url template clients fill in with a resource to look it up over WebFinger.
+pub fn webfinger_template(port: u16, domain: &str) -> Result<String, url::ParseError> {
+    let url = base_url(port, domain)?.join(".well-known/webfinger")?;
+    Ok(format!("{url}?resource={{uri}}"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -41,6 +83,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn check_shared_inbox_url() {
+        assert_eq!(
+            shared_inbox_url(8080, "example.com").unwrap().as_str(),
+            "http://localhost:8080/inbox"
+        );
+    }
+
+    #[test]
+    fn check_activity_url() {
+        let first = activity_url(8080, "example.com").unwrap();
+        let second = activity_url(8080, "example.com").unwrap();
+
+        assert!(
+            first
+                .as_str()
+                .starts_with("http://localhost:8080/activities/")
+        );
+        assert_ne!(first, second);
+    }
+
     #[test]
     fn check_users_url() {
         assert_eq!(
@@ -56,4 +119,72 @@ mod tests {
             "http://localhost:8080/users/alice"
         );
     }
+
+    #[test]
+    fn check_listing_url() {
+        let id = uuid::Uuid::now_v7();
+        assert_eq!(
+            listing_url(8080, "example.com", id).unwrap().as_str(),
+            format!("http://localhost:8080/listings/{id}")
+        );
+    }
+
+    #[test]
+    fn check_media_url() {
+        let id = uuid::Uuid::now_v7();
+        assert_eq!(
+            media_url(8080, "example.com", id).unwrap().as_str(),
+            format!("http://localhost:8080/media/{id}")
+        );
+    }
+
+    #[test]
+    fn check_media_thumbnail_url() {
+        let id = uuid::Uuid::now_v7();
+        assert_eq!(
+            media_thumbnail_url(8080, "example.com", id)
+                .unwrap()
+                .as_str(),
+            format!("http://localhost:8080/media/{id}/thumbnail")
+        );
+    }
+
+    #[track_caller]
+    fn check_local_media_id(input: &str, expected_result: bool) {
+        let url = Url::parse(input).unwrap();
+        assert_eq!(
+            expected_result,
+            local_media_id(8080, "example.com", &url).is_some()
+        );
+    }
+
+    #[test]
+    fn local_media_ids() {
+        let id = uuid::Uuid::now_v7();
+        check_local_media_id(&format!("http://localhost:8080/media/{id}"), true);
+        check_local_media_id(
+            &format!("http://localhost:8080/media/{id}/thumbnail"),
+            false,
+        );
+        check_local_media_id(&format!("http://localhost:9090/media/{id}"), false);
+        check_local_media_id(&format!("https://other.hut/media/{id}"), false);
+        check_local_media_id("http://localhost:8080/media/avatar.png", false);
+    }
+
+    #[test]
+    fn check_profile_url() {
+        let frontend = Url::parse("http://localhost:5173").unwrap();
+        assert_eq!(
+            profile_url(&frontend, "alice").unwrap().as_str(),
+            "http://localhost:5173/@alice"
+        );
+    }
+
+    #[test]
+    fn check_webfinger_template() {
+        assert_eq!(
+            webfinger_template(8080, "example.com").unwrap(),
+            "http://localhost:8080/.well-known/webfinger?resource={uri}"
+        );
+    }
 }
@@ -4,12 +4,27 @@ use super::*;
 
 use std::sync::OnceLock;
 
+use activitypub_federation::config::Data;
+
 use sqlx::PgPool;
 use tracing_subscriber::{EnvFilter, Registry, layer::SubscriberExt, reload};
 
 static TEST_LOG_DATA: OnceLock<LogHandle> = OnceLock::new();
 
 pub async fn test_app(pool: PgPool) -> axum::Router {
+    let (federation_config, config) = test_federation_config(pool).await;
+
+    server::router::router(federation_config, config)
+        .await
+        .expect("test router")
+}
+
+pub async fn test_data(pool: PgPool) -> Data<AppState> {
+    let (federation_config, _) = test_federation_config(pool).await;
+    federation_config.to_request_data()
+}
+
+async fn test_federation_config(pool: PgPool) -> (FederationConfig<AppState>, Configuration) {
     let _log_handle = TEST_LOG_DATA
         .get_or_init(|| {
             let filter = EnvFilter::new("warn");
@@ -23,12 +38,22 @@ pub async fn test_app(pool: PgPool) -> axum::Router {
         })
         .clone();
 
-    let config = Configuration::default();
+    let mut config = Configuration::default();
+    config.media.filesystem = Some(sellershut_media::config::FilesystemConfig {
+        path: std::env::temp_dir().join("sellershut-test-media"),
+    });
     let cache = Cache::connect(&config.cache).await.unwrap();
-    let user_driver = UserService::new(pool.clone(), cache);
-    let state = State::new(&config, user_driver, pool).await.unwrap();
+    let keyring = Keyring::new(Some(&Keyring::generate_master_key()), &[]).unwrap();
+    let user_driver = UserService::new(pool.clone(), cache.clone(), keyring);
+    let listing_driver = ListingService::new(pool.clone(), cache.clone());
+    let category_driver = CategoryService::new(pool.clone(), cache);
+    let state = State::new(&config, user_driver, listing_driver, category_driver, pool)
+        .await
+        .unwrap();
 
-    server::router::router(state, config)
+    let federation_config = server::router::federation_config(state, &config)
         .await
-        .expect("test router")
+        .expect("federation config");
+
+    (federation_config, config)
 }
@@ -0,0 +1,6 @@
+alter table "user" add column shared_inbox text;
+
+-- Local actors share the instance inbox at the root of their origin.
+update "user"
+set shared_inbox = substring(ap_id from '^https?://[^/]+') || '/inbox'
+where is_local;
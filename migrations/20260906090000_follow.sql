@@ -0,0 +1,18 @@
+create type follow_state as enum (
+    'Pending',
+    'Accepted'
+);
+
+create table follow (
+    follower_id uuid not null references "user"(id) on delete cascade,
+    followee_id uuid not null references "user"(id) on delete cascade,
+    state follow_state not null default 'Pending',
+    -- id of the Follow activity, needed to answer it with Accept/Reject or take it back with Undo
+    activity_id text not null unique,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz not null default now(),
+    primary key (follower_id, followee_id),
+    constraint follow_not_self check (follower_id <> followee_id)
+);
+
+create index follow_followee_id_idx on follow(followee_id);
@@ -0,0 +1,2 @@
+-- lets a user keep their followers and following lists private
+alter table "user" add column hide_network boolean not null default false;
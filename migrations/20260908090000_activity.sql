@@ -0,0 +1,12 @@
+create table activity (
+    id uuid primary key,
+    ap_id text not null unique,
+    actor_id uuid not null references "user"(id) on delete cascade,
+    kind text not null,
+    data jsonb not null,
+    -- addressed to the Public collection, and therefore listed in the actor's outbox
+    is_public boolean not null,
+    created_at timestamptz not null default now()
+);
+
+create index idx_activity_outbox on activity(actor_id, created_at desc) where is_public = true;
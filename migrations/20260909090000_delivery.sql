@@ -0,0 +1,23 @@
+create table delivery (
+    id uuid primary key,
+    inbox text not null,
+    -- the activity is signed with this actor's key
+    actor_id uuid not null references "user"(id) on delete cascade,
+    activity_id text not null,
+    payload jsonb not null,
+    attempts integer not null default 0,
+    next_attempt_at timestamptz not null default now(),
+    -- set while a worker is sending the delivery
+    locked_until timestamptz,
+    last_error text,
+    created_at timestamptz not null default now()
+);
+
+create index idx_delivery_next_attempt_at on delivery(next_attempt_at);
+
+-- inboxes that kept failing until a delivery to them was given up
+create table dead_inbox (
+    inbox text primary key,
+    last_error text,
+    dead_at timestamptz not null default now()
+);
@@ -0,0 +1,19 @@
+create table instance (
+    domain text primary key,
+    software_name text,
+    software_version text,
+    last_success_at timestamptz,
+    last_failure_at timestamptz,
+    consecutive_failures integer not null default 0,
+    -- deliveries to the instance are skipped until then
+    suspended_until timestamptz,
+    last_probed_at timestamptz,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz not null default now()
+);
+
+alter table delivery add column domain text;
+update delivery set domain = substring(inbox from '^[a-zA-Z][a-zA-Z0-9+.-]*://([^/?#]+)');
+alter table delivery alter column domain set not null;
+
+create index idx_delivery_domain on delivery(domain);
@@ -0,0 +1,15 @@
+create type block_severity as enum (
+    'Silence',
+    'Suspend',
+    'RejectMedia'
+);
+
+create table domain_block (
+    -- lowercase host, blocks its subdomains as well
+    domain text primary key,
+    severity block_severity not null,
+    -- shown to anyone asking why the domain is blocked
+    public_reason text,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz not null default now()
+);
@@ -0,0 +1,2 @@
+-- remote actors are refreshed oldest first
+create index idx_user_remote_refresh on "user"(last_refreshed_at) where not is_local;
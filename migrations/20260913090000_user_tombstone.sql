@@ -0,0 +1 @@
+alter table "user" add column deleted_at timestamptz;
@@ -0,0 +1,3 @@
+alter table "user"
+    add column also_known_as text[] not null default '{}',
+    add column moved_to text;
@@ -0,0 +1,10 @@
+-- public keys replaced by a rotation, still served until they expire
+create table retired_key (
+    id uuid primary key,
+    user_id uuid not null references "user"(id) on delete cascade,
+    public_key text not null,
+    retired_at timestamptz not null default now(),
+    expires_at timestamptz not null
+);
+
+create index retired_key_user_id_idx on retired_key(user_id);
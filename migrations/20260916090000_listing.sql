@@ -0,0 +1,34 @@
+create type listing_status as enum (
+    'Draft',
+    'Active',
+    'Sold',
+    'Archived'
+);
+
+create type listing_condition as enum (
+    'New',
+    'LikeNew',
+    'Good',
+    'Fair',
+    'Poor'
+);
+
+create table listing (
+    id uuid primary key,
+    ap_id text not null unique,
+    owner_id uuid not null references "user"(id) on delete cascade,
+    title text not null,
+    description text not null default '',
+    -- in the currency's minor unit
+    price bigint not null check (price >= 0),
+    currency text not null check (currency ~ '^[A-Z]{3}$'),
+    condition listing_condition not null,
+    status listing_status not null default 'Draft',
+    is_local boolean not null,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz not null default now(),
+    published_at timestamptz
+);
+
+create index listing_owner_id_idx on listing(owner_id, created_at desc);
+create index listing_local_active_idx on listing(is_local) where status = 'Active';
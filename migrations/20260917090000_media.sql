@@ -0,0 +1,15 @@
+create table media (
+    id uuid primary key,
+    owner_id uuid not null references "user"(id) on delete cascade,
+    content_type text not null,
+    size bigint not null check (size >= 0),
+    storage_key text not null unique,
+    description text,
+    -- unattached uploads are not used anywhere yet
+    listing_id uuid references listing(id) on delete set null,
+    position integer not null default 0,
+    created_at timestamptz not null default now()
+);
+
+create index media_owner_id_idx on media(owner_id);
+create index media_listing_id_idx on media(listing_id, position) where listing_id is not null;
@@ -0,0 +1,14 @@
+create type media_status as enum ('Pending', 'Ready', 'Failed');
+
+-- files uploaded before processing existed are processed like new ones
+alter table media
+    add column status media_status not null default 'Pending',
+    add column width integer,
+    add column height integer,
+    add column blurhash text,
+    add column thumbnail_key text unique,
+    add column attempts integer not null default 0,
+    add column locked_until timestamptz,
+    add column last_error text;
+
+create index media_pending_idx on media(created_at) where status = 'Pending';
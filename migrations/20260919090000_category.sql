@@ -0,0 +1,25 @@
+create table category (
+    id uuid primary key,
+    -- categories with children can't be deleted
+    parent_id uuid references category(id) on delete restrict,
+    slug text not null unique check (slug ~ '^[a-z0-9]+(-[a-z0-9]+)*$'),
+    position integer not null default 0,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz not null default now(),
+    check (parent_id <> id)
+);
+
+create index category_parent_id_idx on category(parent_id);
+
+create table category_name (
+    category_id uuid not null references category(id) on delete cascade,
+    locale text not null,
+    name text not null,
+    primary key (category_id, locale)
+);
+
+alter table listing
+    add column category_id uuid references category(id) on delete set null;
+
+create index listing_category_active_idx on listing(category_id, published_at desc)
+    where status = 'Active';
@@ -0,0 +1,8 @@
+-- an amount in the currency's minor unit, and the ISO 4217 code of the currency
+create type money_amount as (
+    amount bigint,
+    currency text
+);
+
+-- the largest amount that fits in a javascript number
+alter table listing add constraint listing_price_max check (price <= 9007199254740991);
@@ -0,0 +1,11 @@
+create extension if not exists pg_trgm;
+
+-- the simple configuration doesn't stem, listings are written in many languages
+alter table listing add column search tsvector not null generated always as (
+    setweight(to_tsvector('simple', title), 'A')
+    || setweight(to_tsvector('simple', description), 'B')
+) stored;
+
+create index listing_search_idx on listing using gin (search);
+-- for finding titles with typos in them
+create index listing_title_trgm_idx on listing using gin (title gin_trgm_ops);
@@ -0,0 +1,4 @@
+-- when the current run of failures started, null while the instance is reachable
+alter table instance add column first_failure_at timestamptz;
+
+update instance set first_failure_at = last_failure_at where consecutive_failures > 0;
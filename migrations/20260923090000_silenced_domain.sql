@@ -0,0 +1,17 @@
+-- whether the host of `url`, or a domain above it, is silenced or suspended and its content
+-- kept out of public listings
+create function domain_silenced(url text) returns boolean
+language sql stable as $$
+    select exists (
+        select 1
+        from domain_block, (
+            select rtrim(lower(substring(url from '^[a-zA-Z][a-zA-Z0-9+.-]*://(?:[^@/?#]*@)?([^:/?#]+)')), '.') as host
+        ) as target
+        where
+            domain_block.severity in ('Silence', 'Suspend')
+            and (
+                target.host = domain_block.domain
+                or right(target.host, length(domain_block.domain) + 1) = '.' || domain_block.domain
+            )
+    )
+$$;
@@ -0,0 +1,5 @@
+-- files of listings from other instances stay on their servers, only where they are is kept
+alter table media
+    alter column storage_key drop not null,
+    add column remote_url text,
+    add constraint media_stored_or_remote check ((storage_key is null) <> (remote_url is null));